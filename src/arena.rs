@@ -0,0 +1,138 @@
+use std::{collections::BTreeSet, path::PathBuf};
+
+use bevy_ecs::{bundle::Bundle, system::SystemParam};
+use valence::{anvil::AnvilLevel, prelude::*};
+
+use crate::{
+    base::{build::PlayerPlacedBlocks, chests::ChestState},
+    bedwars_config::WIPWorldConfig,
+    lobby::LobbyPlayerState,
+    maps::{LoadedMap, MapRotation, MapShop},
+    GameState,
+};
+
+/// An arena runs its own bedwars game (lobby -> match -> post match).
+///
+/// The arena entity is the same entity as its `ChunkLayer` and `EntityLayer`,
+/// so players and every other entity in the arena are linked to it through their `EntityLayerId`.
+/// All per-arena state (world config, match state, placed blocks, chests, ...) is stored on this entity.
+#[derive(Debug, Component)]
+pub struct Arena {
+    /// Name of the arena
+    pub name: String,
+    /// Path the world config of this arena is loaded from and saved to
+    pub config_path: PathBuf,
+}
+
+#[derive(Bundle)]
+pub struct ArenaBundle {
+    pub arena: Arena,
+    pub state: GameState,
+    pub lobby_state: LobbyPlayerState,
+    pub player_placed_blocks: PlayerPlacedBlocks,
+    pub chest_state: ChestState,
+}
+
+impl ArenaBundle {
+    pub fn new(name: String, config_path: PathBuf, state: GameState) -> Self {
+        Self {
+            arena: Arena { name, config_path },
+            state,
+            lobby_state: LobbyPlayerState::default(),
+            player_placed_blocks: PlayerPlacedBlocks::default(),
+            chest_state: ChestState::default(),
+        }
+    }
+}
+
+/// Resources needed to create the world of an arena
+#[derive(SystemParam)]
+pub struct LayerResources<'w> {
+    server: Res<'w, Server>,
+    biomes: Res<'w, BiomeRegistry>,
+    dimensions: Res<'w, DimensionTypeRegistry>,
+}
+
+/// Spawn an arena playing the current map of the rotation
+pub fn spawn_arena(
+    commands: &mut Commands,
+    layer_resources: &LayerResources,
+    name: String,
+    rotation: MapRotation,
+    state: GameState,
+    map: LoadedMap,
+) -> Entity {
+    let current_map = rotation.current();
+
+    let layer = LayerBundle::new(
+        ident!("overworld"),
+        &layer_resources.dimensions,
+        &layer_resources.biomes,
+        &layer_resources.server,
+    );
+    let level = AnvilLevel::new(&current_map.world_dir, &layer_resources.biomes);
+
+    let wip_config = map
+        .config
+        .as_ref()
+        .map(WIPWorldConfig::from_saved_config)
+        .unwrap_or_default();
+
+    let mut arena = commands.spawn((
+        layer,
+        level,
+        ArenaBundle::new(name, current_map.config_path.clone(), state),
+        wip_config,
+    ));
+
+    if let Some(config) = map.config {
+        if state != GameState::Edit {
+            arena.insert(config);
+        }
+    }
+
+    if let Some(shop) = map.shop {
+        arena.insert(MapShop(shop));
+    }
+
+    arena.insert(rotation);
+    arena.id()
+}
+
+/// Move a player into the world of another arena
+pub fn move_to_arena(commands: &mut Commands, player: Entity, arena: Entity) {
+    commands.entity(player).insert((
+        EntityLayerId(arena),
+        VisibleChunkLayer(arena),
+        VisibleEntityLayers(BTreeSet::from([arena])),
+    ));
+}
+
+/// Run condition that is true if at least one arena is in the given state.
+/// Systems using this still have to check the state of the arena they act on.
+pub fn any_arena_in(state: GameState) -> impl FnMut(Query<&GameState>) -> bool + Clone {
+    move |arenas: Query<&GameState>| arenas.iter().any(|arena_state| *arena_state == state)
+}
+
+/// Whether the arena exists and is in the given state
+pub fn arena_in_state(arenas: &Query<&GameState>, arena: Entity, state: GameState) -> bool {
+    arenas
+        .get(arena)
+        .is_ok_and(|arena_state| *arena_state == state)
+}
+
+/// Pick the arena a newly joined player will be sent to.
+/// Lobbies with the most waiting players are preferred (so matches fill up),
+/// then arenas in edit mode, then running matches (the player will spectate).
+pub fn pick_arena(
+    arenas: &Query<(Entity, &GameState, &LobbyPlayerState), With<Arena>>,
+) -> Option<(Entity, GameState)> {
+    arenas
+        .iter()
+        .max_by_key(|(_, state, lobby_state)| match state {
+            GameState::Lobby => (2, lobby_state.player_count()),
+            GameState::Edit => (1, 0),
+            GameState::Match | GameState::PostMatch => (0, 0),
+        })
+        .map(|(arena, state, _)| (arena, *state))
+}
@@ -17,6 +17,7 @@ use crate::{
         enchantments::{Enchantment, ItemStackExtEnchantments},
         physics::TerminalVelocity,
     },
+    items::custom_item::CustomItems,
     utils::{despawn_timer::DespawnTimer, inventory::InventoryExt},
 };
 
@@ -106,6 +107,7 @@ fn on_bow_draw(
     mut commands: Commands,
     mut clients: Query<(&Inventory, &HeldItem, &mut LivingFlags)>,
     mut events: EventReader<InteractItemEvent>,
+    custom_items: Res<CustomItems>,
 ) {
     for event in events.read() {
         let Ok((inventory, held_item, mut flags)) = clients.get_mut(event.client) else {
@@ -116,6 +118,8 @@ fn on_bow_draw(
         let stack = inventory.slot(held_item.slot()).clone();
         if !inventory.check_contains_stack(&ItemStack::new(ItemKind::Arrow, 1, None), true)
             || stack.item != ItemKind::Bow
+            // Custom bows like the laser bow shoot on their own
+            || custom_items.of_stack(&stack).is_some()
         {
             continue;
         }
@@ -137,34 +141,45 @@ pub struct BowShootEvent {
 }
 
 fn on_bow_release(
+    mut commands: Commands,
     clients: Query<(&BowState, &Position, &Look, &Inventory, &HeldItem)>,
     mut packet_events: EventReader<PacketEvent>,
     mut event_writer: EventWriter<BowShootEvent>,
+    custom_items: Res<CustomItems>,
 ) {
     for packet in packet_events.read() {
         let Some(player_action) = packet.decode::<PlayerActionC2s>() else {
             continue;
         };
 
+        if player_action.action != PlayerAction::ReleaseUseItem {
+            continue;
+        }
+
         let Ok((bow_state, position, look, inventory, held_item)) = clients.get(packet.client)
         else {
             continue;
         };
 
+        // The draw ends with every release, so the next draw starts from zero
+        commands.entity(packet.client).remove::<BowState>();
+
         let selected = inventory.slot(held_item.slot()).clone();
-        if player_action.action != PlayerAction::ReleaseUseItem || selected.item != ItemKind::Bow {
+        if selected.item != ItemKind::Bow
+            // Custom bows like the laser bow shoot on their own
+            || custom_items.of_stack(&selected).is_some()
+        {
             continue;
         }
 
         let ms_drawn = bow_state.start_draw_tick.elapsed().as_millis() as u64;
-        let stack = inventory.slot(held_item.slot()).clone();
 
         event_writer.send(BowShootEvent {
             client: packet.client,
             position: *position,
             look: *look,
             ms_drawn,
-            bow_used: stack,
+            bow_used: selected,
         });
     }
 }
@@ -180,7 +195,14 @@ fn on_shoot(
             continue;
         }
 
-        let mut layer = layer.single_mut();
+        let Ok((mut shooter_inv, layer_id, combat_state)) = shooter.get_mut(event.client) else {
+            continue;
+        };
+
+        let Ok(mut layer) = layer.get_mut(layer_id.0) else {
+            continue;
+        };
+
         let sound_pitch = rand::thread_rng().gen_range(0.75..=1.125);
 
         layer.play_sound(
@@ -200,10 +222,6 @@ fn on_shoot(
             yaw.cos() * pitch.cos(),
         );
 
-        let Ok((mut shooter_inv, layer_id, combat_state)) = shooter.get_mut(event.client) else {
-            continue;
-        };
-
         if !event
             .bow_used
             .enchantments()
@@ -258,15 +276,20 @@ fn on_shoot(
 
 #[allow(clippy::type_complexity)]
 fn on_arrow_fly(
-    query: Query<(&Position, &OnGround, &ArrowPower), (With<ArrowOwner>, With<ArrowPower>)>,
+    query: Query<
+        (&Position, &OnGround, &ArrowPower, &EntityLayerId),
+        (With<ArrowOwner>, With<ArrowPower>),
+    >,
     mut layer: Query<&mut ChunkLayer>,
 ) {
-    for (position, on_ground, power) in query.iter() {
+    for (position, on_ground, power, layer_id) in query.iter() {
         if on_ground.0 {
             continue;
         }
 
-        let mut layer = layer.single_mut();
+        let Ok(mut layer) = layer.get_mut(layer_id.0) else {
+            continue;
+        };
 
         if power.is_critical() {
             layer.play_particle(&Particle::Crit, true, position.0, Vec3::ZERO, 0.1, 1);
@@ -275,12 +298,12 @@ fn on_arrow_fly(
 }
 
 fn on_hit_block(
-    mut arrows: Query<&mut OnGround, (With<ArrowOwner>, With<ArrowPower>)>,
+    mut arrows: Query<(&mut OnGround, &EntityLayerId), (With<ArrowOwner>, With<ArrowPower>)>,
     mut events: EventReader<EntityBlockCollisionEvent>,
     mut layer: Query<&mut ChunkLayer>,
 ) {
     for event in events.read() {
-        let Ok(mut on_ground) = arrows.get_mut(event.entity) else {
+        let Ok((mut on_ground, layer_id)) = arrows.get_mut(event.entity) else {
             continue;
         };
 
@@ -290,7 +313,9 @@ fn on_hit_block(
 
         on_ground.0 = true;
 
-        let mut layer = layer.single_mut();
+        let Ok(mut layer) = layer.get_mut(layer_id.0) else {
+            continue;
+        };
 
         layer.play_sound(
             Sound::EntityArrowHit,
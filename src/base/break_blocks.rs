@@ -1,6 +1,5 @@
 use action::{DiggingEvent, DiggingState};
 use app::{App, Plugin, Update};
-use bevy_state::prelude::in_state;
 use client::Username;
 use entity::{
     entity::NoGravity,
@@ -8,11 +7,14 @@ use entity::{
     EntityLayerId, Position, Velocity,
 };
 use math::{DVec3, Vec3};
-use prelude::{Commands, Entity, Event, EventReader, EventWriter, IntoSystemConfigs, Query, Res};
+use prelude::{Commands, Entity, Event, EventReader, EventWriter, IntoSystemConfigs, Query};
 use rand::Rng;
 use valence::*;
 
-use crate::{bedwars_config::WorldConfig, utils::despawn_timer::DespawnTimer, GameState, Team};
+use crate::{
+    arena::any_arena_in, bedwars_config::WorldConfig, utils::despawn_timer::DespawnTimer,
+    GameState, Team,
+};
 
 use super::{
     build::PlayerPlacedBlocks,
@@ -26,40 +28,59 @@ pub struct BlockBreakPlugin;
 
 #[derive(Debug, Event, PartialEq)]
 pub struct BedDestroyedEvent {
-    pub attacker: Entity,
+    /// The arena the bed was destroyed in
+    pub arena: Entity,
+    /// `None` if the bed was destroyed by the match schedule
+    pub attacker: Option<Entity>,
     pub team: Team,
 }
 
 impl Plugin for BlockBreakPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, (break_blocks,).run_if(in_state(GameState::Match)))
-            .add_event::<BedDestroyedEvent>();
+        app.add_systems(
+            Update,
+            (break_blocks,).run_if(any_arena_in(GameState::Match)),
+        )
+        .add_event::<BedDestroyedEvent>();
     }
 }
 
 #[allow(clippy::too_many_arguments)]
 fn break_blocks(
     mut commands: Commands,
-    clients: Query<(&Username, &Team)>,
+    clients: Query<(&Username, &Team, &EntityLayerId)>,
     mut events: EventReader<DiggingEvent>,
-    mut layer: Query<(Entity, &mut ChunkLayer)>,
-    player_placed_blocks: Res<PlayerPlacedBlocks>,
-    bedwars_config: Res<WorldConfig>,
+    mut arenas: Query<(
+        Entity,
+        &GameState,
+        &mut ChunkLayer,
+        &PlayerPlacedBlocks,
+        &WorldConfig,
+    )>,
     // match_state: ResMut<MatchState>,
     mut event_writer: EventWriter<BedDestroyedEvent>,
 ) {
     for event in events.read() {
-        let (layer, mut layer_mut) = layer.single_mut();
         if event.state != DiggingState::Stop {
             continue;
         }
 
         let block_pos = event.position;
 
-        let Ok((_player_name, player_team)) = clients.get(event.client) else {
+        let Ok((_player_name, player_team, layer_id)) = clients.get(event.client) else {
+            continue;
+        };
+
+        let Ok((layer, state, mut layer_mut, player_placed_blocks, bedwars_config)) =
+            arenas.get_mut(layer_id.0)
+        else {
             continue;
         };
 
+        if *state != GameState::Match {
+            continue;
+        }
+
         let mut broke_bed = false;
 
         for (team_name, bed_block_set) in &bedwars_config.beds {
@@ -73,9 +94,13 @@ fn break_blocks(
                 z: block_pos.z,
             };
 
-            if bed_block_set.iter().any(|(pos, _)| pos == &block_pos_vec) {
+            if bed_block_set
+                .iter()
+                .any(|bed_block| bed_block.pos == block_pos_vec)
+            {
                 // set bed to broken
-                for (pos, _block) in bed_block_set {
+                for bed_block in bed_block_set {
+                    let pos = &bed_block.pos;
                     layer_mut.set_block(BlockPos::new(pos.x, pos.y, pos.z), BlockState::AIR);
                 }
 
@@ -83,7 +108,8 @@ fn break_blocks(
                     bedwars_config.teams.get_key_value(team_name).unwrap();
 
                 event_writer.send(BedDestroyedEvent {
-                    attacker: event.client,
+                    arena: layer,
+                    attacker: Some(event.client),
                     team: Team {
                         name: victim_team.clone(),
                         color: *victim_color,
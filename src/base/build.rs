@@ -1,38 +1,37 @@
 use std::collections::HashMap;
 
-use bevy_state::prelude::in_state;
 use valence::{
     entity::living::LivingEntity, interact_block::InteractBlockEvent, inventory::HeldItem,
     math::Aabb, prelude::*,
 };
 
-use crate::GameState;
+use crate::{arena::any_arena_in, items::custom_item::CustomItems, GameState};
 
 pub struct BuildPlugin;
 
-#[derive(Debug, Default, Resource)]
+/// Blocks placed by players in an arena, stored on the arena entity
+#[derive(Debug, Default, Component)]
 pub struct PlayerPlacedBlocks(pub HashMap<BlockPos, BlockState>);
 
 impl Plugin for BuildPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
             FixedPreUpdate,
-            (place_blocks,).run_if(in_state(GameState::Match)),
-        )
-        .insert_resource(PlayerPlacedBlocks::default());
+            (place_blocks,).run_if(any_arena_in(GameState::Match)),
+        );
     }
 }
 
 fn place_blocks(
-    mut clients: Query<(&mut Inventory, &HeldItem)>,
-    entities: Query<&Hitbox, With<LivingEntity>>,
-    mut layers: Query<&mut ChunkLayer>,
+    mut clients: Query<(&mut Inventory, &HeldItem, &EntityLayerId)>,
+    entities: Query<(&Hitbox, &EntityLayerId), With<LivingEntity>>,
+    mut arenas: Query<(&GameState, &mut ChunkLayer, &mut PlayerPlacedBlocks)>,
     mut events: EventReader<InteractBlockEvent>,
+    custom_items: Res<CustomItems>,
     // bedwars_config: Res<bedwars_config::BedwarsConfig>,
-    mut player_placed_blocks: ResMut<PlayerPlacedBlocks>,
 ) {
     for event in events.read() {
-        let Ok((mut inventory, held)) = clients.get_mut(event.client) else {
+        let Ok((mut inventory, held, layer_id)) = clients.get_mut(event.client) else {
             continue;
         };
 
@@ -40,7 +39,13 @@ fn place_blocks(
             continue;
         }
 
-        let mut layer = layers.single_mut();
+        let Ok((state, mut layer, mut player_placed_blocks)) = arenas.get_mut(layer_id.0) else {
+            continue;
+        };
+
+        if *state != GameState::Match {
+            continue;
+        }
         // get the held item
         let slot_id = held.slot();
         let stack = inventory.slot(slot_id);
@@ -48,7 +53,7 @@ fn place_blocks(
             continue;
         };
 
-        let Some(block_kind) = BlockKind::from_item_kind(stack.item) else {
+        let Some(block_kind) = placed_block(stack, &custom_items) else {
             continue;
         };
 
@@ -76,8 +81,8 @@ fn place_blocks(
             );
 
             // TODO: also very inefficient
-            for entity_hitbox in entities.iter() {
-                if block_hitbox.intersects(**entity_hitbox) {
+            for (entity_hitbox, entity_layer_id) in entities.iter() {
+                if entity_layer_id == layer_id && block_hitbox.intersects(**entity_hitbox) {
                     return;
                 }
             }
@@ -103,3 +108,12 @@ fn place_blocks(
         layer.set_block(real_pos, state);
     }
 }
+
+/// The block a stack is placed as, custom items are used instead of placed
+pub(crate) fn placed_block(stack: &ItemStack, custom_items: &CustomItems) -> Option<BlockKind> {
+    if custom_items.of_stack(stack).is_some() {
+        return None;
+    }
+
+    BlockKind::from_item_kind(stack.item)
+}
@@ -1,11 +1,7 @@
 use bevy_ecs::query::QueryData;
-use bevy_state::prelude::in_state;
 use valence::prelude::*;
 
-use crate::{
-    r#match::{EndMatch, POST_MATCH_TIME_SECS},
-    GameState, Team,
-};
+use crate::{arena::any_arena_in, bedwars_config::GameRules, r#match::EndMatch, GameState, Team};
 
 use super::{
     break_blocks::BedDestroyedEvent,
@@ -19,7 +15,7 @@ impl Plugin for ChatPlugin {
         app.add_systems(
             Update,
             (
-                (handle_death_message, handle_bed_destroyed).run_if(in_state(GameState::Match)),
+                (handle_death_message, handle_bed_destroyed).run_if(any_arena_in(GameState::Match)),
                 handle_match_end,
             ),
         );
@@ -32,6 +28,7 @@ struct ChatQuery {
     client: &'static mut Client,
     username: &'static Username,
     team: &'static Team,
+    layer: &'static EntityLayerId,
 }
 
 fn handle_death_message(
@@ -43,6 +40,7 @@ fn handle_death_message(
         let Ok(victim) = chat_query.get(event.victim) else {
             continue;
         };
+        let arena = victim.layer.0;
 
         let msg = if let Some(attacker) = event.attacker {
             let Ok(attacker) = chat_query.get(attacker) else {
@@ -65,7 +63,9 @@ fn handle_death_message(
         };
 
         for mut client in &mut chat_query {
-            client.client.send_chat_message(&msg);
+            if client.layer.0 == arena {
+                client.client.send_chat_message(&msg);
+            }
         }
     }
 
@@ -73,6 +73,7 @@ fn handle_death_message(
         let Ok(victim) = chat_query.get(event.victim) else {
             continue;
         };
+        let arena = victim.layer.0;
 
         let msg = if let Some(attacker) = event.attacker {
             let Ok(attacker) = chat_query.get(attacker) else {
@@ -95,7 +96,9 @@ fn handle_death_message(
         };
 
         for mut client in &mut chat_query {
-            client.client.send_chat_message(&msg);
+            if client.layer.0 == arena {
+                client.client.send_chat_message(&msg);
+            }
         }
     }
 }
@@ -110,27 +113,38 @@ fn handle_bed_destroyed(mut clients: Query<ChatQuery>, mut events: EventReader<B
         );
 
         for mut client in &mut clients {
-            client.client.send_chat_message(&msg);
+            if client.layer.0 == event.arena {
+                client.client.send_chat_message(&msg);
+            }
         }
     }
 }
 
-fn handle_match_end(mut clients: Query<ChatQuery>, mut events: EventReader<EndMatch>) {
+fn handle_match_end(
+    mut clients: Query<ChatQuery>,
+    mut events: EventReader<EndMatch>,
+    rules: Res<GameRules>,
+) {
     for event in events.read() {
-        let team = &event.winner;
-
-        let msg = format!(
-            "§bTeam {}§n{}§r §bwon the match!",
-            team.color.text_color(),
-            team.name
-        );
+        let msg = match &event.winner {
+            Some(team) => format!(
+                "§bTeam {}§n{}§r §bwon the match!",
+                team.color.text_color(),
+                team.name
+            ),
+            None => "§bThe match ended in a draw!".to_string(),
+        };
 
         let return_to_lobby_msg = format!(
             "§eReturning to lobby in {} seconds...",
-            POST_MATCH_TIME_SECS.round() as i32
+            rules.post_match_time_secs.round() as i32
         );
 
         for mut client in &mut clients {
+            if client.layer.0 != event.arena {
+                continue;
+            }
+
             tracing::debug!("Sending chat message to {}", client.username.0);
             client.client.send_chat_message(&msg);
             client.client.send_chat_message(&return_to_lobby_msg);
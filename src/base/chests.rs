@@ -13,13 +13,12 @@ pub struct ChestPlugin;
 
 impl Plugin for ChestPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, on_chest_open)
-            .insert_resource(ChestState::default())
-            .observe(chest_close);
+        app.add_systems(Update, on_chest_open).observe(chest_close);
     }
 }
 
-#[derive(Resource, Default)]
+/// Contents of the chests in an arena, stored on the arena entity
+#[derive(Component, Default)]
 pub struct ChestState {
     /// Position of chest -> (Inventory, number of players looking into the chest)
     pub chests: HashMap<BlockPos, (Inventory, u32)>,
@@ -36,22 +35,24 @@ struct PlayerChestState {
 fn on_chest_open(
     mut commands: Commands,
     mut events: EventReader<InteractBlockEvent>,
-    mut players: Query<(Entity, &Username)>,
-    mut layer: Query<&mut ChunkLayer>,
-    mut chest_state: ResMut<ChestState>,
+    mut players: Query<(Entity, &Username, &EntityLayerId)>,
+    mut arenas: Query<(&mut ChunkLayer, &mut ChestState)>,
 ) {
     for event in events.read() {
-        let mut layer = layer.single_mut();
-        let Some(block) = layer.block(event.position) else {
+        let Ok((player, player_name, layer_id)) = players.get_mut(event.client) else {
             continue;
         };
 
-        let block = block.into_block();
+        let Ok((mut layer, mut chest_state)) = arenas.get_mut(layer_id.0) else {
+            continue;
+        };
 
-        let Ok((player, player_name)) = players.get_mut(event.client) else {
+        let Some(block) = layer.block(event.position) else {
             continue;
         };
 
+        let block = block.into_block();
+
         let default_inv = Inventory::new(InventoryKind::Generic9x3);
 
         let ((inventory, mut players_looking_into_chest), sound) = match block.state.to_kind() {
@@ -122,12 +123,17 @@ fn on_chest_open(
 fn chest_close(
     trigger: Trigger<OnRemove, OpenInventory>,
     mut commands: Commands,
-    players: Query<(Entity, &PlayerChestState, &OpenInventory, &Username)>,
+    players: Query<(
+        Entity,
+        &PlayerChestState,
+        &OpenInventory,
+        &Username,
+        &EntityLayerId,
+    )>,
     inventories: Query<&Inventory, Without<PlayerChestState>>,
-    mut chest_state: ResMut<ChestState>,
-    mut layer: Query<&mut ChunkLayer>,
+    mut arenas: Query<(&mut ChunkLayer, &mut ChestState)>,
 ) {
-    let Ok((player_ent, player_chest_state, open_inventory, player_name)) =
+    let Ok((player_ent, player_chest_state, open_inventory, player_name, layer_id)) =
         players.get(trigger.entity())
     else {
         return;
@@ -137,7 +143,9 @@ fn chest_close(
         return;
     };
 
-    let mut layer = layer.single_mut();
+    let Ok((mut layer, mut chest_state)) = arenas.get_mut(layer_id.0) else {
+        return;
+    };
 
     let (res, block_kind, close_sound) = if player_chest_state.is_ender_chest {
         (
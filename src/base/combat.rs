@@ -1,5 +1,4 @@
 use bevy_ecs::query::QueryData;
-use bevy_state::prelude::in_state;
 use bevy_time::{Time, Timer, TimerMode};
 use rand::Rng;
 use valence::{
@@ -10,7 +9,9 @@ use valence::{
 };
 
 use crate::{
+    arena::{any_arena_in, arena_in_state},
     base::enchantments::{Enchantment, ItemStackExtEnchantments},
+    bedwars_config::GameRules,
     utils::item_stack::ItemStackExtWeapons,
     GameState, Team,
 };
@@ -26,14 +27,6 @@ use super::{
 pub const EYE_HEIGHT: f32 = 1.62;
 pub const SNEAK_EYE_HEIGHT: f32 = 1.54;
 
-// const ATTACK_COOLDOWN_TICKS: i64 = 10;
-pub const ATTACK_COOLDOWN_MILLIS: u64 = 500;
-const CRIT_MULTIPLIER: f32 = 1.5;
-
-const FRIENLDY_FIRE: bool = false;
-
-const DEFAULT_KNOCKBACK: f32 = 0.4;
-
 const BURN_DAMAGE_PER_SECOND: f32 = 1.0;
 
 #[derive(Component)]
@@ -74,6 +67,31 @@ impl Default for CombatState {
     }
 }
 
+impl CombatState {
+    /// Position of the eyes of the player standing at `feet`
+    pub fn eye_position(&self, feet: DVec3) -> DVec3 {
+        let eye_height = if self.is_sneaking {
+            SNEAK_EYE_HEIGHT
+        } else {
+            EYE_HEIGHT
+        };
+
+        feet + DVec3::new(0.0, eye_height as f64, 0.0)
+    }
+}
+
+/// Unit vector in the direction the entity is looking
+pub fn look_direction(look: &Look) -> Vec3 {
+    let yaw = look.yaw.to_radians();
+    let pitch = look.pitch.to_radians();
+
+    Vec3::new(
+        -yaw.sin() * pitch.cos(),
+        -pitch.sin(),
+        yaw.cos() * pitch.cos(),
+    )
+}
+
 pub struct CombatPlugin;
 
 impl Plugin for CombatPlugin {
@@ -81,7 +99,7 @@ impl Plugin for CombatPlugin {
         app.add_systems(
             Update,
             (combat_system, arrow_hits, apply_fire_damage, on_start_burn)
-                .run_if(in_state(GameState::Match)),
+                .run_if(any_arena_in(GameState::Match)),
         )
         .observe(on_remove_burn);
     }
@@ -101,11 +119,12 @@ pub struct CombatQuery {
     pub falling_state: &'static FallingState,
     pub equipment: &'static Equipment,
     pub team: &'static Team,
+    pub layer: &'static EntityLayerId,
     pub entity: Entity,
     pub stuck_arrow_count: &'static mut StuckArrowCount,
 }
 
-fn xy_knockback(damage_pos: DVec3, victim_pos: DVec3) -> (f32, f32) {
+pub fn xy_knockback(damage_pos: DVec3, victim_pos: DVec3) -> (f32, f32) {
     let mut x = (damage_pos.x - victim_pos.x) as f32;
     let mut z = (damage_pos.z - victim_pos.z) as f32;
 
@@ -117,7 +136,7 @@ fn xy_knockback(damage_pos: DVec3, victim_pos: DVec3) -> (f32, f32) {
     (x, z)
 }
 
-fn receive_knockback(
+pub fn receive_knockback(
     victim: &mut CombatQueryItem<'_>,
     mut strength: f32,
     x: f32,
@@ -154,6 +173,8 @@ fn combat_system(
     mut sneaking: EventReader<SneakEvent>,
     mut interact_entity_events: EventReader<InteractEntityEvent>,
     mut event_writer: EventWriter<PlayerHurtEvent>,
+    arenas: Query<&GameState>,
+    rules: Res<GameRules>,
 ) {
     for &SprintEvent { client, state } in sprinting.read() {
         if let Ok(mut client) = clients.get_mut(client) {
@@ -182,11 +203,18 @@ fn combat_system(
             continue;
         };
 
-        if attacker.team == victim.team && !FRIENLDY_FIRE {
+        if attacker.layer != victim.layer
+            || !arena_in_state(&arenas, attacker.layer.0, GameState::Match)
+        {
+            continue;
+        }
+
+        if attacker.team == victim.team && !rules.friendly_fire {
             continue;
         }
 
-        if (attacker.state.last_attack.elapsed().as_millis() as u64) < ATTACK_COOLDOWN_MILLIS {
+        if (attacker.state.last_attack.elapsed().as_millis() as u64) < rules.attack_cooldown_millis
+        {
             continue;
         }
 
@@ -209,7 +237,7 @@ fn combat_system(
 
         let extra_knockback = attack_weapon.knockback_extra() * dir;
         let (x, z) = xy_knockback(attacker.position.0, victim.position.0);
-        receive_knockback(&mut victim, DEFAULT_KNOCKBACK, x, z, extra_knockback);
+        receive_knockback(&mut victim, rules.knockback, x, z, extra_knockback);
 
         victim.client.trigger_status(EntityStatus::PlayAttackSound);
         victim.statuses.trigger(EntityStatus::PlayAttackSound);
@@ -217,7 +245,7 @@ fn combat_system(
         let weapon_damage = attack_weapon.damage();
         let damage = weapon_damage
             * if attacker.falling_state.falling {
-                CRIT_MULTIPLIER
+                rules.crit_multiplier
             } else {
                 1.0
             };
@@ -242,7 +270,8 @@ fn arrow_hits(
     >,
     mut clients: Query<CombatQuery>,
     mut event_writer: EventWriter<PlayerHurtEvent>,
-    mut layer: Query<&mut ChunkLayer>,
+    mut layers: Query<&mut ChunkLayer>,
+    rules: Res<GameRules>,
 ) {
     for event in events.read() {
         let Ok((arrow_velocity, arrow_power, arrow_owner, bow_used, old_pos)) =
@@ -267,7 +296,9 @@ fn arrow_hits(
             continue;
         };
 
-        let mut layer = layer.single_mut();
+        let Ok(mut layer) = layers.get_mut(victim.layer.0) else {
+            continue;
+        };
 
         layer.play_sound(
             Sound::EntityArrowHit,
@@ -304,7 +335,7 @@ fn arrow_hits(
 
         let extra_knockback = arrow_power.knockback_extra(**arrow_velocity, punch_level);
         let (x, z) = xy_knockback(old_pos.get(), victim.position.0);
-        receive_knockback(&mut victim, DEFAULT_KNOCKBACK, x, z, extra_knockback);
+        receive_knockback(&mut victim, rules.knockback, x, z, extra_knockback);
 
         event_writer.send(PlayerHurtEvent {
             attacker: Some(arrow_owner.0),
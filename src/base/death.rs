@@ -1,9 +1,9 @@
 use bevy_ecs::query::Added;
-use bevy_state::prelude::in_state;
 use bevy_time::{Time, Timer, TimerMode};
 use rand::Rng;
 use valence::client::UpdateClientsSet;
 use valence::entity::lightning::LightningEntityBundle;
+use valence::entity::player::AdditionalHearts;
 use valence::entity::EntityId;
 use valence::prelude::Inventory;
 use valence::protocol::packets::play::EntityDamageS2c;
@@ -12,14 +12,16 @@ use valence::protocol::{Sound, WritePacket};
 use valence::Layer;
 use valence::{entity::living::Health, prelude::*};
 
+use crate::arena::any_arena_in;
+use crate::items::effects::{ActiveEffects, StatusEffect};
 use crate::Spectator;
 use crate::{
-    bedwars_config::WorldConfig, r#match::MatchState, utils::inventory::InventoryExt, GameState,
-    Team,
+    bedwars_config::{GameRules, WorldConfig},
+    r#match::MatchState,
+    utils::inventory::InventoryExt,
+    GameState, Team,
 };
 
-const PLAYER_RESPAWN_TIMER_SECS: u32 = 5;
-
 #[derive(Debug, Clone, Component)]
 pub struct IsDead;
 
@@ -30,10 +32,10 @@ pub struct RespawnTimer {
     pub timer: Timer,
 }
 
-impl Default for RespawnTimer {
-    fn default() -> Self {
+impl RespawnTimer {
+    pub fn new(respawn_time_secs: u32) -> Self {
         Self {
-            repeats: PLAYER_RESPAWN_TIMER_SECS,
+            repeats: respawn_time_secs,
             timer: Timer::from_seconds(1.0, TimerMode::Repeating),
         }
     }
@@ -68,8 +70,8 @@ impl Plugin for DeathPlugin {
             (
                 on_player_hurt
                     .before(UpdateClientsSet)
-                    .run_if(in_state(GameState::Match)),
-                on_death.run_if(in_state(GameState::Match)),
+                    .run_if(any_arena_in(GameState::Match)),
+                on_death.run_if(any_arena_in(GameState::Match)),
                 tick_respawn_timer,
                 on_player_elimination,
             ),
@@ -87,7 +89,8 @@ fn tick_respawn_timer(
     mut clients: Query<(Entity, &mut Client, &Position, &mut RespawnTimer)>,
 ) {
     for (player, mut client, position, mut timer) in &mut clients {
-        if timer.timer.tick(time.delta()).just_finished() {
+        // A respawn time of 0 respawns the player immediately
+        if timer.repeats > 0 && timer.timer.tick(time.delta()).just_finished() {
             timer.repeats -= 1;
             client.set_title_times(0, 21, 0);
             client.set_title(format!("Respawn in: {}", timer.repeats));
@@ -101,7 +104,7 @@ fn tick_respawn_timer(
             );
         }
 
-        if timer.timer.finished() && timer.repeats == 0 {
+        if timer.repeats == 0 {
             commands.entity(player).remove::<IsDead>();
             commands.entity(player).remove::<RespawnTimer>(); // is this required?
             client.clear_title();
@@ -113,6 +116,7 @@ fn player_respawn(
     trigger: Trigger<OnRemove, IsDead>,
     mut clients: Query<
         (
+            &EntityLayerId,
             &mut Position,
             &mut Health,
             &mut GameMode,
@@ -121,14 +125,18 @@ fn player_respawn(
         ),
         Without<Spectator>,
     >,
-    bedwars_config: Res<WorldConfig>,
+    arenas: Query<&WorldConfig>,
 ) {
-    let Ok((mut position, mut health, mut game_mode, team, mut equipment)) =
+    let Ok((layer_id, mut position, mut health, mut game_mode, team, mut equipment)) =
         clients.get_mut(trigger.entity())
     else {
         return;
     };
 
+    let Ok(bedwars_config) = arenas.get(layer_id.0) else {
+        return;
+    };
+
     health.0 = 20.0;
     *game_mode = GameMode::Survival;
 
@@ -148,6 +156,7 @@ fn on_death(
     mut clients: Query<
         (
             Entity,
+            &EntityLayerId,
             &Position,
             &mut Inventory,
             &mut GameMode,
@@ -156,16 +165,21 @@ fn on_death(
         ),
         Added<IsDead>,
     >,
-    match_state: Res<MatchState>,
-    mut layer: Query<&mut ChunkLayer>,
+    mut arenas: Query<(&mut ChunkLayer, &MatchState)>,
+    rules: Res<GameRules>,
 ) {
-    for (player_ent, position, mut inventory, mut game_mode, team, mut health) in &mut clients {
+    for (player_ent, layer_id, position, mut inventory, mut game_mode, team, mut health) in
+        &mut clients
+    {
+        let Ok((mut layer, match_state)) = arenas.get_mut(layer_id.0) else {
+            continue;
+        };
+
         let bed_destroyed = match_state.teams.get(&team.name).unwrap().bed_destroyed;
         *game_mode = GameMode::Spectator;
         inventory.clear();
         health.0 = 20.0;
 
-        let mut layer = layer.single_mut();
         layer.play_sound(
             Sound::EntityPlayerDeath,
             SoundCategory::Master,
@@ -175,7 +189,7 @@ fn on_death(
         );
 
         if !bed_destroyed {
-            let player_respawn_timer = RespawnTimer::default();
+            let player_respawn_timer = RespawnTimer::new(rules.respawn_time_secs);
             commands.entity(player_ent).insert(player_respawn_timer);
         } else {
             commands.entity(player_ent).insert(Spectator);
@@ -187,23 +201,54 @@ fn on_death(
 
 fn on_player_hurt(
     mut commands: Commands,
-    mut clients: Query<(&EntityId, &mut Health, &Team)>,
+    mut clients: Query<(
+        &EntityId,
+        &EntityLayerId,
+        &mut Health,
+        &Team,
+        Option<&mut AdditionalHearts>,
+        Option<&mut ActiveEffects>,
+    )>,
     mut events: EventReader<PlayerHurtEvent>,
     mut death_event_writer: EventWriter<PlayerDeathEvent>,
     mut eliminated_writer: EventWriter<PlayerEliminatedEvent>,
-    mut layer: Query<&mut ChunkLayer>,
-    match_state: Res<MatchState>,
+    mut arenas: Query<(&GameState, &mut ChunkLayer, &MatchState)>,
 ) {
     for event in events.read() {
-        let Ok((victim_id, mut victim_health, team)) = clients.get_mut(event.victim) else {
+        let Ok((victim_id, layer_id, mut victim_health, team, hearts, effects)) =
+            clients.get_mut(event.victim)
+        else {
             continue;
         };
 
         let victim_id = victim_id.get();
 
-        let mut layer = layer.single_mut();
+        let Ok((state, mut layer, match_state)) = arenas.get_mut(layer_id.0) else {
+            continue;
+        };
 
-        let new_health = victim_health.0 - event.damage;
+        if *state != GameState::Match {
+            continue;
+        }
+
+        // Players lose their invisibility when they are attacked
+        if let Some(mut effects) = effects {
+            if event.attacker.is_some() && effects.has(StatusEffect::Invisibility) {
+                effects.remove(StatusEffect::Invisibility);
+            }
+        }
+
+        // Absorption hearts are lost first
+        let mut damage = event.damage;
+        if let Some(mut hearts) = hearts {
+            if hearts.0 > 0.0 {
+                let absorbed = damage.min(hearts.0);
+                hearts.0 -= absorbed;
+                damage -= absorbed;
+            }
+        }
+
+        let new_health = victim_health.0 - damage;
 
         if new_health <= 0.0 {
             let bed_destroyed = match_state.teams.get(&team.name).unwrap().bed_destroyed;
@@ -245,7 +290,7 @@ fn on_player_hurt(
         let attacker_id = event.attacker.map(|attacker| {
             clients
                 .get(attacker)
-                .map(|(id, _, _)| *id)
+                .map(|(id, ..)| *id)
                 .unwrap_or_default()
         });
 
@@ -263,16 +308,19 @@ fn on_player_hurt(
 
 fn on_player_elimination(
     mut commands: Commands,
-    mut layer: Query<&mut ChunkLayer>,
+    mut layers: Query<&mut ChunkLayer>,
     mut events: EventReader<PlayerEliminatedEvent>,
     clients: Query<&EntityLayerId>,
 ) {
     for event in events.read() {
-        let mut layer = layer.single_mut();
         let Ok(layer_id) = clients.get(event.victim) else {
             continue;
         };
 
+        let Ok(mut layer) = layers.get_mut(layer_id.0) else {
+            continue;
+        };
+
         layer.play_sound(
             Sound::EntityLightningBoltThunder,
             SoundCategory::Master,
@@ -16,7 +16,7 @@ use valence::{
     },
     inventory::DropItemStackEvent,
     math::{vec3, DVec3},
-    ChunkLayer, Despawned, EntityLayer, ItemStack, Server,
+    Despawned, ItemStack, Server,
 };
 
 use crate::utils::despawn_timer::DespawnTimer;
@@ -43,17 +43,15 @@ impl Plugin for ItemDropPlugin {
 
 fn drop_items(
     mut commands: Commands,
-    players: Query<(&Position, &Look), With<PlayerEntity>>,
+    players: Query<(&Position, &Look, &EntityLayerId), With<PlayerEntity>>,
     mut event_reader: EventReader<DropItemStackEvent>,
-    layers: Query<Entity, (With<ChunkLayer>, With<EntityLayer>)>,
 ) {
-    let layer = layers.single();
     for event in event_reader.read() {
         let Ok(player) = players.get(event.client) else {
             continue;
         };
 
-        let (player_pos, player_look) = player;
+        let (player_pos, player_look, layer_id) = player;
 
         let item_dropped_from_pos = Position(player_pos.0 + DVec3::new(0.0, DROP_OFFSET, 0.0));
 
@@ -72,7 +70,7 @@ fn drop_items(
                 position: Position(*item_dropped_from_pos),
                 velocity: Velocity(item_velocity),
 
-                layer: EntityLayerId(layer),
+                layer: *layer_id,
                 entity_no_gravity: NoGravity(true),
                 ..Default::default()
             })
@@ -87,7 +85,7 @@ fn drop_items(
 
 fn merge_to_stacks(
     mut commands: Commands,
-    mut items: Query<(Entity, &Position, &mut Stack)>,
+    mut items: Query<(Entity, &Position, &EntityLayerId, &mut Stack)>,
     server: Res<Server>,
 ) {
     let tick_rate = u32::from(server.tick_rate()) as i64;
@@ -100,9 +98,9 @@ fn merge_to_stacks(
     // Contains tuples of two entities that will be merged together (into the first one)
     let mut to_merge = HashMap::new();
 
-    for (entity1, position1, stack1) in items.iter() {
-        for (entity2, position2, stack2) in items.iter() {
-            if entity1 == entity2 {
+    for (entity1, position1, layer1, stack1) in items.iter() {
+        for (entity2, position2, layer2, stack2) in items.iter() {
+            if entity1 == entity2 || layer1 != layer2 {
                 continue;
             }
 
@@ -132,10 +130,10 @@ fn merge_to_stacks(
             continue;
         };
 
-        *entity1.2 = Stack(ItemStack::new(
-            entity1.2.item,
-            entity1.2.count.saturating_add(entity2.2.count),
-            entity1.2.nbt.clone(),
+        *entity1.3 = Stack(ItemStack::new(
+            entity1.3.item,
+            entity1.3.count.saturating_add(entity2.3.count),
+            entity1.3.nbt.clone(),
         ));
         commands.entity(entity2.0).insert(Despawned);
     }
@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
 use valence::{
-    nbt::{value::ValueRef, Value},
+    nbt::{compound, value::ValueRef, Compound, List, Value},
     ItemStack,
 };
 
@@ -31,6 +31,19 @@ impl Enchantment {
             _ => None,
         }
     }
+
+    fn id(&self) -> &'static str {
+        match self {
+            Enchantment::Sharpness => "minecraft:sharpness",
+            Enchantment::Knockback => "minecraft:knockback",
+            Enchantment::Protection => "minecraft:protection",
+            Enchantment::Power => "minecraft:power",
+            Enchantment::Punch => "minecraft:punch",
+            Enchantment::Infinity => "minecraft:infinity",
+            Enchantment::FireAspect => "minecraft:fire_aspect",
+            Enchantment::Flame => "minecraft:flame",
+        }
+    }
 }
 
 /// Calculates the extra damage given by the sharpness enchantment.
@@ -70,6 +83,8 @@ pub fn flame_burn_time(level: u32) -> f32 {
 pub trait ItemStackExtEnchantments {
     /// Get the enchantments of an item stack via NBT.
     fn enchantments(&self) -> HashMap<Enchantment, u32>;
+    /// Set the level of an enchantment, level 0 removes the enchantment.
+    fn set_enchantment(&mut self, enchantment: Enchantment, level: u32);
 }
 
 impl ItemStackExtEnchantments for ItemStack {
@@ -93,4 +108,49 @@ impl ItemStackExtEnchantments for ItemStack {
 
         enchantments
     }
+
+    fn set_enchantment(&mut self, enchantment: Enchantment, level: u32) {
+        let nbt = self.nbt.get_or_insert_with(Compound::new);
+
+        let mut enchantments = match nbt.remove("Enchantments") {
+            Some(Value::List(List::Compound(enchantments))) => enchantments,
+            _ => vec![],
+        };
+
+        enchantments.retain(|enchant| {
+            !matches!(enchant.get("id"), Some(Value::String(id)) if id == enchantment.id())
+        });
+
+        if level > 0 {
+            enchantments.push(compound! {
+                "id" => enchantment.id(),
+                "lvl" => level as i64,
+            });
+        }
+
+        nbt.insert("Enchantments", List::Compound(enchantments));
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use valence::ItemKind;
+
+    use super::*;
+
+    #[test]
+    fn test_set_enchantment() {
+        let mut stack = ItemStack::new(ItemKind::IronSword, 1, None);
+
+        stack.set_enchantment(Enchantment::Sharpness, 2);
+        stack.set_enchantment(Enchantment::Knockback, 1);
+        stack.set_enchantment(Enchantment::Sharpness, 3);
+
+        let enchantments = stack.enchantments();
+        assert_eq!(enchantments.get(&Enchantment::Sharpness), Some(&3));
+        assert_eq!(enchantments.get(&Enchantment::Knockback), Some(&1));
+
+        stack.set_enchantment(Enchantment::Knockback, 0);
+        assert_eq!(stack.enchantments().len(), 1);
+    }
 }
@@ -0,0 +1,291 @@
+use std::collections::HashSet;
+
+use valence::{
+    prelude::*,
+    protocol::{sound::SoundCategory, Sound},
+};
+
+use crate::{
+    arena::any_arena_in,
+    bedwars_config::{ConfigVec3, GameRules, WorldConfig},
+    r#match::MatchState,
+    utils::block::blast_resistance,
+    GameState, Team,
+};
+
+use super::{
+    armor::EquipmentExtReduction,
+    break_blocks::BedDestroyedEvent,
+    build::PlayerPlacedBlocks,
+    combat::{receive_knockback, xy_knockback, Burning, CombatQuery},
+    death::{IsDead, PlayerHurtEvent},
+};
+
+/// Distance a ray of an explosion travels per step
+const RAY_STEP: f64 = 0.3;
+/// Intensity a ray loses per step, even when passing through air
+const RAY_STEP_DECAY: f32 = 0.225;
+
+/// Sent to let something explode
+#[derive(Debug, Clone, Event)]
+pub struct ExplosionEvent {
+    pub arena: Entity,
+    pub position: DVec3,
+    /// Strength of the explosion against blocks, players are hurt within `2 * power` blocks
+    pub power: f32,
+    /// Damage at the center of the explosion, decreases with distance
+    pub damage: f32,
+    /// Knockback at the center of the explosion, decreases with distance
+    pub knockback: f32,
+    /// Players hit by the explosion are set on fire for this long
+    pub burn_secs: f32,
+    /// The player the explosion is credited to
+    pub owner: Option<Entity>,
+}
+
+pub struct ExplosionPlugin;
+
+impl Plugin for ExplosionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (explode,).run_if(any_arena_in(GameState::Match)))
+            .add_event::<ExplosionEvent>();
+    }
+}
+
+/// Blocks reached by an explosion, using the rays of vanilla explosions.
+/// `resistance` returns the blast resistance of a block, or `None` for air.
+pub fn explosion_blocks(
+    center: DVec3,
+    power: f32,
+    resistance: impl Fn(BlockPos) -> Option<f32>,
+) -> HashSet<BlockPos> {
+    let mut blocks = HashSet::new();
+
+    for x in 0..16 {
+        for y in 0..16 {
+            for z in 0..16 {
+                // Only cast rays towards the surface of the cube
+                if ![x, y, z].iter().any(|v| *v == 0 || *v == 15) {
+                    continue;
+                }
+
+                let direction = DVec3::new(x as f64, y as f64, z as f64) / 15.0 * 2.0 - 1.0;
+                let direction = direction.normalize();
+
+                let mut intensity = power;
+                let mut position = center;
+
+                while intensity > 0.0 {
+                    let block_pos = BlockPos::new(
+                        position.x.floor() as i32,
+                        position.y.floor() as i32,
+                        position.z.floor() as i32,
+                    );
+
+                    if let Some(resistance) = resistance(block_pos) {
+                        intensity -= (resistance + 0.3) * 0.3;
+
+                        if intensity > 0.0 {
+                            blocks.insert(block_pos);
+                        }
+                    }
+
+                    position += direction * RAY_STEP;
+                    intensity -= RAY_STEP_DECAY;
+                }
+            }
+        }
+    }
+
+    blocks
+}
+
+#[allow(clippy::type_complexity)]
+fn explode(
+    mut commands: Commands,
+    mut events: EventReader<ExplosionEvent>,
+    mut arenas: Query<(
+        &GameState,
+        &mut ChunkLayer,
+        &mut PlayerPlacedBlocks,
+        &WorldConfig,
+        &MatchState,
+    )>,
+    mut players: Query<CombatQuery, Without<IsDead>>,
+    mut hurt_writer: EventWriter<PlayerHurtEvent>,
+    mut bed_destroyed_writer: EventWriter<BedDestroyedEvent>,
+    rules: Res<GameRules>,
+) {
+    for event in events.read() {
+        let Ok((state, mut layer, mut player_placed_blocks, bedwars_config, match_state)) =
+            arenas.get_mut(event.arena)
+        else {
+            continue;
+        };
+
+        if *state != GameState::Match {
+            continue;
+        }
+
+        layer.play_sound(
+            Sound::EntityGenericExplode,
+            SoundCategory::Block,
+            event.position,
+            4.0,
+            rand::random::<f32>() * 0.2 + 0.7,
+        );
+        layer.play_particle(
+            &Particle::ExplosionEmitter,
+            true,
+            event.position,
+            Vec3::ZERO,
+            0.0,
+            1,
+        );
+
+        let owner_team = event
+            .owner
+            .and_then(|owner| players.get(owner).ok())
+            .map(|owner| owner.team.clone());
+
+        let blocks = explosion_blocks(event.position, event.power, |pos| {
+            layer
+                .block(pos)
+                .filter(|block| !block.state.is_air())
+                .map(|block| blast_resistance(block.state))
+        });
+
+        // Both halves of a bed can be in the same explosion
+        let mut destroyed_beds = HashSet::new();
+
+        for block_pos in blocks {
+            // Map blocks are never destroyed
+            if player_placed_blocks.0.remove(&block_pos).is_some() {
+                layer.set_block(block_pos, BlockState::AIR);
+                continue;
+            }
+
+            if !rules.explosions_break_beds {
+                continue;
+            }
+
+            let block_pos_vec = ConfigVec3 {
+                x: block_pos.x,
+                y: block_pos.y,
+                z: block_pos.z,
+            };
+
+            let Some((team_name, bed_blocks)) =
+                bedwars_config.beds.iter().find(|(_, bed_blocks)| {
+                    bed_blocks
+                        .iter()
+                        .any(|bed_block| bed_block.pos == block_pos_vec)
+                })
+            else {
+                continue;
+            };
+
+            if owner_team
+                .as_ref()
+                .is_some_and(|team| team.name == *team_name)
+            {
+                continue;
+            }
+
+            let already_destroyed = match_state
+                .teams
+                .get(team_name)
+                .map_or(true, |team| team.bed_destroyed);
+
+            if already_destroyed || !destroyed_beds.insert(team_name.clone()) {
+                continue;
+            }
+
+            for bed_block in bed_blocks {
+                let pos = &bed_block.pos;
+                layer.set_block(BlockPos::new(pos.x, pos.y, pos.z), BlockState::AIR);
+            }
+
+            let Some(color) = bedwars_config.teams.get(team_name) else {
+                continue;
+            };
+
+            bed_destroyed_writer.send(BedDestroyedEvent {
+                arena: event.arena,
+                attacker: event.owner,
+                team: Team {
+                    name: team_name.clone(),
+                    color: *color,
+                },
+            });
+        }
+
+        let radius = event.power as f64 * 2.0;
+
+        for mut victim in &mut players {
+            if victim.layer.0 != event.arena {
+                continue;
+            }
+
+            let is_owner = event.owner == Some(victim.entity);
+            if !is_owner && !rules.friendly_fire && owner_team.as_ref() == Some(victim.team) {
+                continue;
+            }
+
+            let distance = victim.position.0.distance(event.position);
+            if distance >= radius {
+                continue;
+            }
+
+            let impact = (1.0 - distance / radius) as f32;
+
+            let (x, z) = xy_knockback(event.position, victim.position.0);
+            let knockback = event.knockback * impact;
+            receive_knockback(&mut victim, knockback, x, z, Vec3::Y * knockback * 10.0);
+
+            victim.state.last_hit = std::time::Instant::now();
+
+            if event.burn_secs > 0.0 {
+                commands
+                    .entity(victim.entity)
+                    .insert(Burning::new(event.burn_secs, event.owner));
+            }
+
+            let damage = victim.equipment.received_damage(event.damage * impact);
+            hurt_writer.send(PlayerHurtEvent {
+                attacker: event.owner.filter(|_| !is_owner),
+                victim: victim.entity,
+                damage,
+                position: victim.position.0,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::utils::block::BLAST_PROOF;
+
+    #[test]
+    fn test_explosion_blocks() {
+        let wool = BlockPos::new(1, 0, 0);
+        let glass = BlockPos::new(-1, 0, 0);
+        let behind_glass = BlockPos::new(-2, 0, 0);
+
+        // A wall of glass at x = -1
+        let blocks = explosion_blocks(DVec3::new(0.5, 0.5, 0.5), 4.0, |pos| {
+            if pos.x == -1 {
+                Some(BLAST_PROOF)
+            } else if pos == wool || pos == behind_glass {
+                Some(0.8)
+            } else {
+                None
+            }
+        });
+
+        assert!(blocks.contains(&wool));
+        assert!(!blocks.contains(&glass));
+        assert!(!blocks.contains(&behind_glass));
+    }
+}
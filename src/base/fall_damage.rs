@@ -1,7 +1,6 @@
-use bevy_state::prelude::in_state;
 use valence::{client::UpdateClientsSet, prelude::*};
 
-use crate::{utils::aabb::is_on_ground, GameState};
+use crate::{arena::any_arena_in, utils::aabb::is_on_ground, GameState};
 
 use super::death::{IsDead, PlayerHurtEvent};
 
@@ -13,7 +12,7 @@ impl Plugin for FallDamagePlugin {
             Update,
             handle_fall_damage
                 .before(UpdateClientsSet)
-                .run_if(in_state(GameState::Match)),
+                .run_if(any_arena_in(GameState::Match)),
         );
     }
 }
@@ -33,14 +32,26 @@ impl FallingState {
 #[allow(clippy::type_complexity)]
 fn handle_fall_damage(
     mut clients: Query<
-        (Entity, &mut FallingState, &Position, &Hitbox),
+        (
+            Entity,
+            &mut FallingState,
+            &Position,
+            &Hitbox,
+            &EntityLayerId,
+        ),
         (Changed<Position>, Without<IsDead>),
     >,
-    layers: Query<&ChunkLayer, With<EntityLayer>>,
+    arenas: Query<(&GameState, &ChunkLayer)>,
     mut event_writer: EventWriter<PlayerHurtEvent>,
 ) {
-    for (player_ent, mut fall_damage_state, position, hitbox) in &mut clients {
-        let layer = layers.single();
+    for (player_ent, mut fall_damage_state, position, hitbox, layer_id) in &mut clients {
+        let Ok((state, layer)) = arenas.get(layer_id.0) else {
+            continue;
+        };
+
+        if *state != GameState::Match {
+            continue;
+        }
 
         // let flattened_player_hitbox = Aabb::new(
         //     DVec3::new(hitbox.min().x, hitbox.min().y - 0.05, hitbox.min().z),
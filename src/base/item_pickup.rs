@@ -7,18 +7,16 @@ use bevy_time::{Time, Timer, TimerMode};
 use valence::{
     app::{App, Plugin, Update},
     client::Client,
-    entity::{item::Stack, Position},
+    entity::{item::Stack, EntityLayerId, Position},
     prelude::{Component, Inventory},
     protocol::{sound::SoundCategory, Sound},
     Despawned,
 };
 
-use crate::{utils::inventory::InventoryExt, Spectator};
+use crate::{bedwars_config::GameRules, utils::inventory::InventoryExt, Spectator};
 
 // https://minecraft.fandom.com/wiki/Item_(entity)
 
-const PICKUP_RANGE_HOR: f64 = 1.0;
-const PICKUP_RANGE_VER: f64 = 0.5;
 const PICKUP_DELAY_SECS: f32 = 0.7;
 pub struct ItemPickupPlugin;
 
@@ -47,13 +45,27 @@ impl Plugin for ItemPickupPlugin {
 
 fn pickup_items(
     mut commands: Commands,
-    mut players: Query<(&Position, &mut Inventory, &mut Client), Without<Spectator>>,
-    mut items: Query<(Entity, &Position, &mut Stack, &mut PickupMarker)>,
+    mut players: Query<
+        (&Position, &EntityLayerId, &mut Inventory, &mut Client),
+        Without<Spectator>,
+    >,
+    mut items: Query<(
+        Entity,
+        &Position,
+        &EntityLayerId,
+        &mut Stack,
+        &mut PickupMarker,
+    )>,
     time: Res<Time>,
+    rules: Res<GameRules>,
 ) {
     // This will be really inefficient, but for a bedwars server it probably won't matter
-    for (player_pos, mut player_inv, mut client) in players.iter_mut() {
-        for (item_entity, item_pos, mut stack, mut pickup_timer) in items.iter_mut() {
+    for (player_pos, player_layer, mut player_inv, mut client) in players.iter_mut() {
+        for (item_entity, item_pos, item_layer, mut stack, mut pickup_timer) in items.iter_mut() {
+            if player_layer != item_layer {
+                continue;
+            }
+
             let player_vec3 = player_pos.0;
             let item_vec3 = item_pos.0;
 
@@ -65,9 +77,9 @@ fn pickup_items(
             let dy = player_vec3.y - item_vec3.y;
             let dz = player_vec3.z - item_vec3.z;
 
-            if dx.abs() > PICKUP_RANGE_HOR
-                || dy.abs() > PICKUP_RANGE_VER
-                || dz.abs() > PICKUP_RANGE_HOR
+            if dx.abs() > rules.pickup_range_horizontal
+                || dy.abs() > rules.pickup_range_vertical
+                || dz.abs() > rules.pickup_range_horizontal
             {
                 continue;
             }
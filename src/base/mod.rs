@@ -9,6 +9,7 @@ pub mod combat;
 pub mod death;
 pub mod drop_items;
 pub mod enchantments;
+pub mod explosion;
 pub mod fall_damage;
 pub mod item_pickup;
 pub mod physics;
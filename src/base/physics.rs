@@ -77,14 +77,14 @@ impl GetsStuckOnCollision {
 }
 
 /// Event that will be emitted when an entity collides with another entity
-#[derive(Debug, Event)]
+#[derive(Debug, Clone, Event)]
 pub struct EntityEntityCollisionEvent {
     pub entity1: Entity,
     pub entity2: Entity,
 }
 
 /// Event that will be emitted when an entity collides with a block
-#[derive(Debug, Event)]
+#[derive(Debug, Clone, Event)]
 pub struct EntityBlockCollisionEvent {
     pub entity: Entity,
     pub collision_pos: DVec3,
@@ -106,6 +106,7 @@ impl Plugin for PhysicsPlugin {
 #[query_data(mutable)]
 struct PhysicsQuery {
     pub entity: Entity,
+    pub layer_id: &'static EntityLayerId,
     pub old_position: &'static mut OldPosition,
     pub position: &'static mut Position,
     pub velocity: &'static mut Velocity,
@@ -126,8 +127,8 @@ fn handle_collision(
     mut entity_block_collision_writer: EventWriter<EntityBlockCollisionEvent>,
     mut entity_collision_writer: EventWriter<EntityEntityCollisionEvent>,
     mut entities: Query<PhysicsQuery, With<PhysicsMarker>>,
-    hittable_entities: Query<(Entity, &Hitbox), With<CollidableForEntities>>,
-    layer: Query<&ChunkLayer, With<EntityLayer>>,
+    hittable_entities: Query<(Entity, &Hitbox, &EntityLayerId), With<CollidableForEntities>>,
+    layers: Query<&ChunkLayer, With<EntityLayer>>,
 ) {
     enum Event {
         MarkAsRemovePhysics(Entity),
@@ -158,7 +159,9 @@ fn handle_collision(
             query.velocity.0 = query.velocity.0.clamp_length(0.0, terminal_velocity.0);
         }
 
-        let layer = layer.single();
+        let Ok(layer) = layers.get(query.layer_id.0) else {
+            return;
+        };
 
         let old_velocity = query.velocity.0;
         if let Some(mut block_collider) = query.block_collider {
@@ -359,8 +362,8 @@ fn handle_collision(
             }
 
             if let Some(ref query_entity_hitbox) = query.entity_collider {
-                for (entity, hitbox) in hittable_entities.iter() {
-                    if entity == query.entity {
+                for (entity, hitbox, layer_id) in hittable_entities.iter() {
+                    if entity == query.entity || layer_id != query.layer_id {
                         continue;
                     }
 
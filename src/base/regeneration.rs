@@ -1,32 +1,43 @@
+use std::time::Duration;
+
 use bevy_ecs::query::QueryData;
-use bevy_state::prelude::in_state;
 use bevy_time::{Time, Timer, TimerMode};
 use valence::{entity::living::Health, prelude::*};
 
-use crate::GameState;
+use crate::{
+    arena::any_arena_in,
+    bedwars_config::GameRules,
+    items::effects::{ActiveEffects, StatusEffect},
+    r#match::MatchState,
+    GameState,
+};
 
 use super::combat::CombatState;
 
-pub struct RegenerationPlugin;
+/// Time the regeneration effect needs to heal one health point, halved by every level
+const REGENERATION_EFFECT_SECS_PER_HP: f32 = 2.5;
 
-const SECS_PER_HP: f32 = 4.0;
-// const COMBAT_COOLDOWN_TICKS: u128 = 140;
-const COMBAT_COOLDOWN_MILLIS: u128 = 7000;
+pub struct RegenerationPlugin;
 
 #[derive(Component)]
 struct RegenTimer(Timer);
 
-impl Default for RegenTimer {
-    fn default() -> Self {
-        Self(Timer::from_seconds(SECS_PER_HP, TimerMode::Repeating))
+impl RegenTimer {
+    fn new(secs_per_hp: f32) -> Self {
+        Self(Timer::from_seconds(secs_per_hp, TimerMode::Repeating))
     }
 }
 
+/// Heals with the regeneration effect, also during combat and sudden death
+#[derive(Component)]
+struct RegenEffectTimer(Timer);
+
 impl Plugin for RegenerationPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
             EventLoopUpdate,
-            regeneration_system.run_if(in_state(GameState::Match)),
+            (regeneration_system, regeneration_effect_system)
+                .run_if(any_arena_in(GameState::Match)),
         );
     }
 }
@@ -37,15 +48,26 @@ struct RegenerationQuery {
     entity: Entity,
     health: &'static mut Health,
     combat_state: &'static CombatState,
+    layer_id: &'static EntityLayerId,
 }
 
 fn regeneration_system(
     mut commands: Commands,
     mut query: Query<(RegenerationQuery, Option<&mut RegenTimer>)>,
+    arenas: Query<&MatchState>,
     time: Res<Time>,
+    rules: Res<GameRules>,
 ) {
     for (mut query, timer) in query.iter_mut() {
-        if query.combat_state.last_hit.elapsed().as_millis() < COMBAT_COOLDOWN_MILLIS {
+        let sudden_death = arenas
+            .get(query.layer_id.0)
+            .is_ok_and(|match_state| match_state.sudden_death);
+
+        if sudden_death {
+            commands.entity(query.entity).remove::<RegenTimer>();
+        } else if query.combat_state.last_hit.elapsed().as_millis()
+            < rules.regeneration_combat_cooldown_millis as u128
+        {
             // player entered / is in combat, remove the regen timer
             commands.entity(query.entity).remove::<RegenTimer>();
         } else if let Some(mut timer) = timer {
@@ -55,7 +77,47 @@ fn regeneration_system(
             }
         } else {
             // player is not in combat, add the regen timer
-            commands.entity(query.entity).insert(RegenTimer::default());
+            commands
+                .entity(query.entity)
+                .insert(RegenTimer::new(rules.regeneration_secs_per_hp));
+        }
+    }
+}
+
+fn regeneration_effect_system(
+    mut commands: Commands,
+    mut query: Query<(
+        Entity,
+        &mut Health,
+        &ActiveEffects,
+        Option<&mut RegenEffectTimer>,
+    )>,
+    time: Res<Time>,
+) {
+    for (entity, mut health, effects, timer) in query.iter_mut() {
+        let Some(amplifier) = effects.amplifier(StatusEffect::Regeneration) else {
+            if timer.is_some() {
+                commands.entity(entity).remove::<RegenEffectTimer>();
+            }
+            continue;
+        };
+
+        let secs_per_hp =
+            Duration::from_secs_f32(REGENERATION_EFFECT_SECS_PER_HP / 2_f32.powi(amplifier as i32));
+
+        match timer {
+            Some(mut timer) => {
+                timer.0.set_duration(secs_per_hp);
+                if timer.0.tick(time.delta()).just_finished() {
+                    health.0 = (health.0 + 1.0).min(20.0);
+                }
+            }
+            None => {
+                commands.entity(entity).insert(RegenEffectTimer(Timer::new(
+                    secs_per_hp,
+                    TimerMode::Repeating,
+                )));
+            }
         }
     }
 }
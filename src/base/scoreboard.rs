@@ -1,10 +1,10 @@
 use std::collections::HashMap;
 
-use bevy_state::prelude::in_state;
 use valence::prelude::*;
 use valence::protocol::packets::play::scoreboard_objective_update_s2c::ObjectiveRenderType;
 use valence::scoreboard::*;
 
+use crate::arena::any_arena_in;
 use crate::r#match::MatchState;
 use crate::GameState;
 use crate::Team;
@@ -26,29 +26,43 @@ impl Plugin for ScoreboardPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
             Update,
-            update_match_scoreboard
-                .run_if(in_state(GameState::Match))
-                .run_if(resource_exists::<MatchState>),
+            update_match_scoreboard.run_if(any_arena_in(GameState::Match)),
         );
     }
 }
 
 fn update_match_scoreboard(
-    commands: Commands,
+    mut commands: Commands,
     players: Query<(&Team, &EntityLayerId)>,
-    mut scoreboard: Query<(&mut BedwarsScoreboard, &mut ObjectiveScores)>,
-    match_state: Res<MatchState>,
+    mut scoreboards: Query<(&EntityLayerId, &mut BedwarsScoreboard, &mut ObjectiveScores)>,
+    arenas: Query<(Entity, &GameState, &MatchState), Changed<MatchState>>,
 ) {
-    if !match_state.is_changed() {
-        return;
+    for (arena, state, match_state) in &arenas {
+        if *state != GameState::Match {
+            continue;
+        }
+
+        update_arena_scoreboard(
+            &mut commands,
+            arena,
+            match_state,
+            &players,
+            &mut scoreboards,
+        );
     }
+}
 
-    let Some((_, layer_id)) = players.iter().next() else {
-        return;
-    };
-
+#[allow(clippy::type_complexity)]
+fn update_arena_scoreboard(
+    commands: &mut Commands,
+    arena: Entity,
+    match_state: &MatchState,
+    players: &Query<(&Team, &EntityLayerId)>,
+    scoreboards: &mut Query<(&EntityLayerId, &mut BedwarsScoreboard, &mut ObjectiveScores)>,
+) {
     let updated_entries = players
         .iter()
+        .filter(|(_, layer_id)| layer_id.0 == arena)
         .map(|(team, _)| {
             let team_state = match_state.teams.get(&team.name).unwrap();
             let entry = ScoreboardEntry {
@@ -59,8 +73,15 @@ fn update_match_scoreboard(
         })
         .collect::<HashMap<Team, ScoreboardEntry>>();
 
-    let Ok((mut scoreboard, mut scores)) = scoreboard.get_single_mut() else {
-        create_scoreboard(commands, updated_entries, *layer_id);
+    if updated_entries.is_empty() {
+        return;
+    }
+
+    let Some((_, mut scoreboard, mut scores)) = scoreboards
+        .iter_mut()
+        .find(|(layer_id, _, _)| layer_id.0 == arena)
+    else {
+        create_scoreboard(commands, updated_entries, EntityLayerId(arena));
         return;
     };
 
@@ -108,7 +129,7 @@ fn update_match_scoreboard(
 }
 
 fn create_scoreboard(
-    mut commands: Commands,
+    commands: &mut Commands,
     entries: HashMap<Team, ScoreboardEntry>,
     layer_id: EntityLayerId,
 ) -> Entity {
@@ -2,16 +2,15 @@ use bevy_ecs::{
     entity::Entity,
     event::EventWriter,
     query::{With, Without},
-    system::{Query, Res},
+    system::Query,
 };
-use bevy_state::prelude::in_state;
 use valence::{
     app::{Plugin, Update},
-    entity::Position,
+    entity::{EntityLayerId, Position},
     prelude::IntoSystemConfigs,
 };
 
-use crate::{bedwars_config, GameState, Team};
+use crate::{arena::any_arena_in, bedwars_config, GameState, Team};
 
 use super::death::{IsDead, PlayerHurtEvent};
 
@@ -19,17 +18,25 @@ pub struct VoidDeathPlugin;
 
 impl Plugin for VoidDeathPlugin {
     fn build(&self, app: &mut valence::prelude::App) {
-        app.add_systems(Update, void_death.run_if(in_state(GameState::Match)));
+        app.add_systems(Update, void_death.run_if(any_arena_in(GameState::Match)));
     }
 }
 
 #[allow(clippy::type_complexity)]
 fn void_death(
-    mut clients: Query<(Entity, &Position), (With<Team>, Without<IsDead>)>,
-    bedwars_config: Res<bedwars_config::WorldConfig>,
+    mut clients: Query<(Entity, &EntityLayerId, &Position), (With<Team>, Without<IsDead>)>,
+    arenas: Query<(&GameState, &bedwars_config::WorldConfig)>,
     mut event_writer: EventWriter<PlayerHurtEvent>,
 ) {
-    for (player, position) in &mut clients {
+    for (player, layer_id, position) in &mut clients {
+        let Ok((state, bedwars_config)) = arenas.get(layer_id.0) else {
+            continue;
+        };
+
+        if *state != GameState::Match {
+            continue;
+        }
+
         let void = &bedwars_config.bounds.0.y.min(bedwars_config.bounds.1.y);
         if position.y < *void as f64 {
             event_writer.send(PlayerHurtEvent {
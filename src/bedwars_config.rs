@@ -1,13 +1,27 @@
+use std::path::{Path, PathBuf};
+
 use crate::colors::TeamColor;
+use crate::config_migration::migrate_world_config;
+use crate::team_upgrades::TeamUpgrade;
+use crate::utils::block::{block_state_to_string, parse_block_state};
 use ordermap::OrderMap;
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use valence::math::DVec3;
 use valence::nbt::Compound;
-use valence::prelude::{Block, Resource};
-use valence::{BlockState, ItemKind, ItemStack};
+use valence::prelude::{Block, Component, Resource};
+use valence::{BlockPos, BlockState, ItemKind, ItemStack};
 
 pub const SHOP_CONFIG_NAME: &str = "shop.json";
 pub const WORLD_CONFIG_NAME: &str = "bw-world.json";
+/// Current version of the world config format, older configs are migrated on load
+pub const WORLD_CONFIG_VERSION: u32 = 2;
+pub const RULES_CONFIG_NAME: &str = "rules.json";
+pub const STRUCTURES_CONFIG_NAME: &str = "structures.json";
+pub const WORLD_DIR_NAME: &str = "world";
+/// Every directory in here is an arena, with its own world folder and world config
+pub const ARENAS_DIR_NAME: &str = "arenas";
+/// Every directory in here is a map, with its own world folder, world config and optional shop
+pub const MAPS_DIR_NAME: &str = "maps";
 
 #[derive(Debug, Serialize, Deserialize, Hash, PartialEq, Eq, Clone)]
 pub struct ConfigVec3 {
@@ -50,29 +64,81 @@ impl std::ops::Add for ConfigVec3 {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Resource, Clone)]
+/// A single block of a team bed
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BedBlock {
+    pub pos: ConfigVec3,
+    pub block: SerBlock,
+}
+
+/// A shop villager
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ShopPlacement {
+    pub pos: ConfigVec3,
+    pub yaw: f32,
+    /// If set, the shop will only spawn if the team is in the match
+    #[serde(default)]
+    pub team: Option<String>,
+    #[serde(default)]
+    pub kind: ShopKind,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ShopKind {
+    /// Sells items from `shop_items`
+    #[default]
+    Items,
+    /// Sells the upgrades of the team, see [`TeamUpgradeConfig`]
+    Upgrades,
+}
+
+/// A resource spawner
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SpawnerPlacement {
+    pub pos: ConfigVec3,
+    /// The item stack that will be spawned
+    pub item: SerItemStack,
+    /// Time between two spawns
+    pub interval_secs: f32,
+    /// If set, the spawner will only spawn if the team is in the match
+    #[serde(default)]
+    pub team: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Component, Clone)]
 pub struct WorldConfig {
+    /// Version of the config format, see [`WORLD_CONFIG_VERSION`]
+    pub version: u32,
     /// Bounds of the bedwars arena
     pub bounds: (ConfigVec3, ConfigVec3),
     /// Team name -> team color
     pub teams: OrderMap<String, TeamColor>,
     /// Team name -> spawn point
     pub spawns: OrderMap<String, ConfigVec3>,
-    /// Team name -> bed location & block
-    pub beds: OrderMap<String, Vec<(ConfigVec3, SerBlock)>>,
-    /// Shop location, yaw ->? team name
-    pub shops: Vec<((ConfigVec3, f32), Option<String>)>,
-    /// Resource spawner location -> (resource type, spawn interval secs, team name)
-    pub resource_spawners: Vec<(ConfigVec3, SerItemStack, f32, Option<String>)>,
+    /// Team name -> bed blocks
+    pub beds: OrderMap<String, Vec<BedBlock>>,
+    pub shops: Vec<ShopPlacement>,
+    pub resource_spawners: Vec<SpawnerPlacement>,
     /// Lobby spawn point
     pub lobby_spawn: ConfigVec3,
     /// Spectator spawn point
     pub spectator_spawn: ConfigVec3,
 }
 
+impl WorldConfig {
+    /// Whether a block is inside of the bounds of the arena
+    pub fn is_in_bounds(&self, pos: BlockPos) -> bool {
+        let (a, b) = &self.bounds;
+        (a.x.min(b.x)..=a.x.max(b.x)).contains(&pos.x)
+            && (a.y.min(b.y)..=a.y.max(b.y)).contains(&pos.y)
+            && (a.z.min(b.z)..=a.z.max(b.z)).contains(&pos.z)
+    }
+}
+
 /// Represents a WIP bedwars config, which will be changed
 /// and can be saved once every value is set
-#[derive(Debug, Serialize, Deserialize, Default, Resource)]
+#[derive(Debug, Serialize, Deserialize, Default, Component)]
 pub struct WIPWorldConfig {
     /// Bounds of the bedwars arena
     pub bounds: Option<(ConfigVec3, ConfigVec3)>,
@@ -80,12 +146,10 @@ pub struct WIPWorldConfig {
     pub teams: OrderMap<String, TeamColor>,
     /// Team name -> spawn point
     pub spawns: OrderMap<String, ConfigVec3>,
-    /// Team name -> bed location & block
-    pub beds: OrderMap<String, Vec<(ConfigVec3, SerBlock)>>,
-    /// Shop location, yaw ->? team name
-    pub shops: Vec<((ConfigVec3, f32), Option<String>)>,
-    /// Resource spawner location -> (resource type, spawn interval secs, team name)
-    pub resource_spawners: Vec<(ConfigVec3, SerItemStack, f32, Option<String>)>,
+    /// Team name -> bed blocks
+    pub beds: OrderMap<String, Vec<BedBlock>>,
+    pub shops: Vec<ShopPlacement>,
+    pub resource_spawners: Vec<SpawnerPlacement>,
     /// Lobby spawn point
     pub lobby_spawn: Option<ConfigVec3>,
     /// Spectator spawn point
@@ -104,6 +168,25 @@ impl WIPWorldConfig {
             && self.spectator_spawn.is_some()
     }
 
+    /// Returns the finished config, or `None` if a value is still missing
+    pub fn to_saved_config(&self) -> Option<WorldConfig> {
+        if !self.is_finished() {
+            return None;
+        }
+
+        Some(WorldConfig {
+            version: WORLD_CONFIG_VERSION,
+            bounds: self.bounds.clone()?,
+            teams: self.teams.clone(),
+            spawns: self.spawns.clone(),
+            beds: self.beds.clone(),
+            shops: self.shops.clone(),
+            resource_spawners: self.resource_spawners.clone(),
+            lobby_spawn: self.lobby_spawn.clone()?,
+            spectator_spawn: self.spectator_spawn.clone()?,
+        })
+    }
+
     pub fn from_saved_config(config: &WorldConfig) -> Self {
         Self {
             bounds: Some(config.bounds.clone()),
@@ -119,7 +202,7 @@ impl WIPWorldConfig {
 }
 
 #[derive(Debug, Clone, PartialEq)]
-struct SerItemKind(pub ItemKind);
+pub struct SerItemKind(pub ItemKind);
 
 impl Serialize for SerItemKind {
     fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
@@ -130,7 +213,9 @@ impl Serialize for SerItemKind {
 impl<'de> Deserialize<'de> for SerItemKind {
     fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
         let s = String::deserialize(deserializer)?;
-        Ok(Self(ItemKind::from_str(&s).unwrap()))
+        ItemKind::from_str(&s)
+            .map(Self)
+            .ok_or_else(|| serde::de::Error::custom(format!("unknown item id `{s}`")))
     }
 }
 
@@ -159,16 +244,39 @@ impl From<ItemStack> for SerItemStack {
 #[derive(Debug, Clone, PartialEq)]
 pub struct SerBlockState(BlockState);
 
+/// Serialized as `"minecraft:red_bed[facing=north,part=head]"`,
+/// raw block state ids of older configs can still be deserialized
 impl serde::Serialize for SerBlockState {
     fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
-        self.0.to_raw().serialize(serializer)
+        block_state_to_string(self.0).serialize(serializer)
     }
 }
 
 impl<'de> serde::Deserialize<'de> for SerBlockState {
     fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
-        let s = u16::deserialize(deserializer)?;
-        Ok(Self(BlockState::from_raw(s).unwrap()))
+        struct BlockStateVisitor;
+
+        impl serde::de::Visitor<'_> for BlockStateVisitor {
+            type Value = SerBlockState;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("a block state like `minecraft:red_bed[part=head]`")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                parse_block_state(v).map(SerBlockState).map_err(E::custom)
+            }
+
+            fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<Self::Value, E> {
+                u16::try_from(v)
+                    .ok()
+                    .and_then(BlockState::from_raw)
+                    .map(SerBlockState)
+                    .ok_or_else(|| E::custom(format!("invalid block state id `{v}`")))
+            }
+        }
+
+        deserializer.deserialize_any(BlockStateVisitor)
     }
 }
 
@@ -214,21 +322,443 @@ pub struct ShopPrice {
 pub struct ShopOffer {
     pub offer: SerItemStack,
     pub price: SerItemStack,
+    /// Id of a custom item, the offer is turned into that item
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom_item: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Resource)]
 pub struct ShopConfig {
     pub shop_items: OrderMap<String, (SerItemStack, Vec<ShopOffer>)>,
+    #[serde(default)]
+    pub team_upgrades: TeamUpgradeConfig,
 }
 
-pub fn load_config() -> color_eyre::Result<WorldConfig> {
-    let config = std::fs::read_to_string(WORLD_CONFIG_NAME)?;
-    let config: WorldConfig = serde_json::from_str(&config)?;
-    Ok(config)
+/// Prices of the team upgrades, every price is one tier of the upgrade
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct TeamUpgradeConfig {
+    /// The item the upgrades are paid with
+    pub currency: SerItemKind,
+    pub sharpness: Vec<i8>,
+    pub protection: Vec<i8>,
+    pub forge: Vec<i8>,
+    pub heal_pool: Vec<i8>,
+    /// Price of a trap by its position in the queue, this also limits the queue length
+    pub traps: Vec<i8>,
+}
+
+impl TeamUpgradeConfig {
+    /// The price of every tier of the upgrade
+    pub fn prices(&self, upgrade: TeamUpgrade) -> &[i8] {
+        match upgrade {
+            TeamUpgrade::Sharpness => &self.sharpness,
+            TeamUpgrade::Protection => &self.protection,
+            TeamUpgrade::Forge => &self.forge,
+            TeamUpgrade::HealPool => &self.heal_pool,
+        }
+    }
+}
+
+impl Default for TeamUpgradeConfig {
+    fn default() -> Self {
+        Self {
+            currency: SerItemKind(ItemKind::GoldIngot),
+            sharpness: vec![8],
+            protection: vec![5, 10, 20, 30],
+            forge: vec![4, 8, 16],
+            heal_pool: vec![6],
+            traps: vec![1, 2, 4],
+        }
+    }
+}
+
+/// Paths and startup options of the server
+#[derive(Debug, Clone, Resource)]
+pub struct BedwarsSettings {
+    /// Directory containing the config files and the arenas directory
+    pub config_dir: PathBuf,
+    /// Overrides the world folder, only used if there is a single arena
+    pub world_dir: Option<PathBuf>,
+    /// Start every arena in edit mode, even if it has a world config
+    pub force_edit: bool,
+    /// Names of the players that can use the `/bw` commands
+    pub admins: Vec<String>,
+    /// Number of arenas that rotate through the maps directory
+    pub map_arenas: usize,
+}
+
+impl Default for BedwarsSettings {
+    fn default() -> Self {
+        Self {
+            config_dir: PathBuf::from("."),
+            world_dir: None,
+            force_edit: false,
+            admins: vec![],
+            map_arenas: 1,
+        }
+    }
+}
+
+/// Tuning values of the game, loaded from `rules.json`.
+/// Missing fields will use the default values.
+#[derive(Debug, Serialize, Deserialize, Clone, Resource)]
+#[serde(default)]
+pub struct GameRules {
+    /// Whether players can damage players of their own team
+    pub friendly_fire: bool,
+    /// Time between two melee attacks of a player
+    pub attack_cooldown_millis: u64,
+    /// Damage multiplier for critical hits (attacking while falling)
+    pub crit_multiplier: f32,
+    /// Base knockback of melee attacks and arrows
+    pub knockback: f32,
+    /// Time until a dead player respawns
+    pub respawn_time_secs: u32,
+    /// Time until the players are moved back to the lobby after a match ended
+    pub post_match_time_secs: f32,
+    /// Time a player that disconnected during a match has to rejoin before being eliminated
+    pub reconnect_grace_secs: u32,
+    /// Time it takes to regenerate one health point
+    pub regeneration_secs_per_hp: f32,
+    /// Time after being hit until a player starts regenerating
+    pub regeneration_combat_cooldown_millis: u64,
+    /// Horizontal distance from which players can pick up items
+    pub pickup_range_horizontal: f64,
+    /// Vertical distance from which players can pick up items
+    pub pickup_range_vertical: f64,
+    /// How the next map is chosen after a match
+    pub map_rotation: MapRotationMode,
+    /// Players needed in the lobby to start the countdown
+    pub min_players: usize,
+    /// Teams with at least one player needed to start the countdown
+    pub min_teams: usize,
+    /// Number of players per team
+    pub team_mode: TeamMode,
+    /// Length of the lobby countdown
+    pub countdown_secs: u32,
+    /// The countdown is shortened to this once every team is full
+    pub full_lobby_countdown_secs: u32,
+    /// Events that happen at fixed times during a match
+    pub match_schedule: Vec<MatchEvent>,
+    /// How the winner is picked if the match ends because of the schedule
+    pub tie_break: TieBreak,
+    /// Distance to a bed in which enemies trigger the traps of the team
+    pub trap_radius: f64,
+    /// Whether tnt and fireballs can destroy beds, other map blocks are never destroyed
+    pub explosions_break_beds: bool,
+    /// Horizontal distance a bridge egg flies before it stops building
+    pub bridge_egg_distance: f64,
+    /// Time between two shots of the laser bow
+    pub laser_bow_cooldown_millis: u64,
+    /// Arrows used by one shot of the laser bow
+    pub laser_bow_ammo_cost: i8,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum MapRotationMode {
+    /// Play the maps in alphabetical order
+    #[default]
+    Sequential,
+    /// Pick a random map that is different from the last one
+    Random,
+}
+
+impl Default for GameRules {
+    fn default() -> Self {
+        Self {
+            friendly_fire: false,
+            attack_cooldown_millis: 500,
+            crit_multiplier: 1.5,
+            knockback: 0.4,
+            respawn_time_secs: 5,
+            post_match_time_secs: 10.0,
+            reconnect_grace_secs: 60,
+            regeneration_secs_per_hp: 4.0,
+            regeneration_combat_cooldown_millis: 7000,
+            pickup_range_horizontal: 1.0,
+            pickup_range_vertical: 0.5,
+            map_rotation: MapRotationMode::default(),
+            min_players: 2,
+            min_teams: 2,
+            team_mode: TeamMode::default(),
+            countdown_secs: 30,
+            full_lobby_countdown_secs: 10,
+            match_schedule: default_match_schedule(),
+            tie_break: TieBreak::default(),
+            trap_radius: 8.0,
+            explosions_break_beds: false,
+            bridge_egg_distance: 30.0,
+            laser_bow_cooldown_millis: 1500,
+            laser_bow_ammo_cost: 1,
+        }
+    }
+}
+
+/// An event of the match schedule
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct MatchEvent {
+    /// Name shown to the players, e.g. "Diamond II"
+    pub name: String,
+    /// Time after the start of the match
+    pub time_secs: u32,
+    pub action: MatchEventAction,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MatchEventAction {
+    /// Multiply the interval of every resource spawner that spawns the item
+    UpgradeSpawners {
+        item: SerItemKind,
+        interval_multiplier: f32,
+    },
+    /// Destroy the beds of all teams
+    DestroyBeds,
+    /// Players no longer regenerate health
+    SuddenDeath,
+    /// End the match, the winner is picked with the [`TieBreak`] rule
+    EndMatch,
+}
+
+/// Decides the winner of a match that ended before only one team was left
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TieBreak {
+    /// The team with the most players alive wins
+    #[default]
+    PlayersAlive,
+    /// The team with the most kills wins
+    Kills,
+    /// The match always ends in a draw
+    Draw,
+}
+
+fn default_match_schedule() -> Vec<MatchEvent> {
+    let upgrade = |name: &str, time_secs, item| MatchEvent {
+        name: name.to_string(),
+        time_secs,
+        action: MatchEventAction::UpgradeSpawners {
+            item: SerItemKind(item),
+            interval_multiplier: 0.75,
+        },
+    };
+
+    vec![
+        upgrade("Diamond II", 6 * 60, ItemKind::Diamond),
+        upgrade("Emerald II", 12 * 60, ItemKind::Emerald),
+        upgrade("Diamond III", 18 * 60, ItemKind::Diamond),
+        upgrade("Emerald III", 24 * 60, ItemKind::Emerald),
+        MatchEvent {
+            name: "Bed destruction".to_string(),
+            time_secs: 30 * 60,
+            action: MatchEventAction::DestroyBeds,
+        },
+        MatchEvent {
+            name: "Sudden death".to_string(),
+            time_secs: 40 * 60,
+            action: MatchEventAction::SuddenDeath,
+        },
+        MatchEvent {
+            name: "Game end".to_string(),
+            time_secs: 50 * 60,
+            action: MatchEventAction::EndMatch,
+        },
+    ]
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TeamMode {
+    Solo,
+    Doubles,
+    #[default]
+    Squads,
+}
+
+impl TeamMode {
+    /// Maximum number of players in a team
+    pub fn team_size(&self) -> usize {
+        match self {
+            TeamMode::Solo => 1,
+            TeamMode::Doubles => 2,
+            TeamMode::Squads => 4,
+        }
+    }
+}
+
+/// Structures that are built by items, loaded from `structures.json`.
+/// Missing structures will use the default ones.
+#[derive(Debug, Serialize, Deserialize, Clone, Resource)]
+#[serde(default)]
+pub struct StructureConfig {
+    /// Built by the pop-up tower item
+    pub pop_up_tower: Structure,
+}
+
+impl Default for StructureConfig {
+    fn default() -> Self {
+        Self {
+            pop_up_tower: default_pop_up_tower(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Structure {
+    /// Number of blocks placed every tick
+    pub blocks_per_tick: usize,
+    /// The blocks are placed in this order
+    pub blocks: Vec<StructureBlock>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct StructureBlock {
+    /// Offset to the block in front of the player, for a player looking south (+z).
+    /// The structure is rotated to the direction the player is looking.
+    pub offset: ConfigVec3,
+    /// Wool, carpets and beds are changed to the color of the team
+    pub block: SerBlockState,
+}
+
+/// A 3x3 tower with a ladder inside and a platform on top
+fn default_pop_up_tower() -> Structure {
+    let block = |x, y, z, block: &str| StructureBlock {
+        offset: ConfigVec3::new(x, y, z),
+        block: SerBlockState(parse_block_state(block).expect("valid block state")),
+    };
+
+    let mut blocks = vec![];
+    for y in 0..5 {
+        for x in -1..=1 {
+            for z in 0..=2 {
+                let is_wall = x != 0 || z != 1;
+                let is_door = x == 0 && z == 0 && y < 2;
+                if is_wall && !is_door {
+                    blocks.push(block(x, y, z, "minecraft:white_wool"));
+                }
+            }
+        }
+
+        blocks.push(block(0, y, 1, "minecraft:ladder[facing=north]"));
+    }
+
+    for x in -1..=1 {
+        for z in 0..=2 {
+            if x != 0 || z != 1 {
+                blocks.push(block(x, 5, z, "minecraft:white_wool"));
+            }
+        }
+    }
+
+    blocks.push(block(0, 5, 1, "minecraft:ladder[facing=north]"));
+
+    for (x, z) in [(-1, 0), (1, 0), (-1, 2), (1, 2)] {
+        blocks.push(block(x, 6, z, "minecraft:white_wool"));
+    }
+
+    Structure {
+        blocks_per_tick: 2,
+        blocks,
+    }
 }
 
-pub fn load_trader_config() -> color_eyre::Result<ShopConfig> {
-    let config = std::fs::read_to_string(SHOP_CONFIG_NAME)?;
-    let config: ShopConfig = serde_json::from_str(&config)?;
+/// Returns the directories of all arenas that should be loaded.
+/// If there is no arenas directory, the config directory is the only arena.
+pub fn arena_dirs(config_dir: &Path) -> color_eyre::Result<Vec<PathBuf>> {
+    let arenas_dir = config_dir.join(ARENAS_DIR_NAME);
+    if !arenas_dir.is_dir() {
+        return Ok(vec![config_dir.to_path_buf()]);
+    }
+
+    sub_dirs(&arenas_dir)
+}
+
+/// Returns the directories of all maps, this is empty if there is no maps directory
+pub fn map_dirs(config_dir: &Path) -> color_eyre::Result<Vec<PathBuf>> {
+    let maps_dir = config_dir.join(MAPS_DIR_NAME);
+    if !maps_dir.is_dir() {
+        return Ok(vec![]);
+    }
+
+    sub_dirs(&maps_dir)
+}
+
+/// Returns the sorted directories inside of a directory
+fn sub_dirs(dir: &Path) -> color_eyre::Result<Vec<PathBuf>> {
+    let mut dirs = vec![];
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            dirs.push(path);
+        }
+    }
+
+    dirs.sort();
+    Ok(dirs)
+}
+
+/// Deserialize a json config, errors contain the path to the invalid value
+fn from_json_str<T: DeserializeOwned>(json: &str) -> color_eyre::Result<T> {
+    let deserializer = &mut serde_json::Deserializer::from_str(json);
+    Ok(serde_path_to_error::deserialize(deserializer)?)
+}
+
+/// Load a world config, configs of an older version are migrated
+/// and saved again (the old file is kept as a backup)
+pub fn load_config(path: impl AsRef<Path>) -> color_eyre::Result<WorldConfig> {
+    let path = path.as_ref();
+    let config = std::fs::read_to_string(path)?;
+    let config: serde_json::Value = from_json_str(&config)?;
+
+    let (config, migrated_from) = migrate_world_config(config)?;
+    let config: WorldConfig = serde_path_to_error::deserialize(config)?;
+
+    if let Some(old_version) = migrated_from {
+        let backup_path = path.with_extension(format!("json.v{}.bak", old_version));
+        std::fs::copy(path, &backup_path)?;
+        std::fs::write(path, serde_json::to_string_pretty(&config)?)?;
+
+        tracing::info!(
+            "Migrated {} from version {} to {}, the old config was saved to {}",
+            path.display(),
+            old_version,
+            WORLD_CONFIG_VERSION,
+            backup_path.display()
+        );
+    }
+
     Ok(config)
 }
+
+/// Load the game rules, if there is no rules file the default rules are used
+pub fn load_game_rules(config_dir: &Path) -> color_eyre::Result<GameRules> {
+    let path = config_dir.join(RULES_CONFIG_NAME);
+    if !path.exists() {
+        return Ok(GameRules::default());
+    }
+
+    let rules = std::fs::read_to_string(path)?;
+    from_json_str(&rules)
+}
+
+/// Load the structures, if there is no structures file the default structures are used
+pub fn load_structures(config_dir: &Path) -> color_eyre::Result<StructureConfig> {
+    let path = config_dir.join(STRUCTURES_CONFIG_NAME);
+    if !path.exists() {
+        return Ok(StructureConfig::default());
+    }
+
+    let structures = std::fs::read_to_string(path)?;
+    from_json_str(&structures)
+}
+
+pub fn load_trader_config(config_dir: &Path) -> color_eyre::Result<ShopConfig> {
+    load_shop_config(config_dir.join(SHOP_CONFIG_NAME))
+}
+
+pub fn load_shop_config(path: impl AsRef<Path>) -> color_eyre::Result<ShopConfig> {
+    let config = std::fs::read_to_string(path)?;
+    from_json_str(&config)
+}
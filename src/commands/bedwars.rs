@@ -0,0 +1,56 @@
+use valence::{command, command_macros, prelude::*};
+
+use command::handler::CommandResultEvent;
+use command_macros::Command;
+
+use crate::lobby::countdown::{LobbyCountdown, FORCE_START_SECS};
+use crate::GameState;
+
+#[derive(Command, Debug, Clone)]
+#[paths("bw", "bedwars")]
+#[scopes("bedwars.command.bw")]
+/// These commands can be used by the admins of the server
+pub enum BedwarsCommand {
+    /// Start the match of the lobby, even if there are not enough players
+    #[paths = "forcestart"]
+    ForceStart,
+}
+
+pub fn handle_bedwars_command(
+    mut commands: Commands,
+    mut clients: Query<(&EntityLayerId, &mut Client)>,
+    mut events: EventReader<CommandResultEvent<BedwarsCommand>>,
+    mut arenas: Query<(&GameState, Option<&mut LobbyCountdown>)>,
+) {
+    for event in events.read() {
+        let Ok((layer_id, mut client)) = clients.get_mut(event.executor) else {
+            continue;
+        };
+        let arena = layer_id.0;
+
+        match &event.result {
+            BedwarsCommand::ForceStart => {
+                let Ok((state, countdown)) = arenas.get_mut(arena) else {
+                    continue;
+                };
+
+                if *state != GameState::Lobby {
+                    client.send_chat_message("§cThe match already started");
+                    continue;
+                }
+
+                match countdown {
+                    Some(mut countdown) => {
+                        countdown.remaining = countdown.remaining.min(FORCE_START_SECS);
+                        countdown.forced = true;
+                    }
+                    None => {
+                        commands.entity(arena).insert(LobbyCountdown::forced());
+                    }
+                }
+
+                client.send_chat_message("§aThe match will be started");
+            }
+        }
+    }
+}
@@ -9,8 +9,13 @@ use command::parsers;
 use command_macros::Command;
 use parsers::Vec3 as Vec3Parser;
 
-use crate::bedwars_config::{ConfigVec3, SerBlock, WIPWorldConfig, WORLD_CONFIG_NAME};
+use crate::arena::Arena;
+use crate::bedwars_config::{
+    self, BedBlock, BedwarsSettings, ConfigVec3, ShopKind, ShopPlacement, SpawnerPlacement,
+    WIPWorldConfig,
+};
 use crate::colors::TeamColor;
+use crate::maps::{MapRotation, MapShop};
 use crate::utils::item_kind::ItemKindExtColor;
 
 // TODO: in addition to the commands
@@ -49,6 +54,13 @@ pub enum BedwarsAdminCommand {
         yaw: f32,
         team: Option<String>,
     },
+    /// Add a shop that sells team upgrades, and optionally bind it to a team
+    #[paths = "upgradeshop add {pos} {yaw} {team?}"]
+    AddUpgradeShop {
+        pos: Vec3Parser,
+        yaw: f32,
+        team: Option<String>,
+    },
     /// Add a resource spawner, and optionally bind it to a team
     /// If bound to a team, resources will stop spawning when the team is eliminated
     /// TODO: resource enum instead of string
@@ -86,22 +98,31 @@ pub enum BedwarsAdminCommand {
     /// Save the bedwars arena config
     #[paths = "save"]
     Save,
+
+    /// Reload the shop config and the game rules
+    #[paths = "reload"]
+    Reload,
 }
 
 pub fn handle_bedwars_admin_command(
+    mut commands: Commands,
     // Query the player entities to get their positions
-    mut entities: Query<(Entity, &Position, &mut Client)>,
+    mut entities: Query<(Entity, &Position, &EntityLayerId, &mut Client)>,
     mut events: EventReader<CommandResultEvent<BedwarsAdminCommand>>,
-    mut wip_config: ResMut<WIPWorldConfig>,
-    mut layer: Query<&mut ChunkLayer>,
+    mut arenas: Query<(&Arena, &mut WIPWorldConfig, &mut ChunkLayer)>,
+    map_arenas: Query<(Entity, &Arena, &MapRotation)>,
+    settings: Res<BedwarsSettings>,
 ) {
     for event in events.read() {
         let caller = event.executor;
 
-        let player_pos = *entities.get(caller).unwrap().1;
-        let mut player_client = entities.get_mut(caller).unwrap().2;
+        let (_, player_pos, layer_id, _) = entities.get(caller).unwrap();
+        let (player_pos, arena) = (**player_pos, layer_id.0);
+        let mut player_client = entities.get_mut(caller).unwrap().3;
 
-        let layer = layer.single_mut();
+        let Ok((arena, mut wip_config, layer)) = arenas.get_mut(arena) else {
+            continue;
+        };
 
         match &event.result {
             BedwarsAdminCommand::SetArenaBounds { pos1, pos2 } => {
@@ -129,7 +150,25 @@ pub fn handle_bedwars_admin_command(
             }
             BedwarsAdminCommand::AddShop { pos, yaw, team } => {
                 let pos = absolute_pos(pos, &player_pos);
-                add_shop_command(&mut wip_config, player_client, pos, *yaw, team.as_ref())
+                add_shop_command(
+                    &mut wip_config,
+                    player_client,
+                    pos,
+                    *yaw,
+                    team.as_ref(),
+                    ShopKind::Items,
+                )
+            }
+            BedwarsAdminCommand::AddUpgradeShop { pos, yaw, team } => {
+                let pos = absolute_pos(pos, &player_pos);
+                add_shop_command(
+                    &mut wip_config,
+                    player_client,
+                    pos,
+                    *yaw,
+                    team.as_ref(),
+                    ShopKind::Upgrades,
+                )
             }
             BedwarsAdminCommand::AddSpawner {
                 pos,
@@ -171,7 +210,10 @@ pub fn handle_bedwars_admin_command(
                 *wip_config = WIPWorldConfig::default();
                 player_client.send_chat_message("§aBedwars arena config reset");
             }
-            BedwarsAdminCommand::Save => bedwars_save_command(&wip_config, player_client),
+            BedwarsAdminCommand::Save => bedwars_save_command(arena, &wip_config, player_client),
+            BedwarsAdminCommand::Reload => {
+                bedwars_reload_command(&mut commands, &settings, &map_arenas, player_client)
+            }
         }
     }
 }
@@ -261,10 +303,13 @@ pub fn set_team_bed_command(
         return;
     }
 
-    let mut bed_blocks: Vec<(ConfigVec3, SerBlock)> = vec![];
+    let mut bed_blocks: Vec<BedBlock> = vec![];
 
     if let Some(block) = layer.block(BlockPos::new(bed.x, bed.y, bed.z)) {
-        bed_blocks.push((bed.clone(), block.into_block().into()));
+        bed_blocks.push(BedBlock {
+            pos: bed.clone(),
+            block: block.into_block().into(),
+        });
         if block.state.to_kind().to_item_kind().is_bed() {
             let neighbors = [
                 BlockPos::new(bed.x + 1, bed.y, bed.z),
@@ -276,14 +321,14 @@ pub fn set_team_bed_command(
             for neighbor in neighbors {
                 if let Some(neighbor_block) = layer.block(neighbor) {
                     if neighbor_block.state.to_kind().to_item_kind().is_bed() {
-                        bed_blocks.push((
-                            ConfigVec3 {
+                        bed_blocks.push(BedBlock {
+                            pos: ConfigVec3 {
                                 x: neighbor.x,
                                 y: neighbor.y,
                                 z: neighbor.z,
                             },
-                            neighbor_block.into_block().into(),
-                        ));
+                            block: neighbor_block.into_block().into(),
+                        });
                     }
                 }
             }
@@ -305,6 +350,7 @@ pub fn add_shop_command(
     pos: ConfigVec3,
     yaw: f32,
     team: Option<&String>,
+    kind: ShopKind,
 ) {
     if let Some(team) = team {
         if !wip_config.teams.contains_key(team) {
@@ -314,7 +360,12 @@ pub fn add_shop_command(
     }
 
     player_client.send_chat_message(format!("§aAdded shop at §7{}", pos));
-    wip_config.shops.push(((pos, yaw), team.cloned()));
+    wip_config.shops.push(ShopPlacement {
+        pos,
+        yaw,
+        team: team.cloned(),
+        kind,
+    });
 }
 
 /// [`BedwarsAdminCommand::AddSpawner`] command
@@ -337,12 +388,12 @@ fn add_spawner_command(
     player_client.send_chat_message(format!("§aAdded resource spawner at §7{}", pos));
     // validate resource type
     if let Some(resource) = ItemKind::from_str(resource) {
-        wip_config.resource_spawners.push((
+        wip_config.resource_spawners.push(SpawnerPlacement {
             pos,
-            ItemStack::new(resource, amount as i8, None).into(),
-            interval,
-            team.clone(),
-        ));
+            item: ItemStack::new(resource, amount as i8, None).into(),
+            interval_secs: interval,
+            team: team.clone(),
+        });
         return;
     }
 
@@ -358,7 +409,7 @@ fn remove_spawner_command(
     if !wip_config
         .resource_spawners
         .iter()
-        .any(|(spawner_pos, _, _, _)| spawner_pos == &pos)
+        .any(|spawner| spawner.pos == pos)
     {
         player_client.send_chat_message("§cResource spawner does not exist");
         return;
@@ -366,7 +417,7 @@ fn remove_spawner_command(
 
     wip_config
         .resource_spawners
-        .retain(|(spawner_pos, _, _, _)| spawner_pos != &pos);
+        .retain(|spawner| spawner.pos != pos);
 
     player_client.send_chat_message(format!("§aRemoved resource spawner at §7{}", pos));
 }
@@ -377,18 +428,12 @@ fn remove_shop_command(
     mut player_client: Mut<'_, Client>,
     pos: ConfigVec3,
 ) {
-    if !wip_config
-        .shops
-        .iter()
-        .any(|((shop_pos, _), _)| shop_pos == &pos)
-    {
+    if !wip_config.shops.iter().any(|shop| shop.pos == pos) {
         player_client.send_chat_message("§cShop does not exist");
         return;
     }
 
-    wip_config
-        .shops
-        .retain(|((shop_pos, _), _)| shop_pos != &pos);
+    wip_config.shops.retain(|shop| shop.pos != pos);
 
     player_client.send_chat_message(format!("§aRemoved shop at §7{}", pos));
 }
@@ -413,6 +458,107 @@ pub fn set_spectator_spawn_command(
     wip_config.spectator_spawn = Some(pos);
 }
 
+/// [`BedwarsAdminCommand::Reload`] command
+/// The new configs only replace the current ones if they are valid
+fn bedwars_reload_command(
+    commands: &mut Commands,
+    settings: &BedwarsSettings,
+    map_arenas: &Query<(Entity, &Arena, &MapRotation)>,
+    mut player_client: Mut<'_, Client>,
+) {
+    match bedwars_config::load_trader_config(&settings.config_dir) {
+        Ok(shop_config) => {
+            let errors = shop_config.validate();
+            if errors.is_empty() {
+                commands.insert_resource(shop_config);
+                player_client.send_chat_message("§aReloaded the shop config");
+            } else {
+                player_client.send_chat_message("§cShop config is invalid, keeping the old one:");
+                for error in errors {
+                    player_client.send_chat_message(format!("§c- {}", error));
+                }
+            }
+        }
+        Err(e) => {
+            player_client.send_chat_message(format!(
+                "§cFailed to load the shop config, keeping the old one: {}",
+                e
+            ));
+        }
+    }
+
+    // Arenas that play a map with its own shop
+    for (arena_ent, arena, rotation) in map_arenas {
+        let Some(shop_path) = &rotation.current().shop_path else {
+            continue;
+        };
+
+        if !shop_path.exists() {
+            commands.entity(arena_ent).remove::<MapShop>();
+            continue;
+        }
+
+        match bedwars_config::load_shop_config(shop_path) {
+            Ok(shop_config) => {
+                let errors = shop_config.validate();
+                if errors.is_empty() {
+                    commands.entity(arena_ent).insert(MapShop(shop_config));
+                    player_client
+                        .send_chat_message(format!("§aReloaded the shop of arena {}", arena.name));
+                } else {
+                    player_client.send_chat_message(format!(
+                        "§cShop of arena {} is invalid, keeping the old one:",
+                        arena.name
+                    ));
+                    for error in errors {
+                        player_client.send_chat_message(format!("§c- {}", error));
+                    }
+                }
+            }
+            Err(e) => {
+                player_client.send_chat_message(format!(
+                    "§cFailed to load the shop of arena {}, keeping the old one: {}",
+                    arena.name, e
+                ));
+            }
+        }
+    }
+
+    match bedwars_config::load_game_rules(&settings.config_dir) {
+        Ok(game_rules) => {
+            let errors = game_rules.validate();
+            if errors.is_empty() {
+                commands.insert_resource(game_rules);
+                player_client.send_chat_message("§aReloaded the game rules");
+            } else {
+                player_client.send_chat_message("§cGame rules are invalid, keeping the old ones:");
+                for error in errors {
+                    player_client.send_chat_message(format!("§c- {}", error));
+                }
+            }
+        }
+        Err(e) => {
+            player_client.send_chat_message(format!(
+                "§cFailed to load the game rules, keeping the old ones: {}",
+                e
+            ));
+        }
+    }
+
+    match bedwars_config::load_structures(&settings.config_dir) {
+        Ok(structures) => {
+            commands.insert_resource(structures);
+            player_client.send_chat_message("§aReloaded the structures");
+        }
+        Err(e) => {
+            player_client.send_chat_message(format!(
+                "§cFailed to load the structures, keeping the old ones: {}",
+                e
+            ));
+        }
+    }
+}
+
 /// [`BedwarsAdminCommand::Summary`] command
 fn bedwars_summary_command(wip_config: &WIPWorldConfig, mut player_client: Mut<'_, Client>) {
     let mut message = "§aBedwars Arena Summary\n".to_string();
@@ -467,11 +613,17 @@ fn bedwars_summary_command(wip_config: &WIPWorldConfig, mut player_client: Mut<'
         wip_config
             .shops
             .iter()
-            .map(|((pos, _), team)| format!(
-                "§7{}: {}",
-                team.as_ref()
+            .map(|shop| format!(
+                "§7{}: {}{}",
+                shop.team
+                    .as_ref()
                     .map_or("GLOBAL".to_string(), |team| team.clone()),
-                pos
+                shop.pos,
+                if shop.kind == ShopKind::Upgrades {
+                    " (upgrades)"
+                } else {
+                    ""
+                }
             ))
             .collect::<Vec<_>>()
             .join(", ")
@@ -482,12 +634,14 @@ fn bedwars_summary_command(wip_config: &WIPWorldConfig, mut player_client: Mut<'
         wip_config
             .resource_spawners
             .iter()
-            .map(|(pos, resource, _interval, team)| format!(
+            .map(|spawner| format!(
                 "§7{}: {} ({})",
-                team.as_ref()
+                spawner
+                    .team
+                    .as_ref()
                     .map_or("GLOBAL".to_string(), |team| team.clone()),
-                pos,
-                Into::<ItemStack>::into(resource.clone()).item.to_str()
+                spawner.pos,
+                Into::<ItemStack>::into(spawner.item.clone()).item.to_str()
             ))
             .collect::<Vec<_>>()
             .join(", ")
@@ -524,15 +678,28 @@ fn bedwars_help_command(_player_client: Mut<'_, Client>) {
 }
 
 /// [`BedwarsAdminCommand::Save`] command
-fn bedwars_save_command(wip_config: &WIPWorldConfig, mut player_client: Mut<'_, Client>) {
-    if !wip_config.is_finished() {
+fn bedwars_save_command(
+    arena: &Arena,
+    wip_config: &WIPWorldConfig,
+    mut player_client: Mut<'_, Client>,
+) {
+    let Some(config) = wip_config.to_saved_config() else {
         player_client.send_chat_message("§cBedwars arena is not ready to be saved!");
         return;
+    };
+
+    let errors = config.validate();
+    if !errors.is_empty() {
+        player_client.send_chat_message("§cBedwars arena is invalid:");
+        for error in errors {
+            player_client.send_chat_message(format!("§c- {}", error));
+        }
+        return;
     }
 
     std::fs::write(
-        WORLD_CONFIG_NAME,
-        serde_json::to_string_pretty(wip_config).unwrap(),
+        &arena.config_path,
+        serde_json::to_string_pretty(&config).unwrap(),
     )
     .unwrap();
     player_client.send_chat_message("§aBedwars arena saved!");
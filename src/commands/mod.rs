@@ -1,3 +1,5 @@
+pub mod bedwars;
 pub mod bedwars_admin;
+pub mod party;
 // pub mod bedwars_lobby;
 pub mod utils;
@@ -0,0 +1,288 @@
+use valence::{command, command_macros, prelude::*};
+
+use command::handler::CommandResultEvent;
+use command_macros::Command;
+
+use crate::party::{Party, PartyInvites, PartyMember};
+
+#[derive(Command, Debug, Clone)]
+#[paths("party", "p")]
+#[scopes("bedwars.command.party")]
+/// Parties are placed into the same team
+pub enum PartyCommand {
+    /// Invite a player into your party
+    #[paths = "invite {player}"]
+    Invite { player: String },
+    /// Join the party of a player that invited you, or of the last invitation
+    #[paths = "accept {player?}"]
+    Accept { player: Option<String> },
+    /// Leave your party
+    #[paths = "leave"]
+    Leave,
+    /// List the members of your party
+    #[paths = "list"]
+    List,
+}
+
+type PartyClient<'a> = (
+    Entity,
+    &'a Username,
+    &'a mut Client,
+    Option<&'a PartyMember>,
+    Option<&'a mut PartyInvites>,
+);
+
+pub fn handle_party_command(
+    mut commands: Commands,
+    mut clients: Query<PartyClient>,
+    mut parties: Query<&mut Party>,
+    mut events: EventReader<CommandResultEvent<PartyCommand>>,
+) {
+    for event in events.read() {
+        let caller = event.executor;
+
+        let Ok((_, username, _, party_member, _)) = clients.get(caller) else {
+            continue;
+        };
+        let (username, party_ent) = (username.0.clone(), party_member.map(|member| member.0));
+
+        match &event.result {
+            PartyCommand::Invite { player } => {
+                party_invite_command(
+                    &mut commands,
+                    &mut clients,
+                    &mut parties,
+                    caller,
+                    &username,
+                    party_ent,
+                    player,
+                );
+            }
+            PartyCommand::Accept { player } => {
+                party_accept_command(
+                    &mut commands,
+                    &mut clients,
+                    &mut parties,
+                    caller,
+                    &username,
+                    party_ent,
+                    player.as_deref(),
+                );
+            }
+            PartyCommand::Leave => {
+                let Some(party_ent) = party_ent else {
+                    send_message(&mut clients, caller, "§cYou are not in a party");
+                    continue;
+                };
+
+                leave_party(
+                    &mut commands,
+                    &mut clients,
+                    &mut parties,
+                    caller,
+                    &username,
+                    party_ent,
+                );
+                send_message(&mut clients, caller, "§aYou left the party");
+            }
+            PartyCommand::List => {
+                let Some(party) = party_ent.and_then(|party_ent| parties.get(party_ent).ok())
+                else {
+                    send_message(&mut clients, caller, "§cYou are not in a party");
+                    continue;
+                };
+
+                let members = party
+                    .members
+                    .iter()
+                    .map(|member| {
+                        if *member == party.leader {
+                            format!("§6{} §7(leader)", member)
+                        } else {
+                            format!("§f{}", member)
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join("§7, ");
+
+                let message = format!("§eParty members: {}", members);
+                send_message(&mut clients, caller, &message);
+            }
+        }
+    }
+}
+
+/// [`PartyCommand::Invite`] command
+fn party_invite_command(
+    commands: &mut Commands,
+    clients: &mut Query<PartyClient>,
+    parties: &mut Query<&mut Party>,
+    caller: Entity,
+    username: &str,
+    party_ent: Option<Entity>,
+    player: &str,
+) {
+    if player == username {
+        send_message(clients, caller, "§cYou can not invite yourself");
+        return;
+    }
+
+    let Some(target) = clients
+        .iter()
+        .find(|(_, target_name, ..)| target_name.0 == player)
+        .map(|(target, ..)| target)
+    else {
+        send_message(clients, caller, &format!("§c{} is not online", player));
+        return;
+    };
+
+    if party_ent
+        .and_then(|party_ent| parties.get(party_ent).ok())
+        .is_some_and(|party| party.members.iter().any(|member| member == player))
+    {
+        send_message(
+            clients,
+            caller,
+            &format!("§c{} is already in your party", player),
+        );
+        return;
+    }
+
+    let Ok((_, _, mut target_client, _, invites)) = clients.get_mut(target) else {
+        return;
+    };
+
+    target_client.send_chat_message(format!(
+        "§e{} invited you to their party, use §6/party accept {}§e to join",
+        username, username
+    ));
+
+    match invites {
+        Some(mut invites) => {
+            invites.0.retain(|inviter| inviter != username);
+            invites.0.push(username.to_string());
+        }
+        None => {
+            commands
+                .entity(target)
+                .insert(PartyInvites(vec![username.to_string()]));
+        }
+    }
+
+    send_message(
+        clients,
+        caller,
+        &format!("§aInvited {} to your party", player),
+    );
+}
+
+/// [`PartyCommand::Accept`] command
+fn party_accept_command(
+    commands: &mut Commands,
+    clients: &mut Query<PartyClient>,
+    parties: &mut Query<&mut Party>,
+    caller: Entity,
+    username: &str,
+    party_ent: Option<Entity>,
+    inviter: Option<&str>,
+) {
+    let Ok((_, _, _, _, Some(mut invites))) = clients.get_mut(caller) else {
+        send_message(clients, caller, "§cYou have no party invitations");
+        return;
+    };
+
+    let invite_idx = match inviter {
+        Some(inviter) => invites.0.iter().position(|name| name == inviter),
+        None => invites.0.len().checked_sub(1),
+    };
+
+    let Some(inviter) = invite_idx.map(|idx| invites.0.remove(idx)) else {
+        send_message(clients, caller, "§cYou have no invitation from this player");
+        return;
+    };
+
+    let Some((inviter_ent, inviter_party)) = clients
+        .iter()
+        .find(|(_, name, ..)| name.0 == inviter)
+        .map(|(inviter_ent, _, _, member, _)| (inviter_ent, member.map(|member| member.0)))
+    else {
+        send_message(clients, caller, &format!("§c{} is not online", inviter));
+        return;
+    };
+
+    let inviter_party = inviter_party.filter(|party_ent| parties.contains(*party_ent));
+
+    if let Some(party_ent) = party_ent {
+        if inviter_party == Some(party_ent) {
+            send_message(clients, caller, "§cYou are already in this party");
+            return;
+        }
+
+        leave_party(commands, clients, parties, caller, username, party_ent);
+    }
+
+    // The party is created once the first invitation is accepted
+    let new_party_ent = match inviter_party {
+        Some(party_ent) => {
+            if let Ok(mut party) = parties.get_mut(party_ent) {
+                party.members.push(username.to_string());
+            }
+            party_ent
+        }
+        None => {
+            let party_ent = commands
+                .spawn(Party::new(inviter.clone(), username.to_string()))
+                .id();
+            commands.entity(inviter_ent).insert(PartyMember(party_ent));
+            party_ent
+        }
+    };
+    commands.entity(caller).insert(PartyMember(new_party_ent));
+
+    for (member_ent, _, mut client, member, _) in clients.iter_mut() {
+        // The inviter of a new party is not a member yet
+        if member_ent == inviter_ent || member.is_some_and(|member| member.0 == new_party_ent) {
+            client.send_chat_message(format!("§e{} joined the party", username));
+        }
+    }
+
+    send_message(
+        clients,
+        caller,
+        &format!("§aYou joined the party of {}", inviter),
+    );
+}
+
+/// Remove a player from their party, the party is disbanded if only one member is left
+fn leave_party(
+    commands: &mut Commands,
+    clients: &mut Query<PartyClient>,
+    parties: &mut Query<&mut Party>,
+    player: Entity,
+    username: &str,
+    party_ent: Entity,
+) {
+    commands.entity(player).remove::<PartyMember>();
+
+    let Ok(mut party) = parties.get_mut(party_ent) else {
+        return;
+    };
+
+    party.remove_member(username);
+
+    for (member_ent, _, mut client, member, _) in clients.iter_mut() {
+        if member_ent != player && member.is_some_and(|member| member.0 == party_ent) {
+            client.send_chat_message(format!("§e{} left the party", username));
+        }
+    }
+
+    if party.is_disbanded() {
+        commands.entity(party_ent).despawn();
+    }
+}
+
+fn send_message(clients: &mut Query<PartyClient>, player: Entity, message: &str) {
+    if let Ok((_, _, mut client, ..)) = clients.get_mut(player) {
+        client.send_chat_message(message);
+    }
+}
@@ -0,0 +1,208 @@
+use color_eyre::eyre::{bail, eyre};
+use serde_json::{json, Value};
+
+use valence::BlockState;
+
+use crate::{bedwars_config::WORLD_CONFIG_VERSION, utils::block::block_state_to_string};
+
+type Migration = fn(Value) -> color_eyre::Result<Value>;
+
+/// The migration at index `n` upgrades a world config from version `n` to `n + 1`
+const MIGRATIONS: &[Migration] = &[migrate_v0_to_v1, migrate_v1_to_v2];
+
+const _: () = assert!(MIGRATIONS.len() == WORLD_CONFIG_VERSION as usize);
+
+/// Upgrade a world config to the current version.
+/// Returns the upgraded config and the version it was upgraded from,
+/// or `None` if the config already was up to date.
+pub fn migrate_world_config(mut config: Value) -> color_eyre::Result<(Value, Option<u32>)> {
+    if !config.is_object() {
+        bail!("expected an object");
+    }
+
+    // Configs from before the version field was added are version 0
+    let version = match config.get("version") {
+        Some(version) => version
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| eyre!("version: expected a positive integer"))?,
+        None => 0,
+    };
+
+    if version > WORLD_CONFIG_VERSION {
+        bail!(
+            "version: config version {} is newer than the supported version {}",
+            version,
+            WORLD_CONFIG_VERSION
+        );
+    }
+
+    if version == WORLD_CONFIG_VERSION {
+        return Ok((config, None));
+    }
+
+    for (from, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        tracing::debug!(
+            "Migrating world config from version {} to {}",
+            from,
+            from + 1
+        );
+        config = migration(config)?;
+        config["version"] = json!(from + 1);
+    }
+
+    Ok((config, Some(version)))
+}
+
+/// Replace the tuples of beds, shops and resource spawners with named structs
+fn migrate_v0_to_v1(mut config: Value) -> color_eyre::Result<Value> {
+    if let Some(beds) = config.get_mut("beds").and_then(Value::as_object_mut) {
+        for (team, blocks) in beds.iter_mut() {
+            *blocks = map_array(blocks, &format!("beds.{}", team), |block, path| {
+                let [pos, block] = tuple(block, path)?;
+                Ok(json!({ "pos": pos, "block": block }))
+            })?;
+        }
+    }
+
+    if let Some(shops) = config.get_mut("shops") {
+        *shops = map_array(shops, "shops", |shop, path| {
+            let [location, team] = tuple(shop, path)?;
+            let [pos, yaw] = tuple(&location, &format!("{}[0]", path))?;
+            Ok(json!({ "pos": pos, "yaw": yaw, "team": team }))
+        })?;
+    }
+
+    if let Some(spawners) = config.get_mut("resource_spawners") {
+        *spawners = map_array(spawners, "resource_spawners", |spawner, path| {
+            let [pos, item, interval_secs, team] = tuple(spawner, path)?;
+            Ok(json!({
+                "pos": pos,
+                "item": item,
+                "interval_secs": interval_secs,
+                "team": team,
+            }))
+        })?;
+    }
+
+    Ok(config)
+}
+
+/// Replace the raw block state ids of beds with block state names,
+/// raw ids change between minecraft versions
+fn migrate_v1_to_v2(mut config: Value) -> color_eyre::Result<Value> {
+    let Some(beds) = config.get_mut("beds").and_then(Value::as_object_mut) else {
+        return Ok(config);
+    };
+
+    for (team, blocks) in beds.iter_mut() {
+        *blocks = map_array(blocks, &format!("beds.{}", team), |bed_block, path| {
+            let mut bed_block = bed_block.clone();
+
+            if let Some(state) = bed_block.pointer_mut("/block/state") {
+                if let Some(raw) = state.as_u64() {
+                    let block_state = u16::try_from(raw)
+                        .ok()
+                        .and_then(BlockState::from_raw)
+                        .ok_or_else(|| {
+                            eyre!("{}.block.state: invalid block state id `{}`", path, raw)
+                        })?;
+
+                    *state = json!(block_state_to_string(block_state));
+                }
+            }
+
+            Ok(bed_block)
+        })?;
+    }
+
+    Ok(config)
+}
+
+/// Map every element of a json array
+fn map_array(
+    value: &Value,
+    path: &str,
+    f: impl Fn(&Value, &str) -> color_eyre::Result<Value>,
+) -> color_eyre::Result<Value> {
+    let Some(array) = value.as_array() else {
+        bail!("{}: expected an array", path);
+    };
+
+    array
+        .iter()
+        .enumerate()
+        .map(|(idx, value)| f(value, &format!("{}[{}]", path, idx)))
+        .collect::<color_eyre::Result<Vec<_>>>()
+        .map(Value::Array)
+}
+
+/// Get the elements of a json array with exactly `N` elements
+fn tuple<const N: usize>(value: &Value, path: &str) -> color_eyre::Result<[Value; N]> {
+    value
+        .as_array()
+        .and_then(|array| <[Value; N]>::try_from(array.clone()).ok())
+        .ok_or_else(|| eyre!("{}: expected an array with {} elements", path, N))
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrate_v0() {
+        let config = json!({
+            "bounds": [{ "x": 0, "y": 0, "z": 0 }, { "x": 10, "y": 10, "z": 10 }],
+            "beds": {
+                "red": [[{ "x": 1, "y": 2, "z": 3 }, { "state": 1, "nbt": null }]]
+            },
+            "shops": [[[{ "x": 4, "y": 5, "z": 6 }, 90.0], "red"]],
+            "resource_spawners": [[{ "x": 7, "y": 8, "z": 9 }, { "item": "iron_ingot", "count": 1, "nbt": null }, 1.5, null]]
+        });
+
+        let (config, migrated_from) = migrate_world_config(config).unwrap();
+
+        assert_eq!(migrated_from, Some(0));
+        assert_eq!(config["version"], json!(WORLD_CONFIG_VERSION));
+        assert_eq!(
+            config["beds"]["red"][0],
+            json!({
+                "pos": { "x": 1, "y": 2, "z": 3 },
+                "block": {
+                    "state": block_state_to_string(BlockState::from_raw(1).unwrap()),
+                    "nbt": null
+                }
+            })
+        );
+        assert_eq!(
+            config["shops"][0],
+            json!({ "pos": { "x": 4, "y": 5, "z": 6 }, "yaw": 90.0, "team": "red" })
+        );
+        assert_eq!(
+            config["resource_spawners"][0],
+            json!({
+                "pos": { "x": 7, "y": 8, "z": 9 },
+                "item": { "item": "iron_ingot", "count": 1, "nbt": null },
+                "interval_secs": 1.5,
+                "team": null,
+            })
+        );
+    }
+
+    #[test]
+    fn test_migrate_up_to_date() {
+        let config = json!({ "version": WORLD_CONFIG_VERSION });
+        let (migrated, migrated_from) = migrate_world_config(config.clone()).unwrap();
+
+        assert_eq!(migrated_from, None);
+        assert_eq!(migrated, config);
+    }
+
+    #[test]
+    fn test_migrate_invalid_shop() {
+        let config = json!({ "shops": [[{ "x": 4, "y": 5, "z": 6 }, "red"]] });
+        let err = migrate_world_config(config).unwrap_err();
+
+        assert!(err.to_string().starts_with("shops[0][0]"));
+    }
+}
@@ -0,0 +1,429 @@
+use valence::{BlockState, ItemKind, ItemStack};
+
+use crate::bedwars_config::{
+    ConfigVec3, GameRules, MatchEventAction, SerItemStack, ShopConfig, WorldConfig,
+};
+use crate::team_upgrades::TeamUpgrade;
+
+/// A problem found in a config file
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("{path}: {message}")]
+pub struct ConfigError {
+    /// Json path of the invalid value, like `beds.red[0].pos`
+    pub path: String,
+    pub message: String,
+}
+
+impl ConfigError {
+    fn new(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl WorldConfig {
+    /// Check the config for invalid values and references to teams that do not exist.
+    /// Returns every problem that was found.
+    pub fn validate(&self) -> Vec<ConfigError> {
+        let mut errors = vec![];
+
+        let min = ConfigVec3::new(
+            self.bounds.0.x.min(self.bounds.1.x),
+            self.bounds.0.y.min(self.bounds.1.y),
+            self.bounds.0.z.min(self.bounds.1.z),
+        );
+        let max = ConfigVec3::new(
+            self.bounds.0.x.max(self.bounds.1.x),
+            self.bounds.0.y.max(self.bounds.1.y),
+            self.bounds.0.z.max(self.bounds.1.z),
+        );
+
+        let check_in_bounds = |path: String, pos: &ConfigVec3, errors: &mut Vec<ConfigError>| {
+            let in_bounds = (min.x..=max.x).contains(&pos.x)
+                && (min.y..=max.y).contains(&pos.y)
+                && (min.z..=max.z).contains(&pos.z);
+
+            if !in_bounds {
+                errors.push(ConfigError::new(
+                    path,
+                    format!("{} is outside of the arena bounds {} - {}", pos, min, max),
+                ));
+            }
+        };
+
+        let check_team = |path: String, team: &str, errors: &mut Vec<ConfigError>| {
+            if !self.teams.contains_key(team) {
+                errors.push(ConfigError::new(path, format!("unknown team `{team}`")));
+            }
+        };
+
+        if self.teams.is_empty() {
+            errors.push(ConfigError::new("teams", "there are no teams"));
+        }
+
+        for team in self.teams.keys() {
+            if !self.spawns.contains_key(team) {
+                errors.push(ConfigError::new(
+                    "spawns",
+                    format!("team `{team}` has no spawn"),
+                ));
+            }
+
+            if !self.beds.contains_key(team) {
+                errors.push(ConfigError::new(
+                    "beds",
+                    format!("team `{team}` has no bed"),
+                ));
+            }
+        }
+
+        for (team, pos) in &self.spawns {
+            check_team(format!("spawns.{team}"), team, &mut errors);
+            check_in_bounds(format!("spawns.{team}"), pos, &mut errors);
+        }
+
+        for (team, bed) in &self.beds {
+            check_team(format!("beds.{team}"), team, &mut errors);
+
+            if bed.is_empty() {
+                errors.push(ConfigError::new(
+                    format!("beds.{team}"),
+                    "bed has no blocks",
+                ));
+            }
+
+            for (idx, bed_block) in bed.iter().enumerate() {
+                check_in_bounds(
+                    format!("beds.{team}[{idx}].pos"),
+                    &bed_block.pos,
+                    &mut errors,
+                );
+
+                let state = BlockState::from(bed_block.block.state.clone());
+                if !state.to_kind().to_str().ends_with("_bed") {
+                    errors.push(ConfigError::new(
+                        format!("beds.{team}[{idx}].block.state"),
+                        format!("`{}` is not a bed", state.to_kind().to_str()),
+                    ));
+                }
+            }
+        }
+
+        for (idx, shop) in self.shops.iter().enumerate() {
+            check_in_bounds(format!("shops[{idx}].pos"), &shop.pos, &mut errors);
+
+            if let Some(team) = &shop.team {
+                check_team(format!("shops[{idx}].team"), team, &mut errors);
+            }
+        }
+
+        if self.resource_spawners.is_empty() {
+            errors.push(ConfigError::new(
+                "resource_spawners",
+                "there are no resource spawners",
+            ));
+        }
+
+        for (idx, spawner) in self.resource_spawners.iter().enumerate() {
+            check_in_bounds(
+                format!("resource_spawners[{idx}].pos"),
+                &spawner.pos,
+                &mut errors,
+            );
+            validate_stack(
+                format!("resource_spawners[{idx}].item"),
+                &spawner.item,
+                &mut errors,
+            );
+
+            if !spawner.interval_secs.is_finite() || spawner.interval_secs <= 0.0 {
+                errors.push(ConfigError::new(
+                    format!("resource_spawners[{idx}].interval_secs"),
+                    format!(
+                        "spawn interval must be greater than 0, got {}",
+                        spawner.interval_secs
+                    ),
+                ));
+            }
+
+            if let Some(team) = &spawner.team {
+                check_team(format!("resource_spawners[{idx}].team"), team, &mut errors);
+            }
+        }
+
+        errors
+    }
+}
+
+impl ShopConfig {
+    /// Check the shop for invalid item stacks.
+    /// Returns every problem that was found.
+    pub fn validate(&self) -> Vec<ConfigError> {
+        let mut errors = vec![];
+
+        for (category, (icon, offers)) in &self.shop_items {
+            validate_stack(format!("shop_items.{category}[0]"), icon, &mut errors);
+
+            for (idx, offer) in offers.iter().enumerate() {
+                validate_stack(
+                    format!("shop_items.{category}[1][{idx}].offer"),
+                    &offer.offer,
+                    &mut errors,
+                );
+                validate_stack(
+                    format!("shop_items.{category}[1][{idx}].price"),
+                    &offer.price,
+                    &mut errors,
+                );
+            }
+        }
+
+        let currency = self.team_upgrades.currency.0;
+        for upgrade in TeamUpgrade::ALL {
+            for (tier, price) in self.team_upgrades.prices(upgrade).iter().enumerate() {
+                if *price < 1 || *price > currency.max_stack() {
+                    errors.push(ConfigError::new(
+                        format!("team_upgrades.{}[{tier}]", upgrade.config_name()),
+                        format!(
+                            "price must be between 1 and {}, got {price}",
+                            currency.max_stack()
+                        ),
+                    ));
+                }
+            }
+        }
+
+        for (idx, price) in self.team_upgrades.traps.iter().enumerate() {
+            if *price < 1 || *price > currency.max_stack() {
+                errors.push(ConfigError::new(
+                    format!("team_upgrades.traps[{idx}]"),
+                    format!(
+                        "price must be between 1 and {}, got {price}",
+                        currency.max_stack()
+                    ),
+                ));
+            }
+        }
+
+        errors
+    }
+}
+
+impl GameRules {
+    /// Check the rules for values that would break a match.
+    /// Returns every problem that was found.
+    pub fn validate(&self) -> Vec<ConfigError> {
+        let mut errors = vec![];
+
+        for (path, value) in [
+            ("crit_multiplier", self.crit_multiplier as f64),
+            ("knockback", self.knockback as f64),
+            ("post_match_time_secs", self.post_match_time_secs as f64),
+            ("pickup_range_horizontal", self.pickup_range_horizontal),
+            ("pickup_range_vertical", self.pickup_range_vertical),
+            ("trap_radius", self.trap_radius),
+            ("bridge_egg_distance", self.bridge_egg_distance),
+        ] {
+            if !value.is_finite() || value < 0.0 {
+                errors.push(ConfigError::new(
+                    path,
+                    format!("must be 0 or greater, got {value}"),
+                ));
+            }
+        }
+
+        let secs_per_hp = self.regeneration_secs_per_hp;
+        if !secs_per_hp.is_finite() || secs_per_hp <= 0.0 {
+            errors.push(ConfigError::new(
+                "regeneration_secs_per_hp",
+                format!("must be greater than 0, got {secs_per_hp}"),
+            ));
+        }
+
+        let max_ammo = ItemKind::Arrow.max_stack();
+        if !(0..=max_ammo).contains(&self.laser_bow_ammo_cost) {
+            errors.push(ConfigError::new(
+                "laser_bow_ammo_cost",
+                format!(
+                    "must be between 0 and {max_ammo}, got {}",
+                    self.laser_bow_ammo_cost
+                ),
+            ));
+        }
+
+        for (idx, event) in self.match_schedule.iter().enumerate() {
+            if let MatchEventAction::UpgradeSpawners {
+                interval_multiplier,
+                ..
+            } = &event.action
+            {
+                if !interval_multiplier.is_finite() || *interval_multiplier <= 0.0 {
+                    errors.push(ConfigError::new(
+                        format!("match_schedule[{idx}].action.interval_multiplier"),
+                        format!("must be greater than 0, got {interval_multiplier}"),
+                    ));
+                }
+            }
+        }
+
+        errors
+    }
+}
+
+fn validate_stack(path: String, stack: &SerItemStack, errors: &mut Vec<ConfigError>) {
+    let stack = ItemStack::from(stack.clone());
+    let max_stack = stack.item.max_stack();
+
+    if stack.count < 1 || stack.count > max_stack {
+        errors.push(ConfigError::new(
+            format!("{path}.count"),
+            format!(
+                "count of `{}` must be between 1 and {max_stack}, got {}",
+                stack.item.to_str(),
+                stack.count
+            ),
+        ));
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn world_config() -> WorldConfig {
+        serde_json::from_value(json!({
+            "version": crate::bedwars_config::WORLD_CONFIG_VERSION,
+            "bounds": [{ "x": 0, "y": 0, "z": 0 }, { "x": 10, "y": 10, "z": 10 }],
+            "teams": { "red": "Red" },
+            "spawns": { "red": { "x": 1, "y": 1, "z": 1 } },
+            "beds": {
+                "red": [{
+                    "pos": { "x": 2, "y": 1, "z": 2 },
+                    "block": { "state": "minecraft:red_bed[part=head]", "nbt": null }
+                }]
+            },
+            "shops": [{ "pos": { "x": 3, "y": 1, "z": 3 }, "yaw": 0.0, "team": "red" }],
+            "resource_spawners": [{
+                "pos": { "x": 4, "y": 1, "z": 4 },
+                "item": { "item": "iron_ingot", "count": 1, "nbt": null },
+                "interval_secs": 1.0,
+                "team": "red"
+            }],
+            "lobby_spawn": { "x": 0, "y": 20, "z": 0 },
+            "spectator_spawn": { "x": 0, "y": 20, "z": 0 }
+        }))
+        .unwrap()
+    }
+
+    fn shop_config() -> ShopConfig {
+        serde_json::from_value(json!({
+            "shop_items": {
+                "Blocks": [
+                    { "item": "white_wool", "count": 1, "nbt": null },
+                    [{
+                        "offer": { "item": "white_wool", "count": 4, "nbt": null },
+                        "price": { "item": "brick", "count": 1, "nbt": null }
+                    }]
+                ]
+            }
+        }))
+        .unwrap()
+    }
+
+    fn paths(errors: &[ConfigError]) -> Vec<&str> {
+        errors.iter().map(|error| error.path.as_str()).collect()
+    }
+
+    #[test]
+    fn test_valid_world_config() {
+        assert_eq!(world_config().validate(), vec![]);
+    }
+
+    #[test]
+    fn test_invalid_world_config() {
+        let mut config = world_config();
+        config
+            .spawns
+            .insert("blue".to_string(), ConfigVec3::new(20, 1, 1));
+        config.beds["red"][0].block.state = BlockState::STONE.into();
+        config.resource_spawners[0].interval_secs = 0.0;
+
+        assert_eq!(
+            paths(&config.validate()),
+            vec![
+                "spawns.blue",
+                "spawns.blue",
+                "beds.red[0].block.state",
+                "resource_spawners[0].interval_secs",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_world_config_missing_team_values() {
+        let mut config = world_config();
+        config.spawns.clear();
+        config.beds.clear();
+        config.resource_spawners.clear();
+
+        assert_eq!(
+            paths(&config.validate()),
+            vec!["spawns", "beds", "resource_spawners"]
+        );
+    }
+
+    #[test]
+    fn test_valid_shop_config() {
+        assert_eq!(shop_config().validate(), vec![]);
+    }
+
+    #[test]
+    fn test_invalid_shop_config() {
+        let mut config = shop_config();
+        config.shop_items["Blocks"].1[0].offer =
+            ItemStack::new(ItemKind::WhiteWool, 65, None).into();
+        config.team_upgrades.sharpness = vec![0];
+
+        assert_eq!(
+            paths(&config.validate()),
+            vec![
+                "shop_items.Blocks[1][0].offer.count",
+                "team_upgrades.sharpness[0]",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_valid_game_rules() {
+        assert_eq!(GameRules::default().validate(), vec![]);
+    }
+
+    #[test]
+    fn test_invalid_game_rules() {
+        let rules: GameRules = serde_json::from_value(json!({
+            "post_match_time_secs": -1.0,
+            "regeneration_secs_per_hp": 0.0,
+            "laser_bow_ammo_cost": -1,
+            "match_schedule": [{
+                "name": "Diamond II",
+                "time_secs": 60,
+                "action": { "type": "upgrade_spawners", "item": "diamond", "interval_multiplier": -0.5 }
+            }]
+        }))
+        .unwrap();
+
+        assert_eq!(
+            paths(&rules.validate()),
+            vec![
+                "post_match_time_secs",
+                "regeneration_secs_per_hp",
+                "laser_bow_ammo_cost",
+                "match_schedule[0].action.interval_multiplier",
+            ]
+        );
+    }
+}
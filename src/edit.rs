@@ -1,4 +1,3 @@
-use bevy_state::prelude::in_state;
 use valence::{
     abilities::PlayerAbilitiesFlags,
     command::scopes::CommandScopes,
@@ -13,7 +12,8 @@ use valence::{
 };
 
 use crate::{
-    bedwars_config::WIPWorldConfig,
+    arena::any_arena_in,
+    bedwars_config::{ShopKind, WIPWorldConfig},
     commands::bedwars_admin::{
         add_shop_command, set_lobby_spawn_command, set_spectator_spawn_command,
         set_team_bed_command, set_team_spawn_command,
@@ -43,7 +43,7 @@ impl Plugin for EditPlugin {
                 edit_click_block,
                 on_change_team_mode,
             )
-                .run_if(in_state(GameState::Edit)),
+                .run_if(any_arena_in(GameState::Edit)),
         );
     }
 }
@@ -150,17 +150,21 @@ fn init_editor(
 
 fn edit_right_click(
     mut commands: Commands,
-    mut clients: Query<(&mut Client, &HeldItem), With<Editor>>,
+    mut clients: Query<(&mut Client, &EntityLayerId, &HeldItem), With<Editor>>,
     mut events: EventReader<InteractItemEvent>,
-    wip_config: Res<WIPWorldConfig>,
+    arenas: Query<&WIPWorldConfig>,
 ) {
     for event in events.read() {
-        let Ok((mut client, held_item)) = clients.get_mut(event.client) else {
+        let Ok((mut client, layer_id, held_item)) = clients.get_mut(event.client) else {
+            continue;
+        };
+
+        let Ok(wip_config) = arenas.get(layer_id.0) else {
             continue;
         };
 
         if held_item.hotbar_idx() == 8 {
-            open_team_selection_menu(&mut commands, event.client, &mut client, &wip_config)
+            open_team_selection_menu(&mut commands, event.client, &mut client, wip_config)
         }
     }
 }
@@ -223,17 +227,32 @@ fn open_team_selection_menu(
     commands.entity(player_ent).insert(menu);
 }
 
+#[allow(clippy::type_complexity)]
 fn on_change_team_mode(
     mut commands: Commands,
-    mut clients: Query<(Entity, &mut Client, &mut Inventory, &Position), With<Editor>>,
+    mut clients: Query<
+        (
+            Entity,
+            &EntityLayerId,
+            &mut Client,
+            &mut Inventory,
+            &Position,
+        ),
+        With<Editor>,
+    >,
     mut events: EventReader<MenuItemSelectEvent>,
-    wip_config: Res<WIPWorldConfig>,
+    arenas: Query<&WIPWorldConfig>,
 ) {
     for event in events.read() {
-        let Ok((player_ent, mut client, mut inventory, position)) = clients.get_mut(event.client)
+        let Ok((player_ent, layer_id, mut client, mut inventory, position)) =
+            clients.get_mut(event.client)
         else {
             continue;
         };
+
+        let Ok(wip_config) = arenas.get(layer_id.0) else {
+            continue;
+        };
         let selected_slot = event.idx;
 
         if selected_slot == 0 {
@@ -338,13 +357,15 @@ fn on_change_team_mode(
 }
 
 fn edit_click_block(
-    mut clients: Query<(&mut Client, &Look, &HeldItem, Option<&Team>)>,
+    mut clients: Query<
+        (&mut Client, &EntityLayerId, &Look, &HeldItem, Option<&Team>),
+        With<Editor>,
+    >,
     mut events: EventReader<InteractBlockEvent>,
-    mut layers: Query<&mut ChunkLayer>,
-    mut wip_config: ResMut<WIPWorldConfig>,
+    mut arenas: Query<(&mut ChunkLayer, &mut WIPWorldConfig)>,
 ) {
     for event in events.read() {
-        let Ok((client, look, held_item, team)) = clients.get_mut(event.client) else {
+        let Ok((client, layer_id, look, held_item, team)) = clients.get_mut(event.client) else {
             continue;
         };
 
@@ -352,7 +373,9 @@ fn edit_click_block(
             continue;
         }
 
-        let layer = layers.single_mut();
+        let Ok((layer, mut wip_config)) = arenas.get_mut(layer_id.0) else {
+            continue;
+        };
 
         let config_vec_pos = crate::bedwars_config::ConfigVec3 {
             x: event.position.x,
@@ -396,6 +419,7 @@ fn edit_click_block(
                     config_vec_pos + crate::bedwars_config::ConfigVec3::up(),
                     yaw,
                     team.map(|t| t.name.clone()).as_ref(),
+                    ShopKind::Items,
                 );
             }
             // TODO: team chest
@@ -0,0 +1,244 @@
+use std::{sync::Arc, time::Duration};
+
+use bevy_ecs::system::RunSystemOnce;
+use valence::{
+    entity::{egg::EggEntityBundle, entity::NoGravity, Velocity},
+    prelude::*,
+    protocol::{sound::SoundCategory, Sound},
+};
+
+use crate::{
+    base::{
+        bow::calculate_projectile_velocity,
+        build::PlayerPlacedBlocks,
+        combat::{look_direction, CombatState},
+        physics::{
+            CollidesWithBlocks, CollidesWithEntities, EntityBlockCollisionEvent, Gravity,
+            PhysicsMarker,
+        },
+    },
+    bedwars_config::{GameRules, WorldConfig},
+    colors::TeamColor,
+    items::custom_item::{
+        custom_projectile_hits, CustomItem, CustomItemAppExt, CustomItemHooks, CustomProjectile,
+        ItemUse,
+    },
+    utils::despawn_timer::DespawnTimer,
+    GameState, Team,
+};
+
+pub const BRIDGE_EGG_ID: &str = "bridge_egg";
+/// Speed of the bridge egg in blocks per tick
+const BRIDGE_EGG_SPEED: f32 = 1.0;
+/// Low gravity, so the bridge only slopes down a little
+const BRIDGE_EGG_GRAVITY: f32 = 2.0;
+/// Blocks between the egg and the bridge
+const BRIDGE_DEPTH: i32 = 2;
+/// Distance between the points of the flight path the bridge is built below,
+/// smaller than a block so the bridge has no gaps
+const BRIDGE_STEP: f64 = 0.25;
+
+/// A thrown bridge egg, builds a bridge below its flight path
+#[derive(Debug, Component)]
+pub struct BridgeEgg {
+    pub color: TeamColor,
+    pub start: DVec3,
+    /// Position in the last tick, the bridge is built between this and the current position
+    pub last_position: DVec3,
+}
+
+pub struct BridgeEggPlugin;
+
+impl Plugin for BridgeEggPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_custom_item(CustomItem {
+            id: BRIDGE_EGG_ID.to_string(),
+            base: ItemKind::Egg,
+            name: "Bridge Egg".to_string(),
+            lore: vec!["Lays a bridge along its flight path".to_string()],
+            cooldown: Duration::ZERO,
+            consumed_on_use: true,
+            hooks: Arc::new(BridgeEggHooks),
+        })
+        // The last part of the bridge is built before the egg is despawned by a hit
+        .add_systems(Update, build_bridge.before(custom_projectile_hits));
+    }
+}
+
+struct BridgeEggHooks;
+
+impl CustomItemHooks for BridgeEggHooks {
+    fn on_use(&self, world: &mut World, item_use: ItemUse) -> bool {
+        world.run_system_once_with(item_use, throw_bridge_egg)
+    }
+
+    /// The bridge ends where the egg hits a block
+    fn on_hit_block(&self, world: &mut World, egg: Entity, _hit: &EntityBlockCollisionEvent) {
+        if let Some(mut egg) = world.get_entity_mut(egg) {
+            egg.insert(Despawned);
+        }
+    }
+
+    /// The bridge ends where the egg hits a player
+    fn on_hit_entity(&self, world: &mut World, egg: Entity, _hit: Entity) {
+        if let Some(mut egg) = world.get_entity_mut(egg) {
+            egg.insert(Despawned);
+        }
+    }
+}
+
+fn throw_bridge_egg(
+    In(item_use): In<ItemUse>,
+    mut commands: Commands,
+    clients: Query<(&EntityLayerId, &Position, &CombatState, &Look, &Team)>,
+    mut layers: Query<&mut ChunkLayer>,
+) -> bool {
+    let Ok((layer_id, position, combat_state, look, team)) = clients.get(item_use.user) else {
+        return false;
+    };
+
+    let Ok(mut layer) = layers.get_mut(layer_id.0) else {
+        return false;
+    };
+
+    layer.play_sound(
+        Sound::EntityEggThrow,
+        SoundCategory::Player,
+        position.0,
+        0.5,
+        0.4,
+    );
+
+    let direction = look_direction(look);
+
+    let velocity = calculate_projectile_velocity(direction, BRIDGE_EGG_SPEED, 0.0);
+
+    let mut position = combat_state.eye_position(position.0);
+    position.y -= 0.1;
+
+    commands
+        .spawn(EggEntityBundle {
+            position: Position(position),
+            velocity: Velocity(velocity),
+            layer: *layer_id,
+            entity_no_gravity: NoGravity(true),
+            ..Default::default()
+        })
+        .insert(PhysicsMarker)
+        .insert(CollidesWithBlocks(None))
+        .insert(CollidesWithEntities(None))
+        .insert(Gravity(BRIDGE_EGG_GRAVITY))
+        .insert(DespawnTimer::from_secs(10.0))
+        .insert(CustomProjectile {
+            item: BRIDGE_EGG_ID.to_string(),
+            owner: item_use.user,
+        })
+        .insert(BridgeEgg {
+            color: team.color,
+            start: position,
+            last_position: position,
+        });
+
+    true
+}
+
+/// Place wool below the path the egg traveled since the last tick
+fn build_bridge(
+    mut commands: Commands,
+    mut eggs: Query<(Entity, &mut BridgeEgg, &EntityLayerId, &Position)>,
+    mut arenas: Query<(
+        &GameState,
+        &WorldConfig,
+        &mut ChunkLayer,
+        &mut PlayerPlacedBlocks,
+    )>,
+    rules: Res<GameRules>,
+) {
+    for (entity, mut egg, layer_id, position) in &mut eggs {
+        let Ok((state, bedwars_config, mut layer, mut player_placed_blocks)) =
+            arenas.get_mut(layer_id.0)
+        else {
+            continue;
+        };
+
+        if *state != GameState::Match {
+            commands.entity(entity).insert(Despawned);
+            continue;
+        }
+
+        let block = BlockState::from_kind(
+            BlockKind::from_item_kind(egg.color.wool_block()).unwrap_or(BlockKind::WhiteWool),
+        );
+
+        let mut placed = false;
+        for pos in bridge_blocks(egg.last_position, position.0) {
+            if !bedwars_config.is_in_bounds(pos)
+                || layer
+                    .block(pos)
+                    .map_or(true, |current| !current.state.is_air())
+            {
+                continue;
+            }
+
+            layer.set_block(pos, block);
+            player_placed_blocks.0.insert(pos, block);
+            placed = true;
+        }
+
+        if placed {
+            layer.play_sound(
+                Sound::BlockWoolPlace,
+                SoundCategory::Block,
+                position.0,
+                0.5,
+                1.0,
+            );
+        }
+
+        egg.last_position = position.0;
+
+        let traveled = (position.0.x - egg.start.x).hypot(position.0.z - egg.start.z);
+        if traveled >= rules.bridge_egg_distance {
+            commands.entity(entity).insert(Despawned);
+        }
+    }
+}
+
+/// The blocks below the path between two positions
+fn bridge_blocks(from: DVec3, to: DVec3) -> Vec<BlockPos> {
+    let steps = (from.distance(to) / BRIDGE_STEP).ceil().max(1.0) as usize;
+
+    let mut blocks = vec![];
+    for step in 0..=steps {
+        let point = from.lerp(to, step as f64 / steps as f64);
+        let pos = BlockPos::new(
+            point.x.floor() as i32,
+            point.y.floor() as i32 - BRIDGE_DEPTH,
+            point.z.floor() as i32,
+        );
+
+        if !blocks.contains(&pos) {
+            blocks.push(pos);
+        }
+    }
+
+    blocks
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bridge_blocks() {
+        let blocks = bridge_blocks(DVec3::new(0.5, 10.5, 0.5), DVec3::new(2.5, 10.5, 1.5));
+
+        assert_eq!(blocks.first(), Some(&BlockPos::new(0, 8, 0)));
+        assert_eq!(blocks.last(), Some(&BlockPos::new(2, 8, 1)));
+        // Every block touches the previous one, so the bridge has no gaps
+        for pair in blocks.windows(2) {
+            let distance = (pair[0].x - pair[1].x).abs() + (pair[0].z - pair[1].z).abs();
+            assert_eq!(distance, 1);
+        }
+    }
+}
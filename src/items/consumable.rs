@@ -1,27 +1,256 @@
-use valence::prelude::*;
+use bevy_time::{Time, Timer, TimerMode};
+use valence::{
+    entity::living::LivingFlags,
+    event_loop::PacketEvent,
+    interact_item::InteractItemEvent,
+    inventory::{HeldItem, PlayerAction},
+    prelude::*,
+    protocol::packets::play::PlayerActionC2s,
+};
 
+use crate::{base::death::IsDead, utils::inventory::InventoryExt, Spectator};
+
+/// An item that is consumed by holding right click
+#[derive(Debug, Clone)]
 pub struct Consumable {
+    /// Only the item kind is compared, so items with different nbt can share a consumable
     pub item: ItemStack,
     pub consumption_time_millis: u64,
+    /// Given to the player after the item was consumed, can be empty
     pub after_consumption: ItemStack,
 }
 
+/// Every item that can be consumed
+#[derive(Debug, Clone, Resource)]
+pub struct Consumables(pub Vec<Consumable>);
+
+impl Default for Consumables {
+    fn default() -> Self {
+        Self(vec![
+            Consumable {
+                item: ItemStack::new(ItemKind::GoldenApple, 1, None),
+                consumption_time_millis: 1600,
+                after_consumption: ItemStack::EMPTY,
+            },
+            Consumable {
+                item: ItemStack::new(ItemKind::Potion, 1, None),
+                consumption_time_millis: 1600,
+                after_consumption: ItemStack::EMPTY,
+            },
+        ])
+    }
+}
+
+impl Consumables {
+    pub fn get(&self, item: ItemKind) -> Option<&Consumable> {
+        self.0
+            .iter()
+            .find(|consumable| consumable.item.item == item)
+    }
+}
+
+/// Sent after a player finished consuming an item, the item is already removed
 #[derive(Debug, Event)]
 pub struct ConsumptionEvent {
     pub consumed: ItemStack,
     pub consumer: Entity,
 }
 
-// pub struct ConsumptionTimer(pub Timer);
+/// Attached to players while they are consuming the item in their hand
+#[derive(Debug, Component)]
+struct Consuming {
+    slot: u16,
+    timer: Timer,
+}
+
+pub struct ConsumablePlugin;
+
+impl Plugin for ConsumablePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Consumables>()
+            .add_systems(
+                Update,
+                (start_consumption, stop_consumption, tick_consumption).chain(),
+            )
+            .add_event::<ConsumptionEvent>();
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn start_consumption(
+    mut commands: Commands,
+    mut players: Query<
+        (&Inventory, &HeldItem, &mut LivingFlags),
+        (Without<IsDead>, Without<Spectator>),
+    >,
+    mut events: EventReader<InteractItemEvent>,
+    consumables: Res<Consumables>,
+) {
+    for event in events.read() {
+        if event.hand != Hand::Main {
+            continue;
+        }
+
+        let Ok((inventory, held_item, mut flags)) = players.get_mut(event.client) else {
+            continue;
+        };
+
+        let slot = held_item.slot();
+        let Some(consumable) = consumables.get(inventory.slot(slot).item) else {
+            continue;
+        };
+
+        flags.set_using_item(true);
+
+        commands.entity(event.client).insert(Consuming {
+            slot,
+            timer: Timer::new(
+                std::time::Duration::from_millis(consumable.consumption_time_millis),
+                TimerMode::Once,
+            ),
+        });
+    }
+}
+
+/// Cancel the consumption if the player releases right click
+fn stop_consumption(
+    mut commands: Commands,
+    mut players: Query<&mut LivingFlags, With<Consuming>>,
+    mut packet_events: EventReader<PacketEvent>,
+) {
+    for packet in packet_events.read() {
+        let Some(player_action) = packet.decode::<PlayerActionC2s>() else {
+            continue;
+        };
+
+        if player_action.action != PlayerAction::ReleaseUseItem {
+            continue;
+        }
 
-// pub struct ConsumablePlugin;
+        let Ok(mut flags) = players.get_mut(packet.client) else {
+            continue;
+        };
+
+        flags.set_using_item(false);
+        commands.entity(packet.client).remove::<Consuming>();
+    }
+}
 
-// impl Plugin for ConsumablePlugin {
-//     fn build(&self, app: &mut App) {
-//         app.add_systems(Update, start_consumption);
-//     }
-// }
+fn tick_consumption(
+    mut commands: Commands,
+    mut players: Query<(
+        Entity,
+        &mut Consuming,
+        &mut Inventory,
+        &HeldItem,
+        &mut LivingFlags,
+    )>,
+    mut event_writer: EventWriter<ConsumptionEvent>,
+    consumables: Res<Consumables>,
+    time: Res<Time>,
+) {
+    for (player, mut consuming, mut inventory, held_item, mut flags) in &mut players {
+        let stack = inventory.slot(consuming.slot).clone();
 
-// fn start_consumption(
+        // The player switched to another slot, or the item is gone
+        let Some(consumable) = consumables
+            .get(stack.item)
+            .filter(|_| held_item.slot() == consuming.slot)
+        else {
+            flags.set_using_item(false);
+            commands.entity(player).remove::<Consuming>();
+            continue;
+        };
 
-// )
+        if !consuming.timer.tick(time.delta()).finished() {
+            continue;
+        }
+
+        flags.set_using_item(false);
+        commands.entity(player).remove::<Consuming>();
+
+        consume_slot(
+            &mut inventory,
+            consuming.slot,
+            &consumable.after_consumption,
+        );
+
+        event_writer.send(ConsumptionEvent {
+            consumed: stack.with_count(1),
+            consumer: player,
+        });
+    }
+}
+
+/// Remove one item from the slot and give the player the item that is left after consumption,
+/// it replaces the consumed item if that was the last one
+fn consume_slot(inventory: &mut Inventory, slot: u16, after_consumption: &ItemStack) {
+    let count = inventory.slot(slot).count;
+    if count > 1 {
+        inventory.set_slot_amount(slot, count - 1);
+    } else {
+        inventory.set_slot(slot, ItemStack::EMPTY);
+    }
+
+    if after_consumption.is_empty() {
+        return;
+    }
+
+    if inventory.slot(slot).is_empty() {
+        inventory.set_slot(slot, after_consumption.clone());
+    } else {
+        inventory.try_pickup_all(after_consumption);
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    const SLOT: u16 = 36;
+
+    #[test]
+    fn test_get_consumable() {
+        let consumables = Consumables::default();
+
+        assert_eq!(
+            consumables
+                .get(ItemKind::GoldenApple)
+                .map(|consumable| consumable.consumption_time_millis),
+            Some(1600)
+        );
+        assert!(consumables.get(ItemKind::Apple).is_none());
+    }
+
+    #[test]
+    fn test_consume_slot() {
+        let mut inventory = Inventory::new(InventoryKind::Player);
+        inventory.set_slot(SLOT, ItemStack::new(ItemKind::GoldenApple, 2, None));
+
+        consume_slot(&mut inventory, SLOT, &ItemStack::EMPTY);
+        assert_eq!(
+            *inventory.slot(SLOT),
+            ItemStack::new(ItemKind::GoldenApple, 1, None)
+        );
+
+        consume_slot(&mut inventory, SLOT, &ItemStack::EMPTY);
+        assert!(inventory.slot(SLOT).is_empty());
+    }
+
+    #[test]
+    fn test_consume_slot_after_consumption() {
+        let bottle = ItemStack::new(ItemKind::GlassBottle, 1, None);
+        let mut inventory = Inventory::new(InventoryKind::Player);
+        inventory.set_slot(SLOT, ItemStack::new(ItemKind::Potion, 2, None));
+
+        // The bottle can not replace the remaining potion
+        consume_slot(&mut inventory, SLOT, &bottle);
+        assert_eq!(inventory.slot(SLOT).item, ItemKind::Potion);
+        // The bottle goes into the next free hotbar slot
+        assert_eq!(*inventory.slot(SLOT + 1), bottle);
+
+        // The last potion is replaced by the bottle
+        consume_slot(&mut inventory, SLOT, &bottle);
+        assert_eq!(*inventory.slot(SLOT), bottle);
+    }
+}
@@ -0,0 +1,712 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use bevy_ecs::system::SystemState;
+use bevy_time::{Time, Timer, TimerMode};
+use valence::{
+    interact_block::InteractBlockEvent,
+    interact_item::InteractItemEvent,
+    inventory::HeldItem,
+    nbt::{Compound, List, Value},
+    prelude::*,
+    protocol::{packets::play::CooldownUpdateS2c, VarInt, WritePacket},
+};
+
+use crate::{
+    base::{
+        death::IsDead,
+        physics::{EntityBlockCollisionEvent, EntityEntityCollisionEvent},
+    },
+    bedwars_config::{ShopConfig, ShopOffer},
+    maps::MapShop,
+    Spectator,
+};
+
+/// Nbt key of the id of a custom item
+pub const CUSTOM_ITEM_TAG: &str = "BedwarsItem";
+
+/// A right click with a custom item
+#[derive(Debug, Clone, Copy)]
+pub struct ItemUse {
+    pub user: Entity,
+    /// Inventory slot of the used item
+    pub slot: u16,
+    /// The clicked block and face, if the item was used on a block
+    pub block: Option<(BlockPos, Direction)>,
+}
+
+/// The behavior of a custom item.
+/// Hooks get the whole world, most hooks run a system with `World::run_system_once_with`.
+pub trait CustomItemHooks: Send + Sync + 'static {
+    /// Returns whether the item was used, only then the item is consumed and the cooldown starts
+    fn on_use(&self, world: &mut World, item_use: ItemUse) -> bool;
+
+    /// A [`CustomProjectile`] of the item hit a block
+    fn on_hit_block(
+        &self,
+        _world: &mut World,
+        _projectile: Entity,
+        _hit: &EntityBlockCollisionEvent,
+    ) {
+    }
+
+    /// A [`CustomProjectile`] of the item hit an entity, other than its owner
+    fn on_hit_entity(&self, _world: &mut World, _projectile: Entity, _hit: Entity) {}
+}
+
+/// An item with special behavior, recognized by the id in its nbt
+#[derive(Clone)]
+pub struct CustomItem {
+    pub id: String,
+    /// Only stacks of this item kind are recognized as the custom item
+    pub base: ItemKind,
+    pub name: String,
+    pub lore: Vec<String>,
+    /// Time between two uses, this is also shown on the client.
+    /// The client shows the cooldown on every stack of the base item kind,
+    /// but only this custom item is blocked.
+    pub cooldown: Duration,
+    /// Whether one item is removed every time it is used
+    pub consumed_on_use: bool,
+    pub hooks: Arc<dyn CustomItemHooks>,
+}
+
+impl CustomItem {
+    /// A stack of the item, with the id, name and lore in its nbt
+    pub fn stack(&self, count: i8) -> ItemStack {
+        let mut stack = ItemStack::new(self.base, count, None);
+        self.apply(&mut stack);
+        stack
+    }
+
+    /// Turn a stack into this item, an existing display name or lore is kept
+    pub fn apply(&self, stack: &mut ItemStack) {
+        stack.item = self.base;
+
+        let nbt = stack.nbt.get_or_insert_with(Compound::new);
+        nbt.insert(CUSTOM_ITEM_TAG, self.id.clone());
+
+        if !matches!(nbt.get("display"), Some(Value::Compound(_))) {
+            nbt.insert("display", Compound::new());
+        }
+
+        let Some(Value::Compound(display)) = nbt.get_mut("display") else {
+            return;
+        };
+
+        if !display.contains_key("Name") {
+            display.insert(
+                "Name",
+                format!("{{\"text\":\"{}\",\"italic\":false}}", self.name),
+            );
+        }
+
+        if !display.contains_key("Lore") && !self.lore.is_empty() {
+            display.insert(
+                "Lore",
+                List::String(
+                    self.lore
+                        .iter()
+                        .map(|line| format!("{{\"text\":\"§7{}\",\"italic\":false}}", line))
+                        .collect(),
+                ),
+            );
+        }
+    }
+}
+
+/// Every registered custom item by id, register items with [`CustomItemAppExt`]
+#[derive(Default, Resource)]
+pub struct CustomItems(HashMap<String, CustomItem>);
+
+impl CustomItems {
+    pub fn register(&mut self, item: CustomItem) {
+        if self.0.contains_key(&item.id) {
+            tracing::warn!("Custom item `{}` is registered twice", item.id);
+        }
+
+        self.0.insert(item.id.clone(), item);
+    }
+
+    pub fn get(&self, id: &str) -> Option<&CustomItem> {
+        self.0.get(id)
+    }
+
+    pub fn get_mut(&mut self, id: &str) -> Option<&mut CustomItem> {
+        self.0.get_mut(id)
+    }
+
+    /// The custom item of a stack
+    pub fn of_stack(&self, stack: &ItemStack) -> Option<&CustomItem> {
+        custom_item_id(stack)
+            .and_then(|id| self.get(id))
+            .filter(|item| item.base == stack.item)
+    }
+
+    /// The item offered by a shop offer, offers can reference a custom item with `custom_item`.
+    /// Unknown custom items are reported when the shop is loaded, the plain item is offered instead.
+    pub fn shop_stack(&self, offer: &ShopOffer) -> ItemStack {
+        let mut stack: ItemStack = offer.offer.clone().into();
+
+        if let Some(item) = offer.custom_item.as_ref().and_then(|id| self.get(id)) {
+            item.apply(&mut stack);
+        }
+
+        stack
+    }
+
+    /// Offers with a `custom_item` that is not registered.
+    /// Returns the json path of every offer with the unknown id.
+    pub fn unknown_offers<'a>(&self, shop: &'a ShopConfig) -> Vec<(String, &'a str)> {
+        let mut unknown = vec![];
+
+        for (category, (_, offers)) in &shop.shop_items {
+            for (idx, offer) in offers.iter().enumerate() {
+                if let Some(id) = &offer.custom_item {
+                    if self.get(id).is_none() {
+                        unknown.push((format!("shop_items.{category}[1][{idx}]"), id.as_str()));
+                    }
+                }
+            }
+        }
+
+        unknown
+    }
+
+    /// Offers that sell the base item of a custom item, but not the custom item itself.
+    /// Returns the json path of every offer with the id of the custom item.
+    pub fn untagged_offers<'a>(&'a self, shop: &ShopConfig) -> Vec<(String, &'a str)> {
+        let mut untagged = vec![];
+
+        for (category, (_, offers)) in &shop.shop_items {
+            for (idx, offer) in offers.iter().enumerate() {
+                let stack = self.shop_stack(offer);
+                if self.of_stack(&stack).is_some() {
+                    continue;
+                }
+
+                if let Some(item) = self.0.values().find(|item| item.base == stack.item) {
+                    untagged.push((format!("shop_items.{category}[1][{idx}]"), item.id.as_str()));
+                }
+            }
+        }
+
+        untagged
+    }
+}
+
+/// The custom item id in the nbt of a stack
+pub fn custom_item_id(stack: &ItemStack) -> Option<&str> {
+    match stack.nbt.as_ref()?.get(CUSTOM_ITEM_TAG)? {
+        Value::String(id) => Some(id),
+        _ => None,
+    }
+}
+
+pub trait CustomItemAppExt {
+    fn register_custom_item(&mut self, item: CustomItem) -> &mut Self;
+}
+
+impl CustomItemAppExt for App {
+    fn register_custom_item(&mut self, item: CustomItem) -> &mut Self {
+        self.init_resource::<CustomItems>();
+        self.world_mut()
+            .resource_mut::<CustomItems>()
+            .register(item);
+        self
+    }
+}
+
+/// A projectile launched by a custom item, hits are passed to the hooks of the item
+#[derive(Debug, Component)]
+pub struct CustomProjectile {
+    /// Id of the custom item
+    pub item: String,
+    pub owner: Entity,
+}
+
+/// The time until a player can use a custom item again, by item id
+#[derive(Debug, Default, Component)]
+pub struct ItemCooldowns(HashMap<String, Timer>);
+
+impl ItemCooldowns {
+    pub fn is_ready(&self, id: &str) -> bool {
+        !self.0.contains_key(id)
+    }
+
+    pub fn start(&mut self, id: &str, cooldown: Duration) {
+        self.0
+            .insert(id.to_string(), Timer::new(cooldown, TimerMode::Once));
+    }
+
+    /// Advance every cooldown, finished cooldowns are removed
+    pub fn tick(&mut self, delta: Duration) {
+        self.0.retain(|_, timer| !timer.tick(delta).finished());
+    }
+}
+
+pub struct CustomItemPlugin;
+
+impl Plugin for CustomItemPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CustomItems>().add_systems(
+            Update,
+            (
+                (tick_item_cooldowns, use_custom_items).chain(),
+                custom_projectile_hits,
+                check_shop_offers,
+            ),
+        );
+    }
+}
+
+/// Report offers with an unknown `custom_item` and offers without `custom_item` that sell a plain item,
+/// which is likely a mistake in the shop config.
+/// Runs when the global shop or a map shop is loaded or reloaded.
+fn check_shop_offers(
+    shop_config: Option<Res<ShopConfig>>,
+    map_shops: Query<&MapShop, Changed<MapShop>>,
+    custom_items: Res<CustomItems>,
+) {
+    let shop_config = shop_config.filter(|shop_config| shop_config.is_changed());
+    let shops = shop_config
+        .as_deref()
+        .into_iter()
+        .chain(map_shops.iter().map(|map_shop| &map_shop.0));
+
+    for shop in shops {
+        for (path, id) in custom_items.unknown_offers(shop) {
+            tracing::warn!(
+                "{}: the unknown custom item `{}` is offered as a plain item",
+                path,
+                id
+            );
+        }
+
+        for (path, id) in custom_items.untagged_offers(shop) {
+            tracing::warn!(
+                "{}: the offer has no `custom_item`, it sells a plain item instead of `{}`",
+                path,
+                id
+            );
+        }
+    }
+}
+
+fn tick_item_cooldowns(mut cooldowns: Query<&mut ItemCooldowns>, time: Res<Time>) {
+    for mut cooldowns in &mut cooldowns {
+        cooldowns.tick(time.delta());
+    }
+}
+
+type UseEvents<'w, 's> = (
+    EventReader<'w, 's, InteractBlockEvent>,
+    EventReader<'w, 's, InteractItemEvent>,
+);
+
+fn use_custom_items(world: &mut World, events: &mut SystemState<UseEvents<'static, 'static>>) {
+    let (mut block_events, mut item_events) = events.get_mut(world);
+
+    let block_uses = block_events
+        .read()
+        .filter(|event| event.hand == Hand::Main)
+        .map(|event| (event.client, Some((event.position, event.face))));
+
+    let item_uses = item_events
+        .read()
+        .filter(|event| event.hand == Hand::Main)
+        .map(|event| (event.client, None));
+
+    let uses = unique_uses(block_uses.chain(item_uses));
+
+    for (user, block) in uses {
+        let Some(slot) = world.get::<HeldItem>(user).map(HeldItem::slot) else {
+            continue;
+        };
+
+        use_custom_item(world, ItemUse { user, slot, block });
+    }
+}
+
+/// The first use of every player.
+/// Right clicking a block can send both a block and an item event, but the item is only used once.
+fn unique_uses(
+    uses: impl IntoIterator<Item = (Entity, Option<(BlockPos, Direction)>)>,
+) -> Vec<(Entity, Option<(BlockPos, Direction)>)> {
+    let mut unique: Vec<(Entity, Option<(BlockPos, Direction)>)> = vec![];
+    for (user, block) in uses {
+        if !unique.iter().any(|(other, _)| *other == user) {
+            unique.push((user, block));
+        }
+    }
+
+    unique
+}
+
+/// Use the custom item in the slot, if there is one
+pub(crate) fn use_custom_item(world: &mut World, item_use: ItemUse) {
+    let ItemUse { user, slot, .. } = item_use;
+
+    if world.get::<IsDead>(user).is_some() || world.get::<Spectator>(user).is_some() {
+        return;
+    }
+
+    let Some(inventory) = world.get::<Inventory>(user) else {
+        return;
+    };
+
+    let Some(item) = world
+        .resource::<CustomItems>()
+        .of_stack(inventory.slot(slot))
+        .cloned()
+    else {
+        return;
+    };
+
+    if world
+        .get::<ItemCooldowns>(user)
+        .is_some_and(|cooldowns| !cooldowns.is_ready(&item.id))
+    {
+        return;
+    }
+
+    if !item.hooks.on_use(world, item_use) {
+        return;
+    }
+
+    if item.consumed_on_use {
+        if let Some(mut inventory) = world.get_mut::<Inventory>(user) {
+            let count = inventory.slot(slot).count;
+            if count > 1 {
+                inventory.set_slot_amount(slot, count - 1);
+            } else {
+                inventory.set_slot(slot, ItemStack::EMPTY);
+            }
+        }
+    }
+
+    if item.cooldown.is_zero() {
+        return;
+    }
+
+    match world.get_mut::<ItemCooldowns>(user) {
+        Some(mut cooldowns) => cooldowns.start(&item.id, item.cooldown),
+        None => {
+            let mut cooldowns = ItemCooldowns::default();
+            cooldowns.start(&item.id, item.cooldown);
+            world.entity_mut(user).insert(cooldowns);
+        }
+    }
+
+    // The client has no cooldowns per nbt, so this greys out every item of the base kind
+    if let Some(mut client) = world.get_mut::<Client>(user) {
+        client.write_packet(&CooldownUpdateS2c {
+            item_id: VarInt(item.base.to_raw() as i32),
+            cooldown_ticks: VarInt((item.cooldown.as_millis() / 50) as i32),
+        });
+    }
+}
+
+enum ProjectileHit {
+    Block(EntityBlockCollisionEvent),
+    Entity(Entity),
+}
+
+type HitParams<'w, 's> = (
+    Query<'w, 's, &'static CustomProjectile>,
+    EventReader<'w, 's, EntityBlockCollisionEvent>,
+    EventReader<'w, 's, EntityEntityCollisionEvent>,
+);
+
+/// Pass the hits of custom projectiles to the hooks of their item
+pub(crate) fn custom_projectile_hits(
+    world: &mut World,
+    params: &mut SystemState<HitParams<'static, 'static>>,
+) {
+    let (projectiles, mut block_events, mut entity_events) = params.get_mut(world);
+
+    let block_hits = block_events
+        .read()
+        .filter_map(|event| {
+            let projectile = projectiles.get(event.entity).ok()?;
+            Some((
+                event.entity,
+                projectile,
+                ProjectileHit::Block(event.clone()),
+            ))
+        })
+        .collect::<Vec<_>>();
+
+    let entity_hits = entity_events.read().filter_map(|event| {
+        let projectile = projectiles.get(event.entity1).ok()?;
+        // Projectiles start inside of the player that launched them
+        (projectile.owner != event.entity2).then_some((
+            event.entity1,
+            projectile,
+            ProjectileHit::Entity(event.entity2),
+        ))
+    });
+
+    // A projectile can hit multiple things in the same tick, only the first hit counts
+    let mut hits: Vec<(Entity, String, ProjectileHit)> = vec![];
+    for (entity, projectile, hit) in block_hits.into_iter().chain(entity_hits) {
+        if !hits.iter().any(|(other, ..)| *other == entity) {
+            hits.push((entity, projectile.item.clone(), hit));
+        }
+    }
+
+    for (projectile, id, hit) in hits {
+        let Some(hooks) = world
+            .resource::<CustomItems>()
+            .get(&id)
+            .map(|item| item.hooks.clone())
+        else {
+            continue;
+        };
+
+        match hit {
+            ProjectileHit::Block(event) => hooks.on_hit_block(world, projectile, &event),
+            ProjectileHit::Entity(entity) => hooks.on_hit_entity(world, projectile, entity),
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use bevy_ecs::system::RunSystemOnce;
+    use serde_json::json;
+
+    use super::*;
+
+    const SLOT: u16 = 36;
+
+    /// Counts how often the item was used, `on_use` returns `succeed`
+    #[derive(Default)]
+    struct StubHooks {
+        succeed: bool,
+        uses: AtomicUsize,
+    }
+
+    impl CustomItemHooks for StubHooks {
+        fn on_use(&self, _world: &mut World, _item_use: ItemUse) -> bool {
+            self.uses.fetch_add(1, Ordering::Relaxed);
+            self.succeed
+        }
+    }
+
+    fn test_item(hooks: Arc<StubHooks>, cooldown: Duration) -> CustomItem {
+        CustomItem {
+            id: "test".to_string(),
+            base: ItemKind::Stick,
+            name: "Test".to_string(),
+            lore: vec![],
+            cooldown,
+            consumed_on_use: true,
+            hooks,
+        }
+    }
+
+    /// A world with the item registered and a player holding two of it
+    fn test_world(item: CustomItem) -> (World, Entity) {
+        let mut world = World::new();
+
+        let mut inventory = Inventory::new(InventoryKind::Player);
+        inventory.set_slot(SLOT, item.stack(2));
+
+        let mut items = CustomItems::default();
+        items.register(item);
+        world.insert_resource(items);
+
+        let user = world.spawn(inventory).id();
+        (world, user)
+    }
+
+    fn held_count(world: &World, user: Entity) -> i8 {
+        world.get::<Inventory>(user).unwrap().slot(SLOT).count
+    }
+
+    fn use_item(world: &mut World, user: Entity) {
+        use_custom_item(
+            world,
+            ItemUse {
+                user,
+                slot: SLOT,
+                block: None,
+            },
+        );
+    }
+
+    #[test]
+    fn test_custom_item_stack() {
+        let mut items = CustomItems::default();
+        items.register(test_item(Arc::default(), Duration::ZERO));
+
+        let stack = items.get("test").unwrap().stack(2);
+        assert_eq!(custom_item_id(&stack), Some("test"));
+        assert!(items.of_stack(&stack).is_some());
+
+        // The base item kind has to match
+        let mut dirt = stack.clone();
+        dirt.item = ItemKind::Dirt;
+        assert!(items.of_stack(&dirt).is_none());
+        assert!(items
+            .of_stack(&ItemStack::new(ItemKind::Stick, 1, None))
+            .is_none());
+    }
+
+    #[test]
+    fn test_shop_stack_unknown_item() {
+        let items = CustomItems::default();
+        let offer: ShopOffer = serde_json::from_value(json!({
+            "custom_item": "unknown",
+            "offer": { "item": "stick", "count": 1, "nbt": null },
+            "price": { "item": "iron_ingot", "count": 1, "nbt": null }
+        }))
+        .unwrap();
+
+        // The plain item is offered
+        assert_eq!(
+            items.shop_stack(&offer),
+            ItemStack::new(ItemKind::Stick, 1, None)
+        );
+    }
+
+    #[test]
+    fn test_use_consumes_item() {
+        let hooks = Arc::new(StubHooks {
+            succeed: true,
+            ..Default::default()
+        });
+        let (mut world, user) = test_world(test_item(hooks.clone(), Duration::ZERO));
+
+        use_item(&mut world, user);
+        assert_eq!(hooks.uses.load(Ordering::Relaxed), 1);
+        assert_eq!(held_count(&world, user), 1);
+
+        use_item(&mut world, user);
+        assert!(world.get::<Inventory>(user).unwrap().slot(SLOT).is_empty());
+
+        // Nothing left to use
+        use_item(&mut world, user);
+        assert_eq!(hooks.uses.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn test_failed_use_keeps_item() {
+        let hooks = Arc::new(StubHooks::default());
+        let (mut world, user) = test_world(test_item(hooks.clone(), Duration::from_secs(1)));
+
+        use_item(&mut world, user);
+        assert_eq!(hooks.uses.load(Ordering::Relaxed), 1);
+        assert_eq!(held_count(&world, user), 2);
+        // The cooldown only starts if the item was used
+        assert!(world.get::<ItemCooldowns>(user).is_none());
+    }
+
+    #[test]
+    fn test_use_starts_cooldown() {
+        let hooks = Arc::new(StubHooks {
+            succeed: true,
+            ..Default::default()
+        });
+        let (mut world, user) = test_world(test_item(hooks.clone(), Duration::from_secs(1)));
+
+        use_item(&mut world, user);
+        use_item(&mut world, user);
+        assert_eq!(hooks.uses.load(Ordering::Relaxed), 1);
+        assert_eq!(held_count(&world, user), 1);
+        assert!(!world.get::<ItemCooldowns>(user).unwrap().is_ready("test"));
+    }
+
+    #[test]
+    fn test_unique_uses() {
+        let mut world = World::new();
+        let (a, b) = (world.spawn_empty().id(), world.spawn_empty().id());
+        let block = Some((BlockPos::new(0, 0, 0), Direction::Up));
+
+        let uses = unique_uses([(a, block), (b, None), (a, None)]);
+        assert_eq!(uses, vec![(a, block), (b, None)]);
+    }
+
+    #[test]
+    fn test_untagged_offers() {
+        let mut items = CustomItems::default();
+        items.register(test_item(Arc::default(), Duration::ZERO));
+
+        let offer = |custom_item: Option<&str>, item: &str| {
+            json!({
+                "custom_item": custom_item,
+                "offer": { "item": item, "count": 1, "nbt": null },
+                "price": { "item": "iron_ingot", "count": 1, "nbt": null }
+            })
+        };
+
+        let shop: ShopConfig = serde_json::from_value(json!({
+            "shop_items": {
+                "Items": [
+                    { "item": "stick", "count": 1, "nbt": null },
+                    [offer(None, "stick"), offer(Some("test"), "stick"), offer(None, "dirt")]
+                ]
+            }
+        }))
+        .unwrap();
+
+        assert_eq!(
+            items.untagged_offers(&shop),
+            vec![("shop_items.Items[1][0]".to_string(), "test")]
+        );
+    }
+
+    #[test]
+    fn test_unknown_offers() {
+        let mut items = CustomItems::default();
+        items.register(test_item(Arc::default(), Duration::ZERO));
+
+        let offer = |custom_item: Option<&str>| {
+            json!({
+                "custom_item": custom_item,
+                "offer": { "item": "stick", "count": 1, "nbt": null },
+                "price": { "item": "iron_ingot", "count": 1, "nbt": null }
+            })
+        };
+
+        let shop: ShopConfig = serde_json::from_value(json!({
+            "shop_items": {
+                "Items": [
+                    { "item": "stick", "count": 1, "nbt": null },
+                    [offer(Some("test")), offer(Some("unknown")), offer(None)]
+                ]
+            }
+        }))
+        .unwrap();
+
+        assert_eq!(
+            items.unknown_offers(&shop),
+            vec![("shop_items.Items[1][1]".to_string(), "unknown")]
+        );
+    }
+
+    #[test]
+    fn test_item_cooldowns() {
+        let mut world = World::new();
+        world.insert_resource(Time::<()>::default());
+
+        let mut cooldowns = ItemCooldowns::default();
+        assert!(cooldowns.is_ready("test"));
+        cooldowns.start("test", Duration::from_secs(1));
+        let user = world.spawn(cooldowns).id();
+
+        let advance = |world: &mut World, millis| {
+            world
+                .resource_mut::<Time>()
+                .advance_by(Duration::from_millis(millis));
+            world.run_system_once(tick_item_cooldowns);
+            world.get::<ItemCooldowns>(user).unwrap().is_ready("test")
+        };
+
+        assert!(!advance(&mut world, 500));
+        assert!(world.get::<ItemCooldowns>(user).unwrap().is_ready("other"));
+        assert!(advance(&mut world, 500));
+    }
+}
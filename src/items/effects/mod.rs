@@ -1 +1,268 @@
+use std::{collections::HashMap, time::Duration};
+
+use bevy_ecs::{
+    query::Added,
+    system::{Query, Res},
+    world::OnRemove,
+};
+use bevy_time::{Time, Timer, TimerMode};
+use valence::{
+    app::{App, Plugin, Update},
+    client::Client,
+    entity::{entity::Flags, player::AdditionalHearts},
+    prelude::{Component, Trigger},
+    protocol::{
+        packets::play::{
+            entity_status_effect_s2c, EntityStatusEffectS2c, RemoveEntityStatusEffectS2c,
+        },
+        VarInt, WritePacket,
+    },
+};
+
+use crate::base::death::IsDead;
+
 pub mod potion;
+
+/// Absorption hearts per level of the absorption effect
+const ABSORPTION_PER_LEVEL: f32 = 4.0;
+
+/// Status effects, the value is the protocol id of the effect
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StatusEffect {
+    Speed = 1,
+    Slowness = 2,
+    Haste = 3,
+    MiningFatigue = 4,
+    JumpBoost = 8,
+    Regeneration = 10,
+    Invisibility = 14,
+    Blindness = 15,
+    Absorption = 22,
+}
+
+impl StatusEffect {
+    pub fn from_id(id: i64) -> Option<Self> {
+        Some(match id {
+            1 => StatusEffect::Speed,
+            2 => StatusEffect::Slowness,
+            3 => StatusEffect::Haste,
+            4 => StatusEffect::MiningFatigue,
+            8 => StatusEffect::JumpBoost,
+            10 => StatusEffect::Regeneration,
+            14 => StatusEffect::Invisibility,
+            15 => StatusEffect::Blindness,
+            22 => StatusEffect::Absorption,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ActiveEffect {
+    /// Level of the effect - 1
+    pub amplifier: u8,
+    pub timer: Timer,
+}
+
+/// Timed status effects of a player.
+/// Movement and vision effects are applied by the client itself,
+/// the others are handled by the systems they affect.
+#[derive(Debug, Clone, Default, Component)]
+pub struct ActiveEffects {
+    effects: HashMap<StatusEffect, ActiveEffect>,
+    /// Effects that were added or removed but not sent to the client yet
+    unsynced: Vec<StatusEffect>,
+}
+
+impl ActiveEffects {
+    /// Add an effect, an active effect of the same kind is only replaced
+    /// by a higher level, or by the same level with a longer duration
+    pub fn add(&mut self, effect: StatusEffect, amplifier: u8, duration: Duration) {
+        if let Some(active) = self.effects.get(&effect) {
+            if active.amplifier > amplifier
+                || (active.amplifier == amplifier && active.timer.remaining() >= duration)
+            {
+                return;
+            }
+        }
+
+        self.effects.insert(
+            effect,
+            ActiveEffect {
+                amplifier,
+                timer: Timer::new(duration, TimerMode::Once),
+            },
+        );
+        self.unsynced.push(effect);
+    }
+
+    pub fn remove(&mut self, effect: StatusEffect) {
+        if self.effects.remove(&effect).is_some() {
+            self.unsynced.push(effect);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.unsynced
+            .extend(self.effects.drain().map(|(effect, _)| effect));
+    }
+
+    /// The amplifier of the effect, if it is active
+    pub fn amplifier(&self, effect: StatusEffect) -> Option<u8> {
+        self.effects.get(&effect).map(|active| active.amplifier)
+    }
+
+    pub fn has(&self, effect: StatusEffect) -> bool {
+        self.effects.contains_key(&effect)
+    }
+
+    /// Tick the effects and remove the expired ones
+    fn tick(&mut self, delta: Duration) {
+        let expired = self
+            .effects
+            .iter_mut()
+            .filter_map(|(effect, active)| active.timer.tick(delta).finished().then_some(*effect))
+            .collect::<Vec<_>>();
+
+        for effect in expired {
+            self.remove(effect);
+        }
+    }
+}
+
+pub struct EffectsPlugin;
+
+impl Plugin for EffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (clear_effects_on_death, tick_effects))
+            .observe(on_remove_effects);
+    }
+}
+
+fn clear_effects_on_death(mut players: Query<&mut ActiveEffects, Added<IsDead>>) {
+    for mut effects in &mut players {
+        effects.clear();
+    }
+}
+
+fn tick_effects(
+    mut players: Query<(
+        &mut Client,
+        &mut ActiveEffects,
+        &mut Flags,
+        &mut AdditionalHearts,
+    )>,
+    time: Res<Time>,
+) {
+    for (mut client, mut effects, mut flags, mut hearts) in &mut players {
+        effects.tick(time.delta());
+
+        if effects.unsynced.is_empty() {
+            continue;
+        }
+
+        let effects = &mut *effects;
+        for effect in effects.unsynced.drain(..) {
+            match effects.effects.get(&effect) {
+                Some(active) => {
+                    send_status_effect(
+                        &mut client,
+                        effect,
+                        active.amplifier,
+                        active.timer.remaining(),
+                    );
+
+                    if effect == StatusEffect::Absorption {
+                        hearts.0 = ABSORPTION_PER_LEVEL * (active.amplifier as f32 + 1.0);
+                    }
+                }
+                None => {
+                    remove_status_effect(&mut client, effect);
+
+                    if effect == StatusEffect::Absorption {
+                        hearts.0 = 0.0;
+                    }
+                }
+            }
+        }
+
+        let invisible = effects.has(StatusEffect::Invisibility);
+        if flags.invisible() != invisible {
+            flags.set_invisible(invisible);
+        }
+    }
+}
+
+fn on_remove_effects(
+    trigger: Trigger<OnRemove, ActiveEffects>,
+    mut players: Query<(
+        &mut Client,
+        &ActiveEffects,
+        &mut Flags,
+        &mut AdditionalHearts,
+    )>,
+) {
+    let Ok((mut client, effects, mut flags, mut hearts)) = players.get_mut(trigger.entity()) else {
+        return;
+    };
+
+    for effect in effects.effects.keys() {
+        remove_status_effect(&mut client, *effect);
+    }
+
+    flags.set_invisible(false);
+    hearts.0 = 0.0;
+}
+
+fn send_status_effect(
+    client: &mut Client,
+    effect: StatusEffect,
+    amplifier: u8,
+    duration: Duration,
+) {
+    client.write_packet(&EntityStatusEffectS2c {
+        // The own entity of a client always has the id 0
+        entity_id: VarInt(0),
+        effect_id: VarInt(effect as i32),
+        amplifier,
+        duration: VarInt((duration.as_millis() / 50) as i32),
+        flags: entity_status_effect_s2c::Flags::new()
+            .with_show_particles(true)
+            .with_show_icon(true),
+        factor_codec: None,
+    });
+}
+
+fn remove_status_effect(client: &mut Client, effect: StatusEffect) {
+    client.write_packet(&RemoveEntityStatusEffectS2c {
+        entity_id: VarInt(0),
+        effect_id: VarInt(effect as i32),
+    });
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn test_active_effects() {
+        let mut effects = ActiveEffects::default();
+        effects.add(StatusEffect::Speed, 0, Duration::from_secs(10));
+
+        // A weaker effect does not replace a stronger one
+        effects.add(StatusEffect::Speed, 0, Duration::from_secs(5));
+        effects.add(StatusEffect::Speed, 1, Duration::from_secs(2));
+        assert_eq!(effects.amplifier(StatusEffect::Speed), Some(1));
+
+        effects.tick(Duration::from_secs(2));
+        assert!(!effects.has(StatusEffect::Speed));
+        assert_eq!(
+            effects.unsynced,
+            vec![
+                StatusEffect::Speed,
+                StatusEffect::Speed,
+                StatusEffect::Speed
+            ]
+        );
+    }
+}
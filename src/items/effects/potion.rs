@@ -1,14 +1,116 @@
-// use bevy_time::Timer;
-// use valence::prelude::*;
+use std::time::Duration;
 
-// pub struct EffectState {
-//     active_effects: Vec<Effect>,
-// }
+use valence::{
+    nbt::{List, Value},
+    prelude::*,
+    protocol::{sound::SoundCategory, Sound},
+};
 
-// pub struct Effect {
-//     pub id: String,
-//     pub timer: Timer,
-//     pub level: u32,
-// }
+use crate::items::consumable::ConsumptionEvent;
 
-// pub struct PotionPlugin;
+use super::{ActiveEffects, StatusEffect};
+
+pub struct PotionPlugin;
+
+impl Plugin for PotionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, drink_potion);
+    }
+}
+
+/// The effects of a potion, read from the vanilla `CustomPotionEffects` nbt:
+/// `{"CustomPotionEffects": [{"Id": 1, "Amplifier": 0, "Duration": 600}]}`,
+/// the duration is in ticks
+pub fn potion_effects(stack: &ItemStack) -> Vec<(StatusEffect, u8, Duration)> {
+    let Some(Value::List(List::Compound(effects))) = stack
+        .nbt
+        .as_ref()
+        .and_then(|nbt| nbt.get("CustomPotionEffects"))
+    else {
+        return vec![];
+    };
+
+    effects
+        .iter()
+        .filter_map(|effect| {
+            let effect_type = StatusEffect::from_id(nbt_int(effect.get("Id")?)?)?;
+            let amplifier = effect.get("Amplifier").and_then(nbt_int).unwrap_or(0);
+            let duration_ticks = effect.get("Duration").and_then(nbt_int).unwrap_or(0);
+
+            Some((
+                effect_type,
+                amplifier.clamp(0, u8::MAX as i64) as u8,
+                Duration::from_millis(duration_ticks.max(0) as u64 * 50),
+            ))
+        })
+        .collect()
+}
+
+/// Integers can be stored as any nbt number type, depending on where the nbt comes from
+fn nbt_int(value: &Value) -> Option<i64> {
+    match value {
+        Value::Byte(v) => Some(*v as i64),
+        Value::Short(v) => Some(*v as i64),
+        Value::Int(v) => Some(*v as i64),
+        Value::Long(v) => Some(*v),
+        _ => None,
+    }
+}
+
+fn drink_potion(
+    mut players: Query<(&Position, &mut ActiveEffects, &mut Client)>,
+    mut events: EventReader<ConsumptionEvent>,
+) {
+    for event in events.read() {
+        if event.consumed.item != ItemKind::Potion {
+            continue;
+        }
+
+        let Ok((position, mut effects, mut client)) = players.get_mut(event.consumer) else {
+            continue;
+        };
+
+        for (effect, amplifier, duration) in potion_effects(&event.consumed) {
+            effects.add(effect, amplifier, duration);
+        }
+
+        client.play_sound(
+            Sound::EntityGenericDrink,
+            SoundCategory::Player,
+            position.0,
+            1.0,
+            1.0,
+        );
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use valence::nbt::compound;
+
+    #[test]
+    fn test_potion_effects() {
+        let stack = ItemStack::new(
+            ItemKind::Potion,
+            1,
+            Some(compound! {
+                "CustomPotionEffects" => List::Compound(vec![
+                    compound! { "Id" => 1_i64, "Amplifier" => 1_i64, "Duration" => 600_i64 },
+                    compound! { "Id" => 8_i8, "Duration" => 20_i32 },
+                    // unsupported effect
+                    compound! { "Id" => 5_i64, "Duration" => 20_i64 },
+                ]),
+            }),
+        );
+
+        assert_eq!(
+            potion_effects(&stack),
+            vec![
+                (StatusEffect::Speed, 1, Duration::from_secs(30)),
+                (StatusEffect::JumpBoost, 0, Duration::from_secs(1)),
+            ]
+        );
+        assert!(potion_effects(&ItemStack::new(ItemKind::Potion, 1, None)).is_empty());
+    }
+}
@@ -1,9 +1,9 @@
-use bevy_time::{Time, Timer, TimerMode};
+use std::{sync::Arc, time::Duration};
+
+use bevy_ecs::system::RunSystemOnce;
 use rand::Rng;
 use valence::{
     entity::{ender_pearl::EnderPearlEntityBundle, entity::NoGravity, Velocity},
-    interact_item::InteractItemEvent,
-    inventory::HeldItem,
     prelude::*,
     protocol::{sound::SoundCategory, Sound},
 };
@@ -12,184 +12,135 @@ use crate::{
     base::{
         armor::EquipmentExtReduction,
         bow::calculate_projectile_velocity,
-        combat::{CombatState, EYE_HEIGHT, SNEAK_EYE_HEIGHT},
+        combat::{look_direction, CombatState},
         death::PlayerHurtEvent,
         fall_damage::FallingState,
         physics::{
             CollidesWithBlocks, EntityBlockCollisionEvent, Gravity, PhysicsMarker, TerminalVelocity,
         },
     },
-    utils::{despawn_timer::DespawnTimer, inventory::InventoryExt},
-    Spectator,
+    items::custom_item::{
+        CustomItem, CustomItemAppExt, CustomItemHooks, CustomProjectile, ItemUse,
+    },
+    utils::despawn_timer::DespawnTimer,
 };
 
+pub const ENDER_PEARL_ID: &str = "ender_pearl";
 const ENDER_PEARL_INACCURACY: f64 = 1.0;
 const ENDER_PEARL_GRAVITY: f32 = 20.0;
 const ENDER_PEARL_TERMINAL_VELOCITY: f32 = 100.0;
 const ENDER_PEARL_BASE_DMG: f32 = 5.0;
-const ENDER_PEARL_COOLDOWN_SECS: f32 = 1.0;
+const ENDER_PEARL_COOLDOWN: Duration = Duration::from_secs(1);
 
 pub struct EnderPearlPlugin;
 
-#[derive(Debug, Component)]
-pub struct EnderPearlOwner(pub Entity);
+impl Plugin for EnderPearlPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_custom_item(CustomItem {
+            id: ENDER_PEARL_ID.to_string(),
+            base: ItemKind::EnderPearl,
+            name: "Ender Pearl".to_string(),
+            lore: vec!["Teleports you to where it lands".to_string()],
+            cooldown: ENDER_PEARL_COOLDOWN,
+            consumed_on_use: true,
+            hooks: Arc::new(EnderPearl),
+        });
+    }
+}
 
-#[derive(Debug, Component)]
-struct EnderPearlTimer(pub Timer);
+struct EnderPearl;
 
-impl Default for EnderPearlTimer {
-    fn default() -> Self {
-        Self(Timer::from_seconds(
-            ENDER_PEARL_COOLDOWN_SECS,
-            TimerMode::Once,
-        ))
+impl CustomItemHooks for EnderPearl {
+    fn on_use(&self, world: &mut World, item_use: ItemUse) -> bool {
+        world.run_system_once_with(item_use, throw_ender_pearl)
     }
-}
 
-impl Plugin for EnderPearlPlugin {
-    fn build(&self, app: &mut App) {
-        app.add_systems(Update, (throw_ender_pearl, on_hit_block, tick_pearl_timer));
+    fn on_hit_block(&self, world: &mut World, pearl: Entity, hit: &EntityBlockCollisionEvent) {
+        world.run_system_once_with((pearl, hit.clone()), on_hit_block);
     }
 }
 
-#[allow(clippy::type_complexity)]
 fn throw_ender_pearl(
+    In(item_use): In<ItemUse>,
     mut commands: Commands,
-    mut clients: Query<
-        (
-            Entity,
-            &EntityLayerId,
-            &mut Inventory,
-            &HeldItem,
-            &Position,
-            &CombatState,
-            &Look,
-            Option<&mut EnderPearlTimer>,
-        ),
-        Without<Spectator>,
-    >,
-    mut events: EventReader<InteractItemEvent>,
-    time: Res<Time>,
+    clients: Query<(&EntityLayerId, &Position, &CombatState, &Look)>,
     mut layer: Query<&mut ChunkLayer>,
-) {
-    for event in events.read() {
-        let Ok((
-            player_ent,
-            layer_id,
-            mut inventory,
-            held_item,
-            position,
-            combat_state,
-            look,
-            pearl_timer,
-        )) = clients.get_mut(event.client)
-        else {
-            continue;
-        };
-
-        if let Some(mut timer) = pearl_timer {
-            if !timer.0.tick(time.delta()).finished() {
-                continue;
-            }
-        }
-
-        let stack = inventory.slot(held_item.slot());
-
-        if stack.item != ItemKind::EnderPearl {
-            continue;
-        }
-
-        inventory.try_remove_all(&ItemStack::new(ItemKind::EnderPearl, 1, None));
-
-        let mut layer = layer.single_mut();
-
-        layer.play_sound(
-            Sound::EntityEnderPearlThrow,
-            SoundCategory::Neutral,
-            position.0,
-            0.5,
-            rand::thread_rng().gen_range(0.333..0.5),
-        );
-
-        let yaw = look.yaw.to_radians();
-        let pitch = look.pitch.to_radians();
-
-        let direction = Vec3::new(
-            -yaw.sin() * pitch.cos(),
-            -pitch.sin(),
-            yaw.cos() * pitch.cos(),
-        );
-
-        let velocity = calculate_projectile_velocity(direction, 2.0, ENDER_PEARL_INACCURACY);
-
-        let mut position = position.0;
-        position.y += if combat_state.is_sneaking {
-            SNEAK_EYE_HEIGHT
-        } else {
-            EYE_HEIGHT
-        } as f64
-            - 0.1;
-
-        commands
-            .spawn(EnderPearlEntityBundle {
-                position: Position(position),
-                velocity: Velocity(velocity),
-                layer: *layer_id,
-                entity_no_gravity: NoGravity(true),
-
-                ..Default::default()
-            })
-            .insert(PhysicsMarker)
-            .insert(CollidesWithBlocks(None))
-            .insert(DespawnTimer::from_secs(50.0))
-            .insert(EnderPearlOwner(player_ent))
-            .insert(TerminalVelocity(ENDER_PEARL_TERMINAL_VELOCITY))
-            .insert(Gravity(ENDER_PEARL_GRAVITY));
-
-        commands
-            .entity(player_ent)
-            .insert(EnderPearlTimer::default());
-    }
+) -> bool {
+    let Ok((layer_id, position, combat_state, look)) = clients.get(item_use.user) else {
+        return false;
+    };
+
+    let Ok(mut layer) = layer.get_mut(layer_id.0) else {
+        return false;
+    };
+
+    layer.play_sound(
+        Sound::EntityEnderPearlThrow,
+        SoundCategory::Neutral,
+        position.0,
+        0.5,
+        rand::thread_rng().gen_range(0.333..0.5),
+    );
+
+    let direction = look_direction(look);
+
+    let velocity = calculate_projectile_velocity(direction, 2.0, ENDER_PEARL_INACCURACY);
+
+    let mut position = combat_state.eye_position(position.0);
+    position.y -= 0.1;
+
+    commands
+        .spawn(EnderPearlEntityBundle {
+            position: Position(position),
+            velocity: Velocity(velocity),
+            layer: *layer_id,
+            entity_no_gravity: NoGravity(true),
+
+            ..Default::default()
+        })
+        .insert(PhysicsMarker)
+        .insert(CollidesWithBlocks(None))
+        .insert(DespawnTimer::from_secs(50.0))
+        .insert(CustomProjectile {
+            item: ENDER_PEARL_ID.to_string(),
+            owner: item_use.user,
+        })
+        .insert(TerminalVelocity(ENDER_PEARL_TERMINAL_VELOCITY))
+        .insert(Gravity(ENDER_PEARL_GRAVITY));
+
+    true
 }
 
 fn on_hit_block(
+    In((pearl, event)): In<(Entity, EntityBlockCollisionEvent)>,
     mut commands: Commands,
-    mut ender_pearls: Query<(Entity, &EnderPearlOwner)>,
+    ender_pearls: Query<&CustomProjectile>,
     mut thrower: Query<(&mut Position, &Equipment, &mut FallingState)>,
-    mut events: EventReader<EntityBlockCollisionEvent>,
     mut damage_writer: EventWriter<PlayerHurtEvent>,
 ) {
-    for event in events.read() {
-        let Ok((pearl, owner)) = ender_pearls.get_mut(event.entity) else {
-            continue;
-        };
-
-        let Ok((mut position, equipment, mut falling_state)) = thrower.get_mut(owner.0) else {
-            continue;
-        };
+    let Ok(owner) = ender_pearls.get(pearl) else {
+        return;
+    };
 
-        commands.entity(pearl).insert(Despawned);
+    commands.entity(pearl).insert(Despawned);
 
-        *falling_state = FallingState {
-            fall_start_y: event.collision_pos.y,
-            falling: false,
-        };
+    let Ok((mut position, equipment, mut falling_state)) = thrower.get_mut(owner.owner) else {
+        return;
+    };
 
-        position.set(event.collision_pos);
+    *falling_state = FallingState {
+        fall_start_y: event.collision_pos.y,
+        falling: false,
+    };
 
-        let damage = equipment.received_damage(ENDER_PEARL_BASE_DMG);
+    position.set(event.collision_pos);
 
-        damage_writer.send(PlayerHurtEvent {
-            attacker: None,
-            victim: owner.0,
-            damage,
-            position: event.collision_pos,
-        });
-    }
-}
+    let damage = equipment.received_damage(ENDER_PEARL_BASE_DMG);
 
-fn tick_pearl_timer(mut throwsers: Query<&mut EnderPearlTimer>, time: Res<Time>) {
-    for mut timer in throwsers.iter_mut() {
-        timer.0.tick(time.delta());
-    }
+    damage_writer.send(PlayerHurtEvent {
+        attacker: None,
+        victim: owner.owner,
+        damage,
+        position: event.collision_pos,
+    });
 }
@@ -0,0 +1,138 @@
+use std::{sync::Arc, time::Duration};
+
+use bevy_ecs::system::RunSystemOnce;
+use valence::{
+    entity::{entity::NoGravity, fireball::FireballEntityBundle, Velocity},
+    prelude::*,
+    protocol::{sound::SoundCategory, Sound},
+};
+
+use crate::{
+    base::{
+        bow::calculate_projectile_velocity,
+        combat::{look_direction, CombatState},
+        explosion::ExplosionEvent,
+        physics::{
+            CollidesWithBlocks, CollidesWithEntities, EntityBlockCollisionEvent, PhysicsMarker,
+        },
+    },
+    items::custom_item::{
+        CustomItem, CustomItemAppExt, CustomItemHooks, CustomProjectile, ItemUse,
+    },
+    utils::despawn_timer::DespawnTimer,
+};
+
+pub const FIREBALL_ID: &str = "fireball";
+/// Speed of the fireball in blocks per tick
+const FIREBALL_SPEED: f32 = 1.0;
+const FIREBALL_COOLDOWN: Duration = Duration::from_millis(500);
+const FIREBALL_POWER: f32 = 2.0;
+const FIREBALL_DAMAGE: f32 = 3.0;
+/// Strong enough to jump with a fireball
+const FIREBALL_KNOCKBACK: f32 = 1.5;
+const FIREBALL_BURN_SECS: f32 = 3.0;
+
+pub struct FireballPlugin;
+
+impl Plugin for FireballPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_custom_item(CustomItem {
+            id: FIREBALL_ID.to_string(),
+            base: ItemKind::FireCharge,
+            name: "Fireball".to_string(),
+            lore: vec!["Explodes on impact".to_string()],
+            cooldown: FIREBALL_COOLDOWN,
+            consumed_on_use: true,
+            hooks: Arc::new(Fireball),
+        });
+    }
+}
+
+struct Fireball;
+
+impl CustomItemHooks for Fireball {
+    fn on_use(&self, world: &mut World, item_use: ItemUse) -> bool {
+        world.run_system_once_with(item_use, launch_fireball)
+    }
+
+    fn on_hit_block(&self, world: &mut World, fireball: Entity, hit: &EntityBlockCollisionEvent) {
+        world.run_system_once_with((fireball, Some(hit.collision_pos)), explode);
+    }
+
+    fn on_hit_entity(&self, world: &mut World, fireball: Entity, _hit: Entity) {
+        world.run_system_once_with((fireball, None), explode);
+    }
+}
+
+fn launch_fireball(
+    In(item_use): In<ItemUse>,
+    mut commands: Commands,
+    clients: Query<(&EntityLayerId, &Position, &CombatState, &Look)>,
+    mut layers: Query<&mut ChunkLayer>,
+) -> bool {
+    let Ok((layer_id, position, combat_state, look)) = clients.get(item_use.user) else {
+        return false;
+    };
+
+    let Ok(mut layer) = layers.get_mut(layer_id.0) else {
+        return false;
+    };
+
+    layer.play_sound(
+        Sound::EntityBlazeShoot,
+        SoundCategory::Player,
+        position.0,
+        1.0,
+        1.0,
+    );
+
+    let direction = look_direction(look);
+
+    let velocity = calculate_projectile_velocity(direction, FIREBALL_SPEED, 0.0);
+
+    let mut position = combat_state.eye_position(position.0);
+    position.y -= 0.1;
+
+    commands
+        .spawn(FireballEntityBundle {
+            position: Position(position),
+            velocity: Velocity(velocity),
+            layer: *layer_id,
+            entity_no_gravity: NoGravity(true),
+            ..Default::default()
+        })
+        .insert(PhysicsMarker)
+        .insert(CollidesWithBlocks(None))
+        .insert(CollidesWithEntities(None))
+        .insert(DespawnTimer::from_secs(10.0))
+        .insert(CustomProjectile {
+            item: FIREBALL_ID.to_string(),
+            owner: item_use.user,
+        });
+
+    true
+}
+
+/// Explode a fireball where it hit a block, or at its position if it hit a player
+fn explode(
+    In((fireball, collision_pos)): In<(Entity, Option<DVec3>)>,
+    mut commands: Commands,
+    fireballs: Query<(&CustomProjectile, &EntityLayerId, &Position)>,
+    mut explosion_writer: EventWriter<ExplosionEvent>,
+) {
+    let Ok((projectile, layer_id, position)) = fireballs.get(fireball) else {
+        return;
+    };
+
+    commands.entity(fireball).insert(Despawned);
+
+    explosion_writer.send(ExplosionEvent {
+        arena: layer_id.0,
+        position: collision_pos.unwrap_or(position.0),
+        power: FIREBALL_POWER,
+        damage: FIREBALL_DAMAGE,
+        knockback: FIREBALL_KNOCKBACK,
+        burn_secs: FIREBALL_BURN_SECS,
+        owner: Some(projectile.owner),
+    });
+}
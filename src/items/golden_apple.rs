@@ -0,0 +1,48 @@
+use std::time::Duration;
+
+use valence::{
+    prelude::*,
+    protocol::{sound::SoundCategory, Sound},
+};
+
+use crate::items::{
+    consumable::ConsumptionEvent,
+    effects::{ActiveEffects, StatusEffect},
+};
+
+const ABSORPTION_DURATION: Duration = Duration::from_secs(120);
+const REGENERATION_DURATION: Duration = Duration::from_secs(5);
+
+pub struct GoldenApplePlugin;
+
+impl Plugin for GoldenApplePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, eat_golden_apple);
+    }
+}
+
+fn eat_golden_apple(
+    mut players: Query<(&Position, &mut ActiveEffects, &mut Client)>,
+    mut events: EventReader<ConsumptionEvent>,
+) {
+    for event in events.read() {
+        if event.consumed.item != ItemKind::GoldenApple {
+            continue;
+        }
+
+        let Ok((position, mut effects, mut client)) = players.get_mut(event.consumer) else {
+            continue;
+        };
+
+        effects.add(StatusEffect::Absorption, 0, ABSORPTION_DURATION);
+        effects.add(StatusEffect::Regeneration, 1, REGENERATION_DURATION);
+
+        client.play_sound(
+            Sound::EntityPlayerBurp,
+            SoundCategory::Player,
+            position.0,
+            0.5,
+            1.0,
+        );
+    }
+}
@@ -0,0 +1,197 @@
+use std::{sync::Arc, time::Duration};
+
+use bevy_ecs::system::RunSystemOnce;
+use valence::{
+    math::Aabb,
+    prelude::*,
+    protocol::{sound::SoundCategory, Sound},
+};
+
+use crate::{
+    base::{
+        armor::EquipmentExtReduction,
+        combat::{look_direction, CombatState},
+        death::{IsDead, PlayerHurtEvent},
+    },
+    bedwars_config::GameRules,
+    items::custom_item::{CustomItem, CustomItemAppExt, CustomItemHooks, CustomItems, ItemUse},
+    utils::{
+        inventory::InventoryExt,
+        ray_cast::{ray_aabb_intersection, ray_blocks},
+    },
+    GameState, Spectator, Team,
+};
+
+pub const LASER_BOW_ID: &str = "laser_bow";
+/// Maximum length of the beam in blocks
+const LASER_BOW_RANGE: f64 = 64.0;
+const LASER_BOW_DAMAGE: f32 = 6.0;
+/// Distance between two particles of the beam
+const BEAM_PARTICLE_SPACING: f64 = 0.5;
+
+pub struct LaserBowPlugin;
+
+impl Plugin for LaserBowPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_custom_item(CustomItem {
+            id: LASER_BOW_ID.to_string(),
+            base: ItemKind::Bow,
+            name: "Laser Bow".to_string(),
+            lore: vec!["Fires an instant beam".to_string()],
+            // Set from the game rules
+            cooldown: Duration::from_millis(GameRules::default().laser_bow_cooldown_millis),
+            consumed_on_use: false,
+            hooks: Arc::new(LaserBow),
+        })
+        .add_systems(Update, update_laser_bow_cooldown);
+    }
+}
+
+struct LaserBow;
+
+impl CustomItemHooks for LaserBow {
+    fn on_use(&self, world: &mut World, item_use: ItemUse) -> bool {
+        world.run_system_once_with(item_use, fire_laser)
+    }
+}
+
+fn update_laser_bow_cooldown(rules: Res<GameRules>, mut custom_items: ResMut<CustomItems>) {
+    if !rules.is_changed() {
+        return;
+    }
+
+    if let Some(laser_bow) = custom_items.get_mut(LASER_BOW_ID) {
+        laser_bow.cooldown = Duration::from_millis(rules.laser_bow_cooldown_millis);
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn fire_laser(
+    In(item_use): In<ItemUse>,
+    mut shooters: Query<(
+        &EntityLayerId,
+        &Position,
+        &CombatState,
+        &Look,
+        &mut Inventory,
+        &mut Client,
+        Option<&Team>,
+    )>,
+    targets: Query<
+        (
+            Entity,
+            &EntityLayerId,
+            &Hitbox,
+            &Equipment,
+            &Position,
+            Option<&Team>,
+        ),
+        (With<Client>, Without<IsDead>, Without<Spectator>),
+    >,
+    mut arenas: Query<(&GameState, &mut ChunkLayer)>,
+    mut hurt_writer: EventWriter<PlayerHurtEvent>,
+    rules: Res<GameRules>,
+) -> bool {
+    let Ok((layer_id, position, combat_state, look, mut inventory, mut client, shooter_team)) =
+        shooters.get_mut(item_use.user)
+    else {
+        return false;
+    };
+
+    let Ok((state, mut layer)) = arenas.get_mut(layer_id.0) else {
+        return false;
+    };
+
+    if *state != GameState::Match {
+        return false;
+    }
+
+    if rules.laser_bow_ammo_cost > 0 {
+        let ammo = ItemStack::new(ItemKind::Arrow, rules.laser_bow_ammo_cost, None);
+        if !inventory.try_remove_all(&ammo) {
+            client.send_chat_message(format!(
+                "§cThe laser bow needs {} arrows",
+                rules.laser_bow_ammo_cost
+            ));
+            return false;
+        }
+    }
+
+    let direction = look_direction(look).as_dvec3();
+    let origin = combat_state.eye_position(position.0);
+
+    let block_distance = ray_blocks(origin, direction, LASER_BOW_RANGE)
+        .into_iter()
+        .find_map(|pos| {
+            let block = layer.block(pos)?;
+            let offset = DVec3::new(pos.x as f64, pos.y as f64, pos.z as f64);
+
+            block
+                .state
+                .collision_shapes()
+                .filter_map(|shape| {
+                    let shape = Aabb::new(shape.min() + offset, shape.max() + offset);
+                    ray_aabb_intersection(origin, direction, LASER_BOW_RANGE, &shape)
+                })
+                .min_by(f64::total_cmp)
+        })
+        .unwrap_or(LASER_BOW_RANGE);
+
+    let hit = targets
+        .iter()
+        .filter(|(target, target_layer_id, ..)| {
+            *target != item_use.user && target_layer_id.0 == layer_id.0
+        })
+        .filter(|(.., target_team)| {
+            rules.friendly_fire || shooter_team.is_none() || shooter_team != *target_team
+        })
+        .filter_map(|(target, _, hitbox, equipment, target_position, _)| {
+            let distance = ray_aabb_intersection(origin, direction, block_distance, hitbox)?;
+            Some((distance, target, equipment, target_position))
+        })
+        .min_by(|(a, ..), (b, ..)| a.total_cmp(b));
+
+    let beam_length = hit.map_or(block_distance, |(distance, ..)| distance);
+
+    layer.play_sound(
+        Sound::EntityArrowShoot,
+        SoundCategory::Player,
+        position.0,
+        1.0,
+        2.0,
+    );
+
+    let particles = (beam_length / BEAM_PARTICLE_SPACING) as usize;
+    for i in 1..=particles {
+        layer.play_particle(
+            &Particle::Dust {
+                rgb: Vec3::new(1.0, 0.0, 0.0),
+                scale: 1.0,
+            },
+            true,
+            origin + direction * (i as f64 * BEAM_PARTICLE_SPACING),
+            Vec3::ZERO,
+            0.0,
+            1,
+        );
+    }
+
+    if let Some((_, victim, equipment, victim_position)) = hit {
+        client.play_sound(
+            Sound::EntityArrowHitPlayer,
+            SoundCategory::Player,
+            position.0,
+            1.0,
+            1.0,
+        );
+
+        hurt_writer.send(PlayerHurtEvent {
+            attacker: Some(item_use.user),
+            victim,
+            damage: equipment.received_damage(LASER_BOW_DAMAGE),
+            position: victim_position.0,
+        });
+    }
+
+    true
+}
@@ -1,5 +1,10 @@
+pub mod bridge_egg;
 pub mod consumable;
+pub mod custom_item;
 pub mod effects;
 pub mod ender_pearl;
+pub mod fireball;
+pub mod golden_apple;
+pub mod laser_bow;
 pub mod port_a_fort;
 pub mod tnt;
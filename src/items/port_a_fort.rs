@@ -1 +1,297 @@
+use std::{collections::VecDeque, sync::Arc, time::Duration};
 
+use bevy_ecs::system::RunSystemOnce;
+use valence::{
+    prelude::*,
+    protocol::{sound::SoundCategory, Sound},
+};
+
+use crate::{
+    base::build::PlayerPlacedBlocks,
+    bedwars_config::{Structure, StructureConfig},
+    items::custom_item::{CustomItem, CustomItemAppExt, CustomItemHooks, ItemUse},
+    utils::item_kind::ItemKindExtColor,
+    GameState, Team,
+};
+
+pub const POP_UP_TOWER_ID: &str = "pop_up_tower";
+
+/// A structure that is built block by block in an arena
+#[derive(Debug, Component)]
+pub struct StructureBuild {
+    pub arena: Entity,
+    /// Blocks that are not placed yet, in placement order
+    pub blocks: VecDeque<(BlockPos, BlockState)>,
+    pub blocks_per_tick: usize,
+}
+
+pub struct PortAFortPlugin;
+
+impl Plugin for PortAFortPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_custom_item(CustomItem {
+            id: POP_UP_TOWER_ID.to_string(),
+            base: ItemKind::Chest,
+            name: "Pop-up Tower".to_string(),
+            lore: vec!["Builds a tower in front of you".to_string()],
+            cooldown: Duration::ZERO,
+            consumed_on_use: true,
+            hooks: Arc::new(PopUpTower),
+        })
+        .add_systems(Update, build_structures);
+    }
+}
+
+struct PopUpTower;
+
+impl CustomItemHooks for PopUpTower {
+    fn on_use(&self, world: &mut World, item_use: ItemUse) -> bool {
+        world.run_system_once_with(item_use, place_pop_up_tower)
+    }
+}
+
+/// The tower is placed like a block, so it needs a clicked block
+fn place_pop_up_tower(
+    In(item_use): In<ItemUse>,
+    mut commands: Commands,
+    clients: Query<(&EntityLayerId, &Position, &Look, &Team)>,
+    arenas: Query<&GameState>,
+    structures: Res<StructureConfig>,
+) -> bool {
+    if item_use.block.is_none() {
+        return false;
+    }
+
+    let Ok((layer_id, position, look, team)) = clients.get(item_use.user) else {
+        return false;
+    };
+
+    if !arenas
+        .get(layer_id.0)
+        .is_ok_and(|state| *state == GameState::Match)
+    {
+        return false;
+    }
+
+    let turns = ((look.yaw / 90.0).round() as i32).rem_euclid(4);
+    let feet = BlockPos::new(
+        position.0.x.floor() as i32,
+        position.0.y.floor() as i32,
+        position.0.z.floor() as i32,
+    );
+    let (forward_x, forward_z) = rotate_offset(0, 1, turns);
+    let origin = BlockPos::new(feet.x + forward_x, feet.y, feet.z + forward_z);
+
+    commands.spawn(StructureBuild {
+        arena: layer_id.0,
+        blocks: structure_blocks(&structures.pop_up_tower, origin, turns, team),
+        blocks_per_tick: structures.pop_up_tower.blocks_per_tick.max(1),
+    });
+
+    true
+}
+
+/// The blocks of a structure in world coordinates, rotated by `turns` quarter turns
+/// (0 = looking south, 1 = west, 2 = north, 3 = east) and colored for the team
+fn structure_blocks(
+    structure: &Structure,
+    origin: BlockPos,
+    turns: i32,
+    team: &Team,
+) -> VecDeque<(BlockPos, BlockState)> {
+    structure
+        .blocks
+        .iter()
+        .map(|block| {
+            let (x, z) = rotate_offset(block.offset.x, block.offset.z, turns);
+            let pos = BlockPos::new(origin.x + x, origin.y + block.offset.y, origin.z + z);
+
+            let state: BlockState = block.block.clone().into();
+            let state = rotate_facing(colored(state, team), turns);
+            (pos, state)
+        })
+        .collect()
+}
+
+/// Rotate an offset by quarter turns, in the direction south -> west -> north -> east
+fn rotate_offset(x: i32, z: i32, turns: i32) -> (i32, i32) {
+    (0..turns).fold((x, z), |(x, z), _| (-z, x))
+}
+
+/// Rotate the `facing` property by quarter turns, like [`rotate_offset`]
+fn rotate_facing(state: BlockState, turns: i32) -> BlockState {
+    const FACINGS: [PropValue; 4] = [
+        PropValue::South,
+        PropValue::West,
+        PropValue::North,
+        PropValue::East,
+    ];
+
+    let Some(index) = state
+        .get(PropName::Facing)
+        .and_then(|facing| FACINGS.iter().position(|f| *f == facing))
+    else {
+        return state;
+    };
+
+    state.set(
+        PropName::Facing,
+        FACINGS[(index + turns as usize) % FACINGS.len()],
+    )
+}
+
+/// Change wool, carpets and beds to the team color, other properties are kept
+fn colored(state: BlockState, team: &Team) -> BlockState {
+    let Some(kind) =
+        BlockKind::from_item_kind(state.to_kind().to_item_kind().to_colored(team.color))
+    else {
+        return state;
+    };
+
+    if kind == state.to_kind() {
+        return state;
+    }
+
+    state
+        .to_kind()
+        .props()
+        .iter()
+        .fold(kind.to_state(), |colored, prop| match state.get(*prop) {
+            Some(value) => colored.set(*prop, value),
+            None => colored,
+        })
+}
+
+/// Place the next blocks of every structure, occupied blocks are skipped
+fn build_structures(
+    mut commands: Commands,
+    mut builds: Query<(Entity, &mut StructureBuild)>,
+    mut arenas: Query<(&GameState, &mut ChunkLayer, &mut PlayerPlacedBlocks)>,
+) {
+    for (entity, mut build) in &mut builds {
+        let Ok((state, mut layer, mut player_placed_blocks)) = arenas.get_mut(build.arena) else {
+            commands.entity(entity).despawn();
+            continue;
+        };
+
+        // The match ended while the structure was being built
+        if *state != GameState::Match {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        let mut placed = 0;
+        while placed < build.blocks_per_tick {
+            let Some((pos, block)) = build.blocks.pop_front() else {
+                break;
+            };
+
+            if layer
+                .block(pos)
+                .map_or(true, |current| !current.state.is_air())
+            {
+                continue;
+            }
+
+            layer.set_block(pos, block);
+            player_placed_blocks.0.insert(pos, block);
+            placed += 1;
+
+            layer.play_sound(
+                Sound::BlockWoolPlace,
+                SoundCategory::Block,
+                DVec3::new(pos.x as f64 + 0.5, pos.y as f64 + 0.5, pos.z as f64 + 0.5),
+                0.5,
+                1.0,
+            );
+        }
+
+        if build.blocks.is_empty() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::{
+        base::build::placed_block,
+        colors::TeamColor,
+        items::custom_item::{use_custom_item, CustomItems},
+    };
+
+    const SLOT: u16 = 36;
+
+    #[test]
+    fn test_pop_up_tower_uses_one_item() {
+        let mut app = App::new();
+        app.add_plugins(PortAFortPlugin)
+            .init_resource::<StructureConfig>();
+        let world = app.world_mut();
+
+        let tower = world
+            .resource::<CustomItems>()
+            .get(POP_UP_TOWER_ID)
+            .unwrap()
+            .stack(2);
+
+        // The tower is built instead of placing a chest
+        assert!(placed_block(&tower, world.resource::<CustomItems>()).is_none());
+
+        let arena = world.spawn(GameState::Match).id();
+        let mut inventory = Inventory::new(InventoryKind::Player);
+        inventory.set_slot(SLOT, tower);
+
+        let user = world
+            .spawn((
+                inventory,
+                EntityLayerId(arena),
+                Position(DVec3::new(0.5, 64.0, 0.5)),
+                Look::new(0.0, 0.0),
+                Team {
+                    name: "red".to_string(),
+                    color: TeamColor::Red,
+                },
+            ))
+            .id();
+
+        use_custom_item(
+            world,
+            ItemUse {
+                user,
+                slot: SLOT,
+                block: Some((BlockPos::new(0, 63, 1), Direction::Up)),
+            },
+        );
+
+        assert_eq!(world.get::<Inventory>(user).unwrap().slot(SLOT).count, 1);
+        assert_eq!(
+            world
+                .query::<&StructureBuild>()
+                .iter(world)
+                .filter(|build| build.arena == arena)
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_rotate_structure() {
+        // Looking west, forward is -x
+        assert_eq!(rotate_offset(0, 1, 1), (-1, 0));
+        assert_eq!(rotate_offset(0, 1, 2), (0, -1));
+        assert_eq!(rotate_offset(0, 1, 3), (1, 0));
+        assert_eq!(rotate_offset(2, 1, 4), (2, 1));
+
+        let ladder = BlockState::LADDER.set(PropName::Facing, PropValue::North);
+        assert_eq!(
+            rotate_facing(ladder, 1).get(PropName::Facing),
+            Some(PropValue::East)
+        );
+        assert_eq!(
+            rotate_facing(BlockState::WHITE_WOOL, 1),
+            BlockState::WHITE_WOOL
+        );
+    }
+}
@@ -1 +1,148 @@
+use std::{sync::Arc, time::Duration};
 
+use bevy_ecs::system::RunSystemOnce;
+use bevy_time::{Time, Timer, TimerMode};
+use valence::{
+    entity::{entity::NoGravity, tnt::TntEntityBundle, Velocity},
+    prelude::*,
+    protocol::{sound::SoundCategory, Sound},
+};
+
+use crate::{
+    base::{
+        explosion::ExplosionEvent,
+        physics::{CollidesWithBlocks, GetsStuckOnCollision, Gravity, PhysicsMarker},
+    },
+    items::custom_item::{CustomItem, CustomItemAppExt, CustomItemHooks, ItemUse},
+    GameState,
+};
+
+pub const TNT_ID: &str = "tnt";
+const TNT_FUSE_TICKS: i32 = 60;
+const TNT_POWER: f32 = 4.0;
+const TNT_DAMAGE: f32 = 8.0;
+const TNT_KNOCKBACK: f32 = 0.8;
+const TNT_GRAVITY: f32 = 20.0;
+/// Primed tnt jumps up a little, like in vanilla
+const TNT_JUMP_VELOCITY: f32 = 4.0;
+
+/// Tnt that explodes once the fuse runs out
+#[derive(Debug, Component)]
+pub struct PrimedTnt {
+    /// The player that placed the tnt
+    pub owner: Option<Entity>,
+    pub fuse: Timer,
+}
+
+pub struct TntPlugin;
+
+impl Plugin for TntPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_custom_item(CustomItem {
+            id: TNT_ID.to_string(),
+            base: ItemKind::Tnt,
+            name: "TNT".to_string(),
+            lore: vec!["Ignites right away when placed".to_string()],
+            cooldown: Duration::ZERO,
+            consumed_on_use: true,
+            hooks: Arc::new(Tnt),
+        })
+        .add_systems(Update, explode_tnt);
+    }
+}
+
+struct Tnt;
+
+impl CustomItemHooks for Tnt {
+    fn on_use(&self, world: &mut World, item_use: ItemUse) -> bool {
+        world.run_system_once_with(item_use, place_tnt)
+    }
+}
+
+/// Tnt is not placed as a block, primed tnt is spawned right away
+fn place_tnt(
+    In(item_use): In<ItemUse>,
+    mut commands: Commands,
+    clients: Query<&EntityLayerId>,
+    mut arenas: Query<(&GameState, &mut ChunkLayer)>,
+) -> bool {
+    let Some((clicked_pos, face)) = item_use.block else {
+        return false;
+    };
+
+    let Ok(layer_id) = clients.get(item_use.user) else {
+        return false;
+    };
+
+    let Ok((state, mut layer)) = arenas.get_mut(layer_id.0) else {
+        return false;
+    };
+
+    let block_pos = clicked_pos.get_in_direction(face);
+    if *state != GameState::Match
+        || !layer
+            .block(block_pos)
+            .is_some_and(|block| block.state.is_air())
+    {
+        return false;
+    }
+
+    let position = DVec3::new(
+        block_pos.x as f64 + 0.5,
+        block_pos.y as f64,
+        block_pos.z as f64 + 0.5,
+    );
+
+    layer.play_sound(
+        Sound::EntityTntPrimed,
+        SoundCategory::Block,
+        position,
+        1.0,
+        1.0,
+    );
+
+    commands
+        .spawn(TntEntityBundle {
+            position: Position(position),
+            velocity: Velocity(Vec3::new(0.0, TNT_JUMP_VELOCITY, 0.0)),
+            layer: *layer_id,
+            tnt_fuse: valence::entity::tnt::Fuse(TNT_FUSE_TICKS),
+            entity_no_gravity: NoGravity(true),
+            ..Default::default()
+        })
+        .insert(PrimedTnt {
+            owner: Some(item_use.user),
+            fuse: Timer::from_seconds(TNT_FUSE_TICKS as f32 / 20.0, TimerMode::Once),
+        })
+        .insert(PhysicsMarker)
+        .insert(Gravity(TNT_GRAVITY))
+        .insert(CollidesWithBlocks(None))
+        .insert(GetsStuckOnCollision::ground());
+
+    true
+}
+
+fn explode_tnt(
+    mut commands: Commands,
+    mut tnt: Query<(Entity, &EntityLayerId, &Position, &mut PrimedTnt)>,
+    mut explosion_writer: EventWriter<ExplosionEvent>,
+    time: Res<Time>,
+) {
+    for (entity, layer_id, position, mut primed) in &mut tnt {
+        if !primed.fuse.tick(time.delta()).finished() {
+            continue;
+        }
+
+        commands.entity(entity).insert(Despawned);
+
+        explosion_writer.send(ExplosionEvent {
+            arena: layer_id.0,
+            position: position.0 + DVec3::new(0.0, 0.0625, 0.0),
+            power: TNT_POWER,
+            damage: TNT_DAMAGE,
+            knockback: TNT_KNOCKBACK,
+            burn_secs: 0.0,
+            owner: primed.owner,
+        });
+    }
+}
@@ -0,0 +1,557 @@
+use std::path::PathBuf;
+
+use arena::{pick_arena, spawn_arena, Arena, LayerResources};
+use base::{
+    armor_right_click_equip::ArmorRightClickEquipPlugin,
+    bow::BowPlugin,
+    break_blocks::BlockBreakPlugin,
+    build::BuildPlugin,
+    chat::ChatPlugin,
+    chests::ChestPlugin,
+    combat::CombatPlugin,
+    death::{DeathPlugin, IsDead},
+    drop_items::ItemDropPlugin,
+    explosion::ExplosionPlugin,
+    fall_damage::FallDamagePlugin,
+    item_pickup::ItemPickupPlugin,
+    physics::PhysicsPlugin,
+    regeneration::RegenerationPlugin,
+    scoreboard::ScoreboardPlugin,
+    utils::debug::DebugPlugin,
+    void_death::VoidDeathPlugin,
+};
+use bedwars_config::{BedwarsSettings, GameRules, WorldConfig};
+use bevy_time::Time;
+use colors::TeamColor;
+use commands::{
+    bedwars::{handle_bedwars_command, BedwarsCommand},
+    bedwars_admin::{handle_bedwars_admin_command, BedwarsAdminCommand},
+};
+use edit::EditPlugin;
+use items::bridge_egg::BridgeEggPlugin;
+use items::consumable::ConsumablePlugin;
+use items::custom_item::CustomItemPlugin;
+use items::effects::{potion::PotionPlugin, EffectsPlugin};
+use items::ender_pearl::EnderPearlPlugin;
+use items::fireball::FireballPlugin;
+use items::golden_apple::GoldenApplePlugin;
+use items::laser_bow::LaserBowPlugin;
+use items::port_a_fort::PortAFortPlugin;
+use items::tnt::TntPlugin;
+use lobby::{LobbyPlayerState, LobbyPlugin};
+use maps::{MapInfo, MapRotation};
+use menu::ItemMenuPlugin;
+use party::PartyPlugin;
+use r#match::{match_player_bundle, MatchPlugin};
+use reconnect::{DisconnectedPlayer, DisconnectedPlayers, ReconnectPlugin};
+use resource_spawners::ResourceSpawnerPlugin;
+use schedule::MatchSchedulePlugin;
+// use resource_spawners::ResourceSpawnerPlugin;
+use shop::ShopPlugin;
+use spectator::SpectatorPlugin;
+use team_upgrades::TeamUpgradesPlugin;
+use traps::TrapPlugin;
+use utils::despawn_timer::DespawnTimerPlugin;
+use valence::{
+    app::{PluginGroup, PluginGroupBuilder},
+    command::{scopes::CommandScopes, AddCommand},
+    message::SendMessage,
+    player_list::DisplayName,
+    prelude::*,
+};
+
+pub mod arena;
+pub mod base;
+pub mod bedwars_config;
+pub mod colors;
+pub mod commands;
+pub mod config_migration;
+pub mod config_validation;
+pub mod edit;
+pub mod items;
+pub mod lobby;
+pub mod maps;
+pub mod r#match;
+pub mod menu;
+pub mod party;
+pub mod reconnect;
+pub mod resource_spawners;
+pub mod schedule;
+pub mod shop;
+pub mod spectator;
+pub mod team_upgrades;
+pub mod traps;
+pub mod utils;
+
+/// A component that will be attached to players in the lobby
+#[derive(Debug, Default, Component)]
+pub struct LobbyPlayer;
+/// A component that will be attached to players spectating a match
+#[derive(Debug, Default, Component)]
+pub struct Spectator;
+
+/// A component that will be attached to players that are still playing
+// #[derive(Debug, Default, Component)]
+// pub struct ActivePlayer;
+
+/// A component that will be attached to players that are editing the map
+#[derive(Debug, Default, Component)]
+pub struct Editor;
+
+/// A component that will be attached to players in a match
+#[derive(Debug, Clone, Component, PartialEq, Eq, Hash)]
+pub struct Team {
+    pub name: String,
+    pub color: TeamColor,
+}
+
+/// The state of an arena, stored on the arena entity.
+/// To switch states insert the new state with `Commands`,
+/// so the change is applied together with the other changes of that tick.
+#[derive(Component, Debug, Hash, PartialEq, Eq, Clone, Copy, Default)]
+pub enum GameState {
+    #[default]
+    Lobby,
+    Match,
+    PostMatch,
+    Edit,
+}
+
+// Time stamp when the last tick finished
+#[derive(Debug, Default, Resource)]
+pub struct LastTickTime(pub std::time::Duration);
+
+/// All plugins of the bedwars server.
+///
+/// This requires the valence `DefaultPlugins` and the bevy `TimePlugin` to be added as well.
+/// Single plugins can be disabled with `BedwarsPlugins.build().disable::<EnderPearlPlugin>()`.
+pub struct BedwarsPlugins;
+
+impl PluginGroup for BedwarsPlugins {
+    fn build(self) -> PluginGroupBuilder {
+        PluginGroupBuilder::start::<Self>()
+            .add(BedwarsCorePlugin)
+            .add(ChatPlugin)
+            .add(SpectatorPlugin)
+            .add(ScoreboardPlugin)
+            .add(EditPlugin)
+            .add(VoidDeathPlugin)
+            .add(DeathPlugin)
+            .add(FallDamagePlugin)
+            .add(LobbyPlugin)
+            .add(PartyPlugin)
+            .add(BuildPlugin)
+            .add(BlockBreakPlugin)
+            .add(ItemMenuPlugin)
+            .add(MatchPlugin)
+            .add(ReconnectPlugin)
+            .add(MatchSchedulePlugin)
+            .add(ShopPlugin)
+            .add(TeamUpgradesPlugin)
+            .add(TrapPlugin)
+            .add(ItemPickupPlugin)
+            .add(RegenerationPlugin)
+            .add(BowPlugin)
+            .add(PhysicsPlugin)
+            .add(CustomItemPlugin)
+            .add(EnderPearlPlugin)
+            .add(EffectsPlugin)
+            .add(PotionPlugin)
+            .add(ConsumablePlugin)
+            .add(GoldenApplePlugin)
+            .add(ExplosionPlugin)
+            .add(TntPlugin)
+            .add(FireballPlugin)
+            .add(PortAFortPlugin)
+            .add(BridgeEggPlugin)
+            .add(LaserBowPlugin)
+            .add(DespawnTimerPlugin)
+            .add(ItemDropPlugin)
+            .add(ResourceSpawnerPlugin)
+            .add(CombatPlugin)
+            .add(ArmorRightClickEquipPlugin)
+            .add(ChestPlugin)
+            .add(DebugPlugin)
+    }
+}
+
+/// Loads the arenas and moves joining players into them
+pub struct BedwarsCorePlugin;
+
+impl Plugin for BedwarsCorePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup)
+            .add_systems(
+                Update,
+                (
+                    init_clients,
+                    handle_bedwars_admin_command,
+                    handle_bedwars_command,
+                    update_last_tick_time,
+                    despawn_disconnected_clients,
+                ),
+            )
+            .add_command::<BedwarsAdminCommand>()
+            .add_command::<BedwarsCommand>()
+            .init_resource::<BedwarsSettings>()
+            .insert_resource(LastTickTime::default())
+            .observe(on_disconnect);
+    }
+}
+
+fn setup(mut commands: Commands, layer_resources: LayerResources, settings: Res<BedwarsSettings>) {
+    let map_dirs = bedwars_config::map_dirs(&settings.config_dir).unwrap_or_else(|e| {
+        tracing::error!("Failed to find maps: {}", e);
+        std::process::exit(1);
+    });
+
+    let arena_count = if map_dirs.is_empty() {
+        setup_arenas(&mut commands, &layer_resources, &settings)
+    } else {
+        setup_map_arenas(&mut commands, &layer_resources, &settings, map_dirs)
+    };
+
+    if arena_count == 0 {
+        tracing::error!("No arena could be loaded");
+        std::process::exit(1);
+    }
+
+    let shop_config = {
+        bedwars_config::load_trader_config(&settings.config_dir).unwrap_or_else(|e| {
+            tracing::error!("Failed to load trader config: {}", e);
+            std::process::exit(1);
+        })
+    };
+
+    let errors = shop_config.validate();
+    if !errors.is_empty() {
+        for error in &errors {
+            tracing::error!("Invalid trader config: {}", error);
+        }
+        std::process::exit(1);
+    }
+
+    commands.insert_resource(shop_config);
+
+    let game_rules = bedwars_config::load_game_rules(&settings.config_dir).unwrap_or_else(|e| {
+        tracing::error!("Failed to load game rules: {}", e);
+        std::process::exit(1);
+    });
+
+    let errors = game_rules.validate();
+    if !errors.is_empty() {
+        for error in &errors {
+            tracing::error!("Invalid game rules: {}", error);
+        }
+        std::process::exit(1);
+    }
+
+    commands.insert_resource(game_rules);
+
+    let structures = bedwars_config::load_structures(&settings.config_dir).unwrap_or_else(|e| {
+        tracing::error!("Failed to load structures: {}", e);
+        std::process::exit(1);
+    });
+
+    commands.insert_resource(structures);
+}
+
+/// Spawn one arena for every directory in the arenas directory (or the config directory),
+/// every arena always plays the same map. Returns the number of arenas.
+fn setup_arenas(
+    commands: &mut Commands,
+    layer_resources: &LayerResources,
+    settings: &BedwarsSettings,
+) -> usize {
+    let arena_dirs = bedwars_config::arena_dirs(&settings.config_dir).unwrap_or_else(|e| {
+        tracing::error!("Failed to find arenas: {}", e);
+        std::process::exit(1);
+    });
+
+    let single_arena = arena_dirs.len() == 1 && arena_dirs[0] == settings.config_dir;
+
+    if settings.world_dir.is_some() && !single_arena {
+        tracing::warn!("The world path is ignored, because multiple arenas are configured");
+    }
+
+    let mut arena_count = 0;
+
+    for arena_dir in arena_dirs {
+        let map = if single_arena {
+            MapInfo {
+                name: "default".to_string(),
+                world_dir: settings
+                    .world_dir
+                    .clone()
+                    .unwrap_or_else(|| arena_dir.join(bedwars_config::WORLD_DIR_NAME)),
+                config_path: arena_dir.join(bedwars_config::WORLD_CONFIG_NAME),
+                // The shop of the config directory is the global shop
+                shop_path: None,
+            }
+        } else {
+            MapInfo::from_dir(&arena_dir)
+        };
+
+        let arena_name = map.name.clone();
+
+        let loaded = match maps::load_map(&map) {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                tracing::error!("Failed to load arena {}, skipping it: {}", arena_name, e);
+                continue;
+            }
+        };
+
+        for error in &loaded.errors {
+            tracing::error!("Invalid bedwars config of arena {}: {}", arena_name, error);
+        }
+
+        let state = if loaded.config.is_none() {
+            tracing::warn!(
+                "No bedwars config found for arena {}, enabling edit mode",
+                arena_name
+            );
+            GameState::Edit
+        } else if !loaded.errors.is_empty() {
+            tracing::warn!(
+                "The bedwars config of arena {} is invalid, enabling edit mode",
+                arena_name
+            );
+            GameState::Edit
+        } else if settings.force_edit {
+            GameState::Edit
+        } else {
+            GameState::Lobby
+        };
+
+        tracing::info!("Loaded arena {}", arena_name);
+        arena_count += 1;
+
+        spawn_arena(
+            commands,
+            layer_resources,
+            arena_name,
+            MapRotation::single(map),
+            state,
+            loaded,
+        );
+    }
+
+    arena_count
+}
+
+/// Spawn the arenas that rotate through the maps directory.
+/// Maps that can not be played yet get their own arena in edit mode.
+/// Returns the number of arenas.
+fn setup_map_arenas(
+    commands: &mut Commands,
+    layer_resources: &LayerResources,
+    settings: &BedwarsSettings,
+    map_dirs: Vec<PathBuf>,
+) -> usize {
+    if settings.world_dir.is_some() {
+        tracing::warn!("The world path is ignored, because the maps directory is used");
+    }
+
+    let mut arena_count = 0;
+    let mut playable_maps = vec![];
+
+    for map_dir in map_dirs {
+        let map = MapInfo::from_dir(&map_dir);
+
+        let loaded = match maps::load_map(&map) {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                tracing::error!("Failed to load map {}, skipping it: {}", map.name, e);
+                continue;
+            }
+        };
+
+        for error in &loaded.errors {
+            tracing::error!("Invalid bedwars config of map {}: {}", map.name, error);
+        }
+
+        if loaded.is_playable() && !settings.force_edit {
+            tracing::info!("Loaded map {}", map.name);
+            playable_maps.push((map, loaded));
+            continue;
+        }
+
+        tracing::warn!("Map {} can not be played yet, enabling edit mode", map.name);
+        arena_count += 1;
+
+        spawn_arena(
+            commands,
+            layer_resources,
+            map.name.clone(),
+            MapRotation::single(map),
+            GameState::Edit,
+            loaded,
+        );
+    }
+
+    if playable_maps.is_empty() {
+        return arena_count;
+    }
+
+    let maps: Vec<_> = playable_maps.iter().map(|(map, _)| map.clone()).collect();
+
+    for idx in 0..settings.map_arenas {
+        // Start the arenas on different maps
+        let rotation = MapRotation::new(maps.clone(), idx);
+        let loaded = playable_maps[rotation.current].1.clone();
+        let arena_name = format!("arena{}", idx + 1);
+
+        tracing::info!(
+            "Loaded arena {} with map {}",
+            arena_name,
+            rotation.current().name
+        );
+        arena_count += 1;
+
+        spawn_arena(
+            commands,
+            layer_resources,
+            arena_name,
+            rotation,
+            GameState::Lobby,
+            loaded,
+        );
+    }
+
+    arena_count
+}
+
+#[allow(clippy::type_complexity)]
+fn init_clients(
+    mut commands: Commands,
+    mut clients: Query<
+        (
+            Entity,
+            &mut Client,
+            &mut EntityLayerId,
+            &mut VisibleChunkLayer,
+            &mut VisibleEntityLayers,
+            &mut Position,
+            &mut DisplayName,
+            &Username,
+            &mut CommandScopes,
+        ),
+        Added<Client>,
+    >,
+    arenas: Query<(Entity, &GameState, &LobbyPlayerState), With<Arena>>,
+    mut matches: Query<(Entity, &GameState, &WorldConfig, &mut DisconnectedPlayers)>,
+    settings: Res<BedwarsSettings>,
+) {
+    for (
+        entity,
+        mut client,
+        mut layer_id,
+        mut visible_chunk_layer,
+        mut visible_entity_layers,
+        mut position,
+        mut display_name,
+        username,
+        mut scopes,
+    ) in &mut clients
+    {
+        if settings.admins.contains(&username.0) {
+            scopes.add("bedwars.command.bw");
+        }
+
+        // Players that disconnected during a match are put back into their team
+        let rejoined = matches
+            .iter_mut()
+            .filter(|(_, state, ..)| **state == GameState::Match)
+            .find_map(|(arena, _, bedwars_config, mut disconnected)| {
+                let player = disconnected.0.remove(&username.0)?;
+                Some((arena, bedwars_config, player))
+            });
+
+        if let Some((arena, bedwars_config, player)) = rejoined {
+            tracing::info!("Player {} rejoined the match", username);
+
+            layer_id.0 = arena;
+            visible_chunk_layer.0 = arena;
+            visible_entity_layers.0.insert(arena);
+
+            position.set(Into::<DVec3>::into(bedwars_config.spectator_spawn.clone()));
+            display_name.0 = Some(format!("{}{}", player.team.color.text_color(), username).into());
+            client.send_chat_message("§aYou rejoined the match");
+
+            // The player lost their items, so they respawn like after a death
+            commands
+                .entity(entity)
+                .insert(player.team)
+                .insert(match_player_bundle())
+                .insert(IsDead);
+            continue;
+        }
+
+        let Some((arena, state)) = pick_arena(&arenas) else {
+            tracing::error!("There is no arena to put the player in");
+            continue;
+        };
+
+        layer_id.0 = arena;
+        visible_chunk_layer.0 = arena;
+        visible_entity_layers.0.insert(arena);
+
+        match state {
+            GameState::Lobby => {
+                commands.entity(entity).insert(LobbyPlayer);
+            }
+            GameState::Match | GameState::PostMatch => {
+                commands.entity(entity).insert(Spectator);
+            }
+            GameState::Edit => {
+                commands.entity(entity).insert(Editor);
+            } // _ => {}
+        };
+    }
+}
+
+fn on_disconnect(
+    trigger: Trigger<OnRemove, Client>,
+    query: Query<(&Username, &EntityLayerId, Option<&Team>)>,
+    // commands: Commands,
+    mut arenas: Query<(
+        &GameState,
+        &mut LobbyPlayerState,
+        Option<&mut DisconnectedPlayers>,
+    )>,
+    rules: Res<GameRules>,
+) {
+    let Ok((username, layer_id, team)) = query.get(trigger.entity()) else {
+        return;
+    };
+
+    tracing::info!("Player {} disconnected", username);
+
+    let Ok((game_state, mut lobby_state, disconnected)) = arenas.get_mut(layer_id.0) else {
+        return;
+    };
+
+    match game_state {
+        GameState::Lobby => {
+            if lobby_state.players.remove(&username.0).is_none() {
+                lobby_state.without_team = lobby_state.without_team.saturating_sub(1);
+            }
+            lobby_state.map_votes.remove(&username.0);
+        }
+        GameState::Match => {
+            // Keep the team of the player, they are eliminated if they do not rejoin in time
+            if let (Some(team), Some(mut disconnected)) = (team, disconnected) {
+                disconnected.0.insert(
+                    username.0.clone(),
+                    DisconnectedPlayer::new(team.clone(), rules.reconnect_grace_secs),
+                );
+            }
+        }
+        _ => {}
+    }
+}
+
+fn update_last_tick_time(time: Res<Time>, mut last_tick_time: ResMut<LastTickTime>) {
+    last_tick_time.0 = time.delta();
+}
@@ -0,0 +1,177 @@
+use std::collections::HashSet;
+
+use crate::bedwars_config::{GameRules, WorldConfig};
+
+use super::LobbyPlayerState;
+
+impl LobbyPlayerState {
+    /// Number of players that selected the team
+    pub fn team_player_count(&self, team: &str) -> usize {
+        self.players
+            .values()
+            .filter(|player_team| *player_team == team)
+            .count()
+    }
+
+    pub fn is_team_full(&self, team: &str, rules: &GameRules) -> bool {
+        self.team_player_count(team) >= rules.team_mode.team_size()
+    }
+
+    /// Number of teams that will have players once the players without a team are assigned
+    pub fn teams_after_assignment(&self, bedwars_config: &WorldConfig) -> usize {
+        let selected_teams = self.players.values().collect::<HashSet<_>>().len();
+        let empty_teams = bedwars_config.teams.len().saturating_sub(selected_teams);
+
+        selected_teams + empty_teams.min(self.without_team as usize)
+    }
+
+    /// Put the players without a team into the teams with the fewest players.
+    /// Every group (a party, or a single player) is kept in one team,
+    /// and only split up if there is no team with enough space left.
+    /// Returns the team of every player that was assigned,
+    /// players are left out if every team is full.
+    pub fn assign_teams(
+        &mut self,
+        groups: &[Vec<String>],
+        bedwars_config: &WorldConfig,
+        rules: &GameRules,
+    ) -> Vec<(String, String)> {
+        let team_size = rules.team_mode.team_size();
+        let mut assigned = vec![];
+
+        for group in groups {
+            let mut without_team = group
+                .iter()
+                .filter(|player| !self.players.contains_key(*player))
+                .cloned()
+                .collect::<Vec<_>>();
+
+            while !without_team.is_empty() {
+                // Join the group members that already have a team
+                let group_team = group
+                    .iter()
+                    .filter_map(|player| self.players.get(player))
+                    .find(|team| !self.is_team_full(team, rules))
+                    .cloned();
+
+                // The team with the fewest players has the most space for the group
+                let Some(team) = group_team.or_else(|| {
+                    bedwars_config
+                        .teams
+                        .keys()
+                        .filter(|team| !self.is_team_full(team, rules))
+                        .min_by_key(|team| self.team_player_count(team))
+                        .cloned()
+                }) else {
+                    break;
+                };
+
+                let space = team_size - self.team_player_count(&team);
+                for player in without_team.drain(..space.min(without_team.len())) {
+                    self.players.insert(player.clone(), team.clone());
+                    self.without_team = self.without_team.saturating_sub(1);
+                    assigned.push((player, team.clone()));
+                }
+            }
+        }
+
+        assigned
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use ordermap::OrderMap;
+
+    use super::*;
+    use crate::{
+        bedwars_config::{ConfigVec3, TeamMode, WORLD_CONFIG_VERSION},
+        colors::TeamColor,
+    };
+
+    pub(crate) fn config(teams: &[(&str, TeamColor)]) -> WorldConfig {
+        WorldConfig {
+            version: WORLD_CONFIG_VERSION,
+            bounds: (ConfigVec3::new(0, 0, 0), ConfigVec3::new(0, 0, 0)),
+            teams: teams
+                .iter()
+                .map(|(name, color)| (name.to_string(), *color))
+                .collect::<OrderMap<_, _>>(),
+            spawns: OrderMap::new(),
+            beds: OrderMap::new(),
+            shops: vec![],
+            resource_spawners: vec![],
+            lobby_spawn: ConfigVec3::new(0, 0, 0),
+            spectator_spawn: ConfigVec3::new(0, 0, 0),
+        }
+    }
+
+    #[test]
+    fn test_assign_teams_balances() {
+        let config = config(&[("red", TeamColor::Red), ("blue", TeamColor::Blue)]);
+        let rules = GameRules {
+            team_mode: TeamMode::Doubles,
+            ..Default::default()
+        };
+
+        let mut lobby_state = LobbyPlayerState::default();
+        lobby_state
+            .players
+            .insert("a".to_string(), "red".to_string());
+        lobby_state.without_team = 4;
+
+        let groups = ["b", "c", "d", "e"].map(|player| vec![player.to_string()]);
+        let assigned = lobby_state.assign_teams(&groups, &config, &rules);
+
+        assert_eq!(
+            assigned,
+            vec![
+                ("b".to_string(), "blue".to_string()),
+                ("c".to_string(), "red".to_string()),
+                ("d".to_string(), "blue".to_string()),
+            ]
+        );
+        // Every team is full
+        assert!(!lobby_state.players.contains_key("e"));
+        assert_eq!(lobby_state.without_team, 1);
+    }
+
+    #[test]
+    fn test_assign_teams_keeps_parties_together() {
+        let config = config(&[
+            ("red", TeamColor::Red),
+            ("blue", TeamColor::Blue),
+            ("green", TeamColor::Green),
+        ]);
+        let rules = GameRules {
+            team_mode: TeamMode::Doubles,
+            ..Default::default()
+        };
+
+        let mut lobby_state = LobbyPlayerState::default();
+        lobby_state
+            .players
+            .insert("a".to_string(), "red".to_string());
+        lobby_state.without_team = 5;
+
+        let groups = [
+            // "b" joins the team of "a"
+            vec!["a".to_string(), "b".to_string()],
+            // The party is too large for one team and is split up
+            vec!["c".to_string(), "d".to_string(), "e".to_string()],
+            vec!["f".to_string()],
+        ];
+        let assigned = lobby_state.assign_teams(&groups, &config, &rules);
+
+        assert_eq!(
+            assigned,
+            vec![
+                ("b".to_string(), "red".to_string()),
+                ("c".to_string(), "blue".to_string()),
+                ("d".to_string(), "blue".to_string()),
+                ("e".to_string(), "green".to_string()),
+                ("f".to_string(), "green".to_string()),
+            ]
+        );
+    }
+}
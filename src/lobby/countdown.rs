@@ -0,0 +1,285 @@
+use std::collections::HashMap;
+
+use bevy_ecs::{
+    entity::Entity,
+    event::EventWriter,
+    query::With,
+    system::{Commands, Query, Res},
+};
+use bevy_time::Time;
+use valence::{
+    client::{Client, Username},
+    entity::{EntityLayerId, Position},
+    message::SendMessage,
+    player_list::DisplayName,
+    prelude::Component,
+    protocol::{
+        packets::play::ExperienceBarUpdateS2c, sound::SoundCategory, Sound, VarInt, WritePacket,
+    },
+    title::SetTitle,
+};
+
+use crate::{
+    bedwars_config::{GameRules, WorldConfig},
+    party::PartyMember,
+    GameState, LobbyPlayer, Spectator, Team,
+};
+
+use super::{map_vote::LobbyFinishedEvent, LobbyPlayerState};
+
+/// Length of the countdown after `/bw forcestart`
+pub const FORCE_START_SECS: f32 = 5.0;
+/// Seconds at which the remaining time is also shown in the chat
+const ANNOUNCED_SECS: [u32; 3] = [30, 20, 10];
+/// The remaining time is shown as a title for the last seconds
+const TITLE_SECS: u32 = 5;
+
+/// Countdown until the match of an arena starts, stored on the arena entity
+#[derive(Debug, Clone, Component)]
+pub struct LobbyCountdown {
+    /// Time left in seconds
+    pub remaining: f32,
+    /// The countdown is paused while there are not enough players
+    pub paused: bool,
+    /// Started by `/bw forcestart`, the countdown does not wait for more players
+    pub forced: bool,
+}
+
+impl LobbyCountdown {
+    pub fn new(secs: f32) -> Self {
+        Self {
+            remaining: secs,
+            paused: false,
+            forced: false,
+        }
+    }
+
+    pub fn forced() -> Self {
+        Self {
+            forced: true,
+            ..Self::new(FORCE_START_SECS)
+        }
+    }
+}
+
+impl LobbyPlayerState {
+    /// Whether there are enough players and teams to start a match,
+    /// players without a team will be assigned to a team when the countdown ends
+    pub fn is_ready(&self, bedwars_config: &WorldConfig, rules: &GameRules) -> bool {
+        self.player_count() >= rules.min_players
+            && self.teams_after_assignment(bedwars_config) >= rules.min_teams
+    }
+
+    /// Whether every team of the map is full
+    pub fn is_full(&self, bedwars_config: &WorldConfig, rules: &GameRules) -> bool {
+        self.player_count() >= bedwars_config.teams.len() * rules.team_mode.team_size()
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub(super) fn tick_lobby_countdown(
+    mut commands: Commands,
+    mut arenas: Query<(
+        Entity,
+        &GameState,
+        &WorldConfig,
+        &mut LobbyPlayerState,
+        Option<&mut LobbyCountdown>,
+    )>,
+    mut players: Query<
+        (
+            Entity,
+            &EntityLayerId,
+            &mut Client,
+            &Position,
+            &Username,
+            &mut DisplayName,
+            Option<&Team>,
+            Option<&PartyMember>,
+        ),
+        With<LobbyPlayer>,
+    >,
+    mut lobby_finished_writer: EventWriter<LobbyFinishedEvent>,
+    rules: Res<GameRules>,
+    time: Res<Time>,
+) {
+    for (arena, state, bedwars_config, mut lobby_state, countdown) in &mut arenas {
+        if *state != GameState::Lobby {
+            continue;
+        }
+
+        let mut arena_players = players
+            .iter_mut()
+            .filter(|(_, layer_id, ..)| layer_id.0 == arena)
+            .collect::<Vec<_>>();
+
+        let mut broadcast = |message: &str| {
+            for (_, _, client, ..) in arena_players.iter_mut() {
+                client.send_chat_message(message);
+            }
+        };
+
+        let Some(mut countdown) = countdown else {
+            if lobby_state.is_ready(bedwars_config, &rules) {
+                commands
+                    .entity(arena)
+                    .insert(LobbyCountdown::new(rules.countdown_secs as f32));
+                broadcast(&format!(
+                    "§eThe match starts in §6{}§e seconds",
+                    rules.countdown_secs
+                ));
+            }
+            continue;
+        };
+
+        if !countdown.forced && !lobby_state.is_ready(bedwars_config, &rules) {
+            if !countdown.paused {
+                countdown.paused = true;
+                broadcast("§cNot enough players, the countdown was paused");
+
+                for (_, _, client, ..) in arena_players.iter_mut() {
+                    set_experience_bar(client, 0.0, 0);
+                }
+            }
+            continue;
+        }
+
+        if countdown.paused {
+            countdown.paused = false;
+            broadcast("§aThe countdown continues");
+        }
+
+        let full_lobby_secs = rules.full_lobby_countdown_secs as f32;
+        if countdown.remaining > full_lobby_secs && lobby_state.is_full(bedwars_config, &rules) {
+            countdown.remaining = full_lobby_secs;
+            broadcast(&format!(
+                "§aThe lobby is full, the match starts in §6{}§a seconds",
+                rules.full_lobby_countdown_secs
+            ));
+        }
+
+        let secs_before = countdown.remaining.ceil() as u32;
+        countdown.remaining -= time.delta_seconds();
+        let secs = countdown.remaining.max(0.0).ceil() as u32;
+
+        if secs != secs_before && secs > 0 {
+            let progress =
+                (countdown.remaining / rules.countdown_secs.max(1) as f32).clamp(0.0, 1.0);
+
+            for (_, _, client, position, ..) in arena_players.iter_mut() {
+                set_experience_bar(client, progress, secs);
+
+                if secs <= TITLE_SECS {
+                    client.set_title_times(0, 25, 0);
+                    client.set_title(format!("§e{}", secs));
+                    client.play_sound(
+                        Sound::BlockNoteBlockHat,
+                        SoundCategory::Master,
+                        position.0,
+                        1.0,
+                        1.0,
+                    );
+                }
+
+                if secs <= TITLE_SECS || ANNOUNCED_SECS.contains(&secs) {
+                    client.send_chat_message(format!(
+                        "§eThe match starts in §6{}§e {}",
+                        secs,
+                        if secs == 1 { "second" } else { "seconds" }
+                    ));
+                }
+            }
+        }
+
+        if countdown.remaining > 0.0 {
+            continue;
+        }
+
+        commands.entity(arena).remove::<LobbyCountdown>();
+
+        // Fill up the smallest teams with the players that did not select a team,
+        // parties are placed first so they can be kept together
+        let mut parties: HashMap<Entity, Vec<String>> = HashMap::new();
+        let mut without_party = vec![];
+        for (_, _, _, _, username, _, team, party_member) in arena_players.iter() {
+            match party_member {
+                Some(party_member) => parties
+                    .entry(party_member.0)
+                    .or_default()
+                    .push(username.0.clone()),
+                None if team.is_none() => without_party.push(vec![username.0.clone()]),
+                None => {}
+            }
+        }
+
+        let mut groups = parties.into_values().collect::<Vec<_>>();
+        groups.sort_by_key(|group| std::cmp::Reverse(group.len()));
+        groups.extend(without_party);
+
+        let without_team = arena_players
+            .iter()
+            .filter(|(_, _, _, _, _, _, team, _)| team.is_none())
+            .count();
+        let assigned = lobby_state.assign_teams(&groups, bedwars_config, &rules);
+
+        if lobby_state.players.is_empty() {
+            // Everyone left after a forced start
+            continue;
+        }
+
+        if assigned.len() < without_team {
+            tracing::warn!(
+                "{} players did not fit into a team and will spectate",
+                without_team - assigned.len()
+            );
+        }
+
+        for (player, _, client, _, username, display_name, team, _) in arena_players.iter_mut() {
+            set_experience_bar(client, 0.0, 0);
+            client.clear_title();
+
+            let mut player = commands.entity(*player);
+            player.remove::<LobbyPlayer>();
+
+            if team.is_some() {
+                continue;
+            }
+
+            let assigned_team = assigned
+                .iter()
+                .find(|(name, _)| *name == username.0)
+                .and_then(|(_, team)| Some((team, bedwars_config.teams.get(team)?)));
+
+            match assigned_team {
+                Some((team, color)) => {
+                    display_name.0 = Some(format!("{}{}", color.text_color(), username).into());
+                    client.send_chat_message(format!(
+                        "§7You were put into the {}{}§7 team",
+                        color.text_color(),
+                        team
+                    ));
+
+                    player.insert(Team {
+                        name: team.clone(),
+                        color: *color,
+                    });
+                }
+                None => {
+                    client.send_chat_message("§cEvery team is full, you will spectate the match");
+                    player.insert(Spectator);
+                }
+            }
+        }
+
+        tracing::info!("Lobby countdown finished, starting match");
+        lobby_finished_writer.send(LobbyFinishedEvent { arena });
+    }
+}
+
+fn set_experience_bar(client: &mut Client, bar: f32, level: u32) {
+    client.write_packet(&ExperienceBarUpdateS2c {
+        bar,
+        level: VarInt(level as i32),
+        total_xp: VarInt(0),
+    });
+}
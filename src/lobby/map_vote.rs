@@ -0,0 +1,389 @@
+use std::collections::HashMap;
+
+use bevy_ecs::{
+    entity::Entity,
+    event::{Event, EventReader},
+    query::{With, Without},
+    system::{Commands, Query},
+    world::OnRemove,
+};
+use rand::seq::SliceRandom;
+use valence::{
+    client::{Client, Username},
+    entity::{EntityLayerId, Position},
+    message::SendMessage,
+    nbt::{compound, List},
+    prelude::{Component, Inventory, InventoryKind, Trigger},
+    protocol::{sound::SoundCategory, Sound},
+    Despawned, ItemKind, ItemStack,
+};
+
+use crate::{
+    arena::{move_to_arena, spawn_arena, Arena, LayerResources},
+    bedwars_config::WorldConfig,
+    maps::{self, MapRotation},
+    menu::{ItemMenu, MenuItemSelectEvent},
+    GameState, LobbyPlayer, Spectator, Team,
+};
+
+use super::LobbyPlayerState;
+
+pub const MAP_VOTE_ITEM: ItemKind = ItemKind::Map;
+/// The vote item is placed next to the team selector
+pub const MAP_VOTE_SLOT: u16 = 37;
+
+/// Marks players that have the map vote menu open
+#[derive(Debug, Component)]
+pub struct MapVoteMenu;
+
+/// Sent when every player in the lobby of an arena is ready.
+/// The voted map will be loaded and the match is started on it.
+#[derive(Debug, Clone, Event)]
+pub struct LobbyFinishedEvent {
+    pub arena: Entity,
+}
+
+impl LobbyPlayerState {
+    /// Number of votes of every map in the rotation
+    pub fn vote_counts(&self, map_count: usize) -> Vec<usize> {
+        let mut counts = vec![0; map_count];
+        for map in self.map_votes.values() {
+            if let Some(count) = counts.get_mut(*map) {
+                *count += 1;
+            }
+        }
+
+        counts
+    }
+
+    /// The maps with the most votes, empty if nobody voted
+    pub fn leading_maps(&self, map_count: usize) -> Vec<usize> {
+        let counts = self.vote_counts(map_count);
+        let max = counts.iter().copied().max().unwrap_or(0);
+        if max == 0 {
+            return vec![];
+        }
+
+        (0..map_count).filter(|idx| counts[*idx] == max).collect()
+    }
+
+    /// The map that won the vote, ties are broken randomly
+    pub fn winning_map(&self, map_count: usize) -> Option<usize> {
+        self.leading_maps(map_count)
+            .choose(&mut rand::thread_rng())
+            .copied()
+    }
+}
+
+pub fn open_map_vote_menu(
+    commands: &mut Commands,
+    player_ent: Entity,
+    rotation: &MapRotation,
+    lobby_state: &LobbyPlayerState,
+) {
+    let rows = rotation.maps.len().div_ceil(9).clamp(1, 6);
+    let kind = match rows {
+        1 => InventoryKind::Generic9x1,
+        2 => InventoryKind::Generic9x2,
+        3 => InventoryKind::Generic9x3,
+        4 => InventoryKind::Generic9x4,
+        5 => InventoryKind::Generic9x5,
+        _ => InventoryKind::Generic9x6,
+    };
+
+    let mut inv = Inventory::new(kind);
+    fill_map_vote_menu(&mut inv, rotation, lobby_state);
+
+    commands
+        .entity(player_ent)
+        .insert(ItemMenu::new(inv))
+        .insert(MapVoteMenu);
+}
+
+/// Show every map of the rotation, the stack size is the number of votes
+fn fill_map_vote_menu(
+    menu_inventory: &mut Inventory,
+    rotation: &MapRotation,
+    lobby_state: &LobbyPlayerState,
+) {
+    menu_inventory.clear();
+
+    let counts = lobby_state.vote_counts(rotation.maps.len());
+    for (idx, (map, votes)) in rotation.maps.iter().zip(counts).enumerate() {
+        if idx as u16 >= menu_inventory.slot_count() {
+            break;
+        }
+
+        let item_stack = ItemStack::new(
+            ItemKind::FilledMap,
+            votes.clamp(1, 64) as i8,
+            Some(compound! {
+                "display" => compound! {
+                    "Name" => format!("{{\"text\":\"§a{}\",\"italic\":false}}", map.name),
+                    "Lore" => List::String(vec![format!(
+                        "{{\"text\":\"§7{} {}\",\"italic\":false}}",
+                        votes,
+                        if votes == 1 { "vote" } else { "votes" }
+                    )]),
+                }
+            }),
+        );
+        menu_inventory.set_slot(idx as u16, item_stack);
+    }
+}
+
+pub(super) fn close_map_vote_menu(trigger: Trigger<OnRemove, ItemMenu>, mut commands: Commands) {
+    commands.entity(trigger.entity()).remove::<MapVoteMenu>();
+}
+
+#[allow(clippy::type_complexity)]
+pub(super) fn on_map_vote(
+    mut clients: Query<
+        (&EntityLayerId, &Position, &mut Client, &Username),
+        (With<LobbyPlayer>, With<MapVoteMenu>),
+    >,
+    menus: Query<(&EntityLayerId, &ItemMenu), With<MapVoteMenu>>,
+    mut inventories: Query<&mut Inventory, Without<Client>>,
+    mut events: EventReader<MenuItemSelectEvent>,
+    mut arenas: Query<(&GameState, &MapRotation, &mut LobbyPlayerState)>,
+) {
+    for event in events.read() {
+        let Ok((layer_id, position, mut client, username)) = clients.get_mut(event.client) else {
+            continue;
+        };
+        let arena = layer_id.0;
+
+        let Ok((state, rotation, mut lobby_state)) = arenas.get_mut(arena) else {
+            continue;
+        };
+
+        if *state != GameState::Lobby {
+            continue;
+        }
+
+        let Some(map) = rotation.maps.get(event.idx as usize) else {
+            continue;
+        };
+
+        lobby_state
+            .map_votes
+            .insert(username.0.clone(), event.idx as usize);
+
+        client.play_sound(
+            Sound::BlockNoteBlockBell,
+            SoundCategory::Master,
+            position.0,
+            1.0,
+            1.0,
+        );
+        client.send_chat_message(format!("§aYou voted for §6{}", map.name));
+
+        // Update the vote counts of everyone looking at the menu
+        for (menu_layer_id, item_menu) in &menus {
+            if menu_layer_id.0 != arena {
+                continue;
+            }
+
+            let Some(inventory_ent) = item_menu.inventory_ent() else {
+                continue;
+            };
+
+            if let Ok(mut menu_inventory) = inventories.get_mut(inventory_ent) {
+                fill_map_vote_menu(&mut menu_inventory, rotation, &lobby_state);
+            }
+        }
+    }
+}
+
+/// Load the map that won the vote and start the match on it
+pub(super) fn load_voted_map(
+    mut commands: Commands,
+    mut events: EventReader<LobbyFinishedEvent>,
+    arenas: Query<(&Arena, &MapRotation, &LobbyPlayerState, &WorldConfig)>,
+    mut players: Query<(Entity, &EntityLayerId, &mut Client, Option<&Team>)>,
+    layer_resources: LayerResources,
+) {
+    for event in events.read() {
+        let Ok((arena_info, rotation, lobby_state, old_config)) = arenas.get(event.arena) else {
+            continue;
+        };
+
+        let voted_map = lobby_state
+            .winning_map(rotation.maps.len())
+            .filter(|map| *map != rotation.current)
+            .and_then(|map| {
+                let mut rotation = rotation.clone();
+                rotation.current = map;
+
+                match maps::load_map(rotation.current()) {
+                    Ok(loaded) if loaded.is_playable() => Some((rotation, loaded)),
+                    Ok(_) => {
+                        tracing::error!(
+                            "The voted map {} is invalid, staying on the current map",
+                            rotation.current().name
+                        );
+                        None
+                    }
+                    Err(e) => {
+                        tracing::error!(
+                            "Failed to load the voted map {}: {}",
+                            rotation.current().name,
+                            e
+                        );
+                        None
+                    }
+                }
+            });
+
+        let Some((rotation, loaded)) = voted_map else {
+            commands
+                .entity(event.arena)
+                .insert((GameState::Match, LobbyPlayerState::default()));
+            continue;
+        };
+
+        tracing::info!(
+            "Arena {} switches to the voted map {}",
+            arena_info.name,
+            rotation.current().name
+        );
+
+        let Some(new_config) = loaded.config.clone() else {
+            continue;
+        };
+
+        let new_arena = spawn_arena(
+            &mut commands,
+            &layer_resources,
+            arena_info.name.clone(),
+            rotation,
+            GameState::Match,
+            loaded,
+        );
+
+        let team_mapping = remap_teams(old_config, &new_config);
+
+        for (player, layer_id, mut client, team) in &mut players {
+            if layer_id.0 != event.arena {
+                continue;
+            }
+
+            move_to_arena(&mut commands, player, new_arena);
+
+            let Some(team) = team else {
+                continue;
+            };
+
+            match team_mapping.get(&team.name) {
+                Some(new_team) => {
+                    commands.entity(player).insert(new_team.clone());
+                }
+                None => {
+                    client.send_chat_message(
+                        "§cThe map has no team for you, you will spectate the match",
+                    );
+                    commands.entity(player).remove::<Team>().insert(Spectator);
+                }
+            }
+        }
+
+        commands.entity(event.arena).insert(Despawned);
+    }
+}
+
+/// Map every team of the old map to a different team of the new map.
+/// Teams with the same name are kept, the other teams get the unused teams of the new map in order.
+/// Teams that are left over have no team on the new map.
+fn remap_teams(old_config: &WorldConfig, new_config: &WorldConfig) -> HashMap<String, Team> {
+    let mut mapping = HashMap::new();
+
+    for (name, color) in &new_config.teams {
+        if old_config.teams.contains_key(name) {
+            mapping.insert(
+                name.clone(),
+                Team {
+                    name: name.clone(),
+                    color: *color,
+                },
+            );
+        }
+    }
+
+    let mut unused_teams = new_config
+        .teams
+        .iter()
+        .filter(|(name, _)| !old_config.teams.contains_key(*name));
+
+    for old_team in old_config.teams.keys() {
+        if mapping.contains_key(old_team) {
+            continue;
+        }
+
+        let Some((name, color)) = unused_teams.next() else {
+            break;
+        };
+
+        mapping.insert(
+            old_team.clone(),
+            Team {
+                name: name.clone(),
+                color: *color,
+            },
+        );
+    }
+
+    mapping
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::{colors::TeamColor, lobby::balancing::tests::config};
+
+    #[test]
+    fn test_leading_maps() {
+        let mut lobby_state = LobbyPlayerState::default();
+        assert_eq!(lobby_state.winning_map(3), None);
+
+        lobby_state.map_votes.insert("a".to_string(), 1);
+        lobby_state.map_votes.insert("b".to_string(), 2);
+        lobby_state.map_votes.insert("c".to_string(), 2);
+
+        assert_eq!(lobby_state.vote_counts(3), vec![0, 1, 2]);
+        assert_eq!(lobby_state.leading_maps(3), vec![2]);
+
+        lobby_state.map_votes.insert("d".to_string(), 1);
+        assert_eq!(lobby_state.leading_maps(3), vec![1, 2]);
+        assert!(matches!(lobby_state.winning_map(3), Some(1 | 2)));
+    }
+
+    fn new_team_name<'a>(mapping: &'a HashMap<String, Team>, team: &str) -> Option<&'a str> {
+        mapping.get(team).map(|team| team.name.as_str())
+    }
+
+    #[test]
+    fn test_remap_teams_one_to_one() {
+        let old = config(&[("red", TeamColor::Red), ("blue", TeamColor::Blue)]);
+        let new = config(&[("blue", TeamColor::Blue), ("green", TeamColor::Green)]);
+
+        let mapping = remap_teams(&old, &new);
+        assert_eq!(new_team_name(&mapping, "red"), Some("green"));
+        assert_eq!(new_team_name(&mapping, "blue"), Some("blue"));
+    }
+
+    #[test]
+    fn test_remap_teams_fewer_teams() {
+        let old = config(&[
+            ("red", TeamColor::Red),
+            ("blue", TeamColor::Blue),
+            ("green", TeamColor::Green),
+        ]);
+        let new = config(&[("yellow", TeamColor::Yellow), ("green", TeamColor::Green)]);
+
+        let mapping = remap_teams(&old, &new);
+        assert_eq!(new_team_name(&mapping, "red"), Some("yellow"));
+        assert_eq!(new_team_name(&mapping, "green"), Some("green"));
+        // There is no team left for blue
+        assert_eq!(new_team_name(&mapping, "blue"), None);
+    }
+}
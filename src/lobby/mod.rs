@@ -0,0 +1,367 @@
+use std::collections::HashMap;
+
+use bevy_ecs::{
+    entity::Entity,
+    event::EventReader,
+    query::{Added, With, Without},
+    system::{Commands, Query, Res},
+};
+use bevy_time::{Time, Timer, TimerMode};
+use map_vote::{LobbyFinishedEvent, MapVoteMenu, MAP_VOTE_ITEM, MAP_VOTE_SLOT};
+use valence::{
+    app::{App, Plugin, Update},
+    client::{Client, Username},
+    entity::{living::Health, EntityLayerId, Position},
+    interact_item::InteractItemEvent,
+    inventory::HeldItem,
+    message::SendMessage,
+    nbt::{compound, Compound, List},
+    player_list::DisplayName,
+    prelude::{Component, IntoSystemConfigs, Inventory, InventoryKind},
+    protocol::{sound::SoundCategory, Sound},
+    title::SetTitle,
+    GameMode, ItemKind, ItemStack,
+};
+
+pub mod balancing;
+pub mod countdown;
+pub mod map_vote;
+
+use crate::{
+    arena::any_arena_in,
+    bedwars_config::{GameRules, WorldConfig},
+    maps::MapRotation,
+    menu::{ItemMenu, MenuItemSelectEvent},
+    utils::inventory::InventoryExt,
+    GameState, LobbyPlayer, Team,
+};
+
+const TEAM_SELECTOR_ITEM: ItemKind = ItemKind::Compass;
+const _TEAM_SELECTOR_ITEM_NAME: &str = "Team Selector";
+const NO_TEAM_SELECTED_ITEM: ItemKind = ItemKind::Barrier;
+const FULL_TEAM_ITEM: ItemKind = ItemKind::GrayStainedGlass;
+/// TODO: create a lobby plugin
+
+pub struct LobbyPlugin;
+
+/// Represents the players in the lobby of an arena
+#[derive(Debug, Clone, Component, Default)]
+pub struct LobbyPlayerState {
+    /// player name -> team name
+    pub players: HashMap<String, String>,
+    /// Number of players that did not select a team
+    /// If this is 0, the game can start
+    pub without_team: u16,
+    /// player name -> index of the voted map in the map rotation
+    pub map_votes: HashMap<String, usize>,
+}
+
+impl LobbyPlayerState {
+    /// Number of players in the lobby, with or without a team
+    pub fn player_count(&self) -> usize {
+        self.players.len() + self.without_team as usize
+    }
+}
+
+impl Plugin for LobbyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<LobbyFinishedEvent>()
+            .add_systems(
+                Update,
+                (
+                    (
+                        init_lobby_player,
+                        lobby_right_click,
+                        on_team_select,
+                        countdown::tick_lobby_countdown,
+                        map_vote::on_map_vote,
+                        map_vote::load_voted_map.after(countdown::tick_lobby_countdown),
+                    )
+                        .run_if(any_arena_in(GameState::Lobby)),
+                    set_action_bar,
+                ),
+            )
+            .observe(map_vote::close_map_vote_menu);
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn init_lobby_player(
+    mut commands: Commands,
+    mut clients: Query<
+        (
+            Entity,
+            &EntityLayerId,
+            &mut Position,
+            &mut GameMode,
+            &mut Inventory,
+            &mut Health,
+        ),
+        (Added<LobbyPlayer>,),
+    >,
+    mut arenas: Query<(&WorldConfig, &MapRotation, &mut LobbyPlayerState)>,
+) {
+    for (player, layer_id, mut position, mut game_mode, mut inventory, mut health) in &mut clients {
+        let Ok((bedwars_config, rotation, mut lobby_state)) = arenas.get_mut(layer_id.0) else {
+            continue;
+        };
+
+        tracing::info!("Initializing lobby player");
+        position.set(bedwars_config.lobby_spawn.clone());
+        *game_mode = GameMode::Survival;
+        *health = Health(20.0);
+
+        inventory.clear();
+        inventory.readonly = true;
+
+        let team_selector_nbt = Compound::new();
+
+        // TODO: custom item name
+
+        let team_selector = ItemStack::new(TEAM_SELECTOR_ITEM, 1, Some(team_selector_nbt));
+
+        inventory.set_slot(36, team_selector);
+
+        if rotation.maps.len() > 1 {
+            let map_vote = ItemStack::new(MAP_VOTE_ITEM, 1, None);
+            inventory.set_slot(MAP_VOTE_SLOT, map_vote);
+        }
+
+        let no_team_selected_item = ItemStack::new(NO_TEAM_SELECTED_ITEM, 1, None);
+        inventory.set_slot(40, no_team_selected_item);
+
+        // commands.entity(player).insert(CombatState::default());
+
+        commands.entity(player).insert(ActionBarTimer::default());
+
+        lobby_state.without_team += 1;
+    }
+}
+
+fn lobby_right_click(
+    mut commands: Commands,
+    clients: Query<(&EntityLayerId, &HeldItem, &Inventory), With<LobbyPlayer>>,
+    mut events: EventReader<InteractItemEvent>,
+    arenas: Query<(&WorldConfig, &MapRotation, &LobbyPlayerState)>,
+    rules: Res<GameRules>,
+) {
+    for event in events.read() {
+        let Ok((layer_id, held_item, inventory)) = clients.get(event.client) else {
+            continue;
+        };
+
+        let Ok((bedwars_config, rotation, lobby_state)) = arenas.get(layer_id.0) else {
+            continue;
+        };
+
+        let held_item_slot = held_item.slot();
+        let held_item = inventory.slot(held_item_slot).item;
+        if held_item == TEAM_SELECTOR_ITEM {
+            tracing::info!("Team selector right click");
+            open_team_selection_menu(
+                &mut commands,
+                event.client,
+                bedwars_config,
+                lobby_state,
+                &rules,
+            );
+        } else if held_item == MAP_VOTE_ITEM {
+            map_vote::open_map_vote_menu(&mut commands, event.client, rotation, lobby_state);
+        }
+    }
+}
+
+fn open_team_selection_menu(
+    commands: &mut Commands,
+    player_ent: Entity,
+    bedwars_config: &WorldConfig,
+    lobby_state: &LobbyPlayerState,
+    rules: &GameRules,
+) {
+    let mut inv = Inventory::new(InventoryKind::Generic9x1);
+    for (team_name, color) in &bedwars_config.teams {
+        let next_slot = inv.first_empty_slot().unwrap();
+
+        let player_count = lobby_state.team_player_count(team_name);
+        let team_size = rules.team_mode.team_size();
+
+        // Full teams are greyed out
+        let (team_block, name) = if player_count >= team_size {
+            (FULL_TEAM_ITEM, format!("§7{} (full)", team_name))
+        } else {
+            (
+                color.wool_block(),
+                format!("{}{}", color.text_color(), team_name),
+            )
+        };
+
+        let item_stack = ItemStack::new(
+            team_block,
+            1,
+            Some(compound! {
+                "display" => compound! {
+                    "Name" => format!("{{\"text\":\"{}\",\"italic\":false}}", name),
+                    "Lore" => List::String(vec![format!(
+                        "{{\"text\":\"§7{}/{} players\",\"italic\":false}}",
+                        player_count, team_size
+                    )]),
+                }
+            }),
+        );
+        inv.set_slot(next_slot, item_stack);
+    }
+
+    let menu = ItemMenu::new(inv);
+    commands
+        .entity(player_ent)
+        .insert(menu)
+        .remove::<MapVoteMenu>();
+}
+
+#[allow(clippy::type_complexity)]
+fn on_team_select(
+    mut commands: Commands,
+    mut clients: Query<
+        (
+            Entity,
+            &EntityLayerId,
+            &Position,
+            &mut Client,
+            &Username,
+            Option<&Team>,
+            &mut DisplayName,
+        ),
+        (With<LobbyPlayer>, Without<MapVoteMenu>),
+    >,
+    mut events: EventReader<MenuItemSelectEvent>,
+    mut arenas: Query<(&GameState, &WorldConfig, &mut LobbyPlayerState)>,
+    rules: Res<GameRules>,
+) {
+    for event in events.read() {
+        let Ok((
+            player_ent,
+            layer_id,
+            position,
+            mut client,
+            username,
+            switched_from,
+            mut display_name,
+        )) = clients.get_mut(event.client)
+        else {
+            continue;
+        };
+        let arena = layer_id.0;
+
+        let Ok((state, bedwars_config, mut lobby_state)) = arenas.get_mut(arena) else {
+            continue;
+        };
+
+        if *state != GameState::Lobby {
+            continue;
+        }
+
+        let selected_slot = event.idx;
+
+        if let Some((team, team_color)) = bedwars_config.teams.iter().nth(selected_slot as usize) {
+            let already_in_team = switched_from.is_some_and(|current| current.name == *team);
+            if !already_in_team && lobby_state.is_team_full(team, &rules) {
+                client.send_chat_message("§cThis team is full");
+                client.play_sound(
+                    Sound::BlockNoteBlockBass,
+                    SoundCategory::Master,
+                    position.0,
+                    1.0,
+                    0.8,
+                );
+                continue;
+            }
+
+            commands.entity(player_ent).insert(Team {
+                name: team.to_string(),
+                color: *team_color,
+            });
+
+            client.play_sound(
+                Sound::BlockNoteBlockBell,
+                SoundCategory::Master,
+                position.0,
+                1.0,
+                1.0,
+            );
+
+            display_name.0 = Some(format!("{}{}", team_color.text_color(), username).into());
+
+            commands.entity(player_ent).remove::<ItemMenu>();
+
+            client.set_action_bar(format!("{}{} team", team_color.text_color(), team));
+
+            lobby_state.players.insert(username.0.clone(), team.clone());
+            if switched_from.is_none() {
+                lobby_state.without_team = lobby_state.without_team.saturating_sub(1);
+            }
+        }
+    }
+}
+
+const ACTION_BAR_UPDATE_INTERVAL_SEC: f32 = 2.0;
+#[derive(Debug, Component)]
+struct ActionBarTimer(pub Timer);
+
+impl Default for ActionBarTimer {
+    fn default() -> Self {
+        Self(Timer::from_seconds(
+            ACTION_BAR_UPDATE_INTERVAL_SEC,
+            TimerMode::Repeating,
+        ))
+    }
+}
+
+/// This will be called every 2 seconds,
+/// to show the team of each player and the leading map of the vote
+#[allow(clippy::type_complexity)]
+fn set_action_bar(
+    mut players: Query<
+        (
+            &mut Client,
+            &EntityLayerId,
+            Option<&Team>,
+            &mut ActionBarTimer,
+        ),
+        With<LobbyPlayer>,
+    >,
+    arenas: Query<(&MapRotation, &LobbyPlayerState)>,
+    time: Res<Time>,
+) {
+    for (mut client, layer_id, team, mut timer) in players.iter_mut() {
+        if !timer.0.tick(time.delta()).just_finished() {
+            continue;
+        }
+
+        let mut parts = vec![];
+
+        if let Some(team) = team {
+            parts.push(format!("{}Team: {}", team.color.text_color(), team.name));
+        }
+
+        if let Ok((rotation, lobby_state)) = arenas.get(layer_id.0) {
+            if rotation.maps.len() > 1 {
+                let leading_maps = lobby_state.leading_maps(rotation.maps.len());
+                let map_names = if leading_maps.is_empty() {
+                    rotation.current().name.clone()
+                } else {
+                    leading_maps
+                        .iter()
+                        .map(|idx| rotation.maps[*idx].name.as_str())
+                        .collect::<Vec<_>>()
+                        .join(" / ")
+                };
+
+                parts.push(format!("§7Map: §6{}", map_names));
+            }
+        }
+
+        if !parts.is_empty() {
+            client.set_action_bar(parts.join(" §8| "));
+        }
+    }
+}
@@ -1,248 +1,196 @@
-use std::num::NonZero;
-
-use base::{
-    armor_right_click_equip::ArmorRightClickEquipPlugin,
-    bow::BowPlugin,
-    break_blocks::BlockBreakPlugin,
-    build::BuildPlugin,
-    chat::ChatPlugin,
-    chests::ChestPlugin,
-    combat::CombatPlugin,
-    death::{DeathPlugin, PlayerEliminatedEvent},
-    drop_items::ItemDropPlugin,
-    fall_damage::FallDamagePlugin,
-    item_pickup::ItemPickupPlugin,
-    physics::PhysicsPlugin,
-    regeneration::RegenerationPlugin,
-    scoreboard::ScoreboardPlugin,
-    utils::debug::DebugPlugin,
-    void_death::VoidDeathPlugin,
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    num::NonZero,
+    path::{Path, PathBuf},
+    sync::Arc,
 };
-use bevy_state::{app::StatesPlugin, prelude::*};
-use bevy_time::{Time, TimePlugin};
-use colors::TeamColor;
-use commands::bedwars_admin::{handle_bedwars_admin_command, BedwarsAdminCommand};
-use edit::EditPlugin;
-use items::ender_pearl::EnderPearlPlugin;
-use lobby::{LobbyPlayerState, LobbyPlugin};
-use menu::ItemMenuPlugin;
-use r#match::MatchPlugin;
-use resource_spawners::ResourceSpawnerPlugin;
-// use resource_spawners::ResourceSpawnerPlugin;
-use shop::ShopPlugin;
-use spectator::SpectatorPlugin;
-use utils::despawn_timer::DespawnTimerPlugin;
-use valence::{anvil::AnvilLevel, command::AddCommand, prelude::*, ServerSettings};
-
-pub mod base;
-pub mod bedwars_config;
-pub mod colors;
-pub mod commands;
-pub mod edit;
-pub mod items;
-pub mod lobby;
-pub mod r#match;
-pub mod menu;
-pub mod resource_spawners;
-pub mod shop;
-pub mod spectator;
-pub mod utils;
-
-/// A component that will be attached to players in the lobby
-#[derive(Debug, Default, Component)]
-pub struct LobbyPlayer;
-/// A component that will be attached to players spectating a match
-#[derive(Debug, Default, Component)]
-pub struct Spectator;
-
-/// A component that will be attached to players that are still playing
-// #[derive(Debug, Default, Component)]
-// pub struct ActivePlayer;
-
-/// A component that will be attached to players that are editing the map
-#[derive(Debug, Default, Component)]
-pub struct Editor;
-
-/// A component that will be attached to players in a match
-#[derive(Debug, Clone, Component, PartialEq, Eq, Hash)]
-pub struct Team {
-    pub name: String,
-    pub color: TeamColor,
+
+use bedwa_rs::{bedwars_config::BedwarsSettings, BedwarsPlugins};
+use bevy_time::TimePlugin;
+use clap::{Parser, ValueEnum};
+use serde::Deserialize;
+use valence::{
+    network::{ConnectionMode, NetworkSettings},
+    prelude::*,
+    ServerSettings,
+};
+
+const SERVER_SETTINGS_NAME: &str = "server.toml";
+
+const DEFAULT_PORT: u16 = 25565;
+const DEFAULT_TICK_RATE: u32 = 20;
+const DEFAULT_LOG_LEVEL: &str = "info";
+
+/// A minecraft bedwars server
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Cli {
+    /// Path to the server settings file [default: <config-dir>/server.toml]
+    #[arg(long)]
+    settings: Option<PathBuf>,
+    /// Directory containing shop.json, rules.json, bw-world.json and the arenas or maps directory
+    #[arg(long)]
+    config_dir: Option<PathBuf>,
+    /// World folder, if there is only a single arena
+    #[arg(long)]
+    world: Option<PathBuf>,
+    /// Address the server will listen on
+    #[arg(long)]
+    address: Option<IpAddr>,
+    /// Port the server will listen on
+    #[arg(long)]
+    port: Option<u16>,
+    /// How players are authenticated
+    #[arg(long)]
+    connection_mode: Option<ConnectionModeSetting>,
+    /// Forwarding secret, required for the velocity connection mode
+    #[arg(long)]
+    velocity_secret: Option<String>,
+    /// Maximum number of players on the server
+    #[arg(long)]
+    max_players: Option<usize>,
+    /// Ticks per second
+    #[arg(long)]
+    tick_rate: Option<u32>,
+    /// Log level or filter, like `info` or `bedwa_rs=debug` (overridden by `RUST_LOG`)
+    #[arg(long)]
+    log_level: Option<String>,
+    /// Start every arena in edit mode
+    #[arg(long)]
+    edit: bool,
+    /// Number of arenas that rotate through the maps directory [default: 1]
+    #[arg(long)]
+    map_arenas: Option<usize>,
+    /// Player that can use the `/bw` commands, can be used multiple times
+    #[arg(long = "admin")]
+    admins: Vec<String>,
 }
 
-#[derive(States, Debug, Hash, PartialEq, Eq, Clone, Default)]
-enum GameState {
-    #[default]
-    Lobby,
-    Match,
-    PostMatch,
-    Edit,
+/// Contents of the optional `server.toml` file, command line arguments take precedence
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ServerSettingsFile {
+    config_dir: Option<PathBuf>,
+    world: Option<PathBuf>,
+    address: Option<IpAddr>,
+    port: Option<u16>,
+    connection_mode: Option<ConnectionModeSetting>,
+    velocity_secret: Option<String>,
+    max_players: Option<usize>,
+    tick_rate: Option<u32>,
+    log_level: Option<String>,
+    edit: bool,
+    map_arenas: Option<usize>,
+    admins: Vec<String>,
 }
 
-// Time stamp when the last tick finished
-#[derive(Debug, Default, Resource)]
-pub struct LastTickTime(pub std::time::Duration);
+#[derive(Debug, Clone, Copy, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum ConnectionModeSetting {
+    Online,
+    Offline,
+    Bungeecord,
+    Velocity,
+}
 
-fn main() {
-    std::env::set_var("RUST_LOG", "debug");
+fn load_settings_file(path: &Path) -> color_eyre::Result<ServerSettingsFile> {
+    if !path.exists() {
+        return Ok(ServerSettingsFile::default());
+    }
 
-    App::new()
-        .insert_resource(ServerSettings {
-            tick_rate: NonZero::new(20).unwrap(),
-            ..Default::default()
-        })
-        .add_plugins(StatesPlugin)
-        .add_plugins(ChatPlugin)
-        .add_plugins(SpectatorPlugin)
-        .add_plugins(ScoreboardPlugin)
-        .add_plugins(EditPlugin)
-        .add_plugins(VoidDeathPlugin)
-        .add_plugins(DeathPlugin)
-        .add_plugins(TimePlugin)
-        .add_plugins(FallDamagePlugin)
-        .init_state::<GameState>()
-        .add_plugins(DefaultPlugins)
-        .add_plugins(LobbyPlugin)
-        .add_plugins(BuildPlugin)
-        .add_plugins(BlockBreakPlugin)
-        .add_plugins(ItemMenuPlugin)
-        .add_plugins(MatchPlugin)
-        .add_plugins(ShopPlugin)
-        .add_plugins(ItemPickupPlugin)
-        .add_plugins(RegenerationPlugin)
-        .add_plugins(BowPlugin)
-        .add_plugins(PhysicsPlugin)
-        .add_plugins(EnderPearlPlugin)
-        // .add_plugins(ItemEntityPlugin)
-        .add_plugins(DespawnTimerPlugin)
-        .add_plugins(ItemDropPlugin)
-        .add_plugins(ResourceSpawnerPlugin)
-        .add_plugins(CombatPlugin)
-        .add_plugins(ArmorRightClickEquipPlugin)
-        .add_plugins(ChestPlugin)
-        .add_systems(Startup, setup)
-        .add_systems(
-            Update,
-            (
-                init_clients,
-                handle_bedwars_admin_command,
-                update_last_tick_time,
-                despawn_disconnected_clients,
-            ),
-        )
-        // DEBUG
-        .add_plugins(DebugPlugin)
-        .add_command::<BedwarsAdminCommand>()
-        .insert_resource(LastTickTime::default())
-        .observe(on_disconnect)
-        .run();
+    let settings = std::fs::read_to_string(path)?;
+    let settings: ServerSettingsFile = toml::from_str(&settings)?;
+    Ok(settings)
 }
 
-fn setup(
-    mut commands: Commands,
-    server: Res<Server>,
-    biomes: Res<BiomeRegistry>,
-    dimensions: Res<DimensionTypeRegistry>,
-    mut state: ResMut<NextState<GameState>>,
-) {
-    let layer = LayerBundle::new(ident!("overworld"), &dimensions, &biomes, &server);
-
-    let level = AnvilLevel::new("world", &biomes);
-
-    commands.spawn((layer, level));
-
-    let wip_config = {
-        if let Ok(config) = bedwars_config::load_config() {
-            commands.insert_resource(config.clone());
-            bedwars_config::WIPWorldConfig::from_saved_config(&config)
-        } else {
-            tracing::warn!("No bedwars config found, enabling edit mode");
-            state.set(GameState::Edit);
-            bedwars_config::WIPWorldConfig::default()
-        }
-    };
+fn main() -> color_eyre::Result<()> {
+    let cli = Cli::parse();
 
-    commands.insert_resource(wip_config);
+    let settings_path = cli.settings.clone().unwrap_or_else(|| {
+        cli.config_dir
+            .clone()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join(SERVER_SETTINGS_NAME)
+    });
+    let file = load_settings_file(&settings_path)?;
 
-    let shop_config = {
-        bedwars_config::load_trader_config().unwrap_or_else(|e| {
-            tracing::error!("Failed to load trader config: {}", e);
-            std::process::exit(1);
-        })
-    };
+    let log_level = cli
+        .log_level
+        .or(file.log_level)
+        .unwrap_or_else(|| DEFAULT_LOG_LEVEL.to_string());
 
-    commands.insert_resource(shop_config);
-}
+    if std::env::var_os("RUST_LOG").is_none() {
+        std::env::set_var("RUST_LOG", log_level);
+    }
 
-#[allow(clippy::type_complexity)]
-fn init_clients(
-    mut commands: Commands,
-    mut clients: Query<
-        (
-            Entity,
-            &mut EntityLayerId,
-            &mut VisibleChunkLayer,
-            &mut VisibleEntityLayers,
-        ),
-        Added<Client>,
-    >,
-    layers: Query<Entity, (With<ChunkLayer>, With<EntityLayer>)>,
-    // bedwars_config: Option<Res<bedwars_config::BedwarsConfig>>,
-    state: Res<State<GameState>>,
-) {
-    let layer = layers.single();
-    for (entity, mut layer_id, mut visible_chunk_layer, mut visible_entity_layers) in &mut clients {
-        layer_id.0 = layer;
-        visible_chunk_layer.0 = layer;
-        visible_entity_layers.0.insert(layer);
-
-        match state.get() {
-            GameState::Lobby => {
-                commands.entity(entity).insert(LobbyPlayer);
-            }
-            GameState::Match | GameState::PostMatch => {
-                commands.entity(entity).insert(Spectator);
+    let connection_mode = match cli
+        .connection_mode
+        .or(file.connection_mode)
+        .unwrap_or(ConnectionModeSetting::Online)
+    {
+        ConnectionModeSetting::Online => ConnectionMode::Online {
+            prevent_proxy_connections: false,
+        },
+        ConnectionModeSetting::Offline => ConnectionMode::Offline,
+        ConnectionModeSetting::Bungeecord => ConnectionMode::BungeeCord,
+        ConnectionModeSetting::Velocity => {
+            let Some(secret) = cli.velocity_secret.or(file.velocity_secret) else {
+                color_eyre::eyre::bail!("The velocity connection mode requires a velocity secret");
+            };
+
+            ConnectionMode::Velocity {
+                secret: Arc::from(secret),
             }
-            GameState::Edit => {
-                commands.entity(entity).insert(Editor);
-            } // _ => {}
-        };
-    }
-}
+        }
+    };
+
+    let address = SocketAddr::new(
+        cli.address
+            .or(file.address)
+            .unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED)),
+        cli.port.or(file.port).unwrap_or(DEFAULT_PORT),
+    );
+
+    let Some(tick_rate) = NonZero::new(
+        cli.tick_rate
+            .or(file.tick_rate)
+            .unwrap_or(DEFAULT_TICK_RATE),
+    ) else {
+        color_eyre::eyre::bail!("The tick rate must be greater than 0");
+    };
 
-fn on_disconnect(
-    trigger: Trigger<OnRemove, Client>,
-    query: Query<(&Username, &Position)>,
-    // commands: Commands,
-    game_state: Res<State<GameState>>,
-    mut lobby_state: Option<ResMut<LobbyPlayerState>>,
-    // eliminate a player that disconnects during a match
-    mut elimination_writer: EventWriter<PlayerEliminatedEvent>,
-) {
-    let Ok((username, position)) = query.get(trigger.entity()) else {
-        return;
+    let bedwars_settings = BedwarsSettings {
+        config_dir: cli
+            .config_dir
+            .or(file.config_dir)
+            .unwrap_or_else(|| PathBuf::from(".")),
+        world_dir: cli.world.or(file.world),
+        force_edit: cli.edit || file.edit,
+        admins: cli.admins.into_iter().chain(file.admins).collect(),
+        map_arenas: cli.map_arenas.or(file.map_arenas).unwrap_or(1),
     };
 
-    tracing::info!("Player {} disconnected", username);
+    let mut network_settings = NetworkSettings {
+        address,
+        connection_mode,
+        ..Default::default()
+    };
 
-    match game_state.get() {
-        GameState::Lobby => {
-            if let Some(lobby_state) = lobby_state.as_mut() {
-                lobby_state.players.remove(&username.0);
-                lobby_state.without_team = lobby_state.without_team.saturating_sub(1);
-            }
-        }
-        GameState::Match => {
-            elimination_writer.send(PlayerEliminatedEvent {
-                attacker: None,
-                victim: trigger.entity(),
-                position: position.0,
-            });
-        }
-        _ => {}
+    if let Some(max_players) = cli.max_players.or(file.max_players) {
+        network_settings.max_players = max_players;
     }
-}
 
-fn update_last_tick_time(time: Res<Time>, mut last_tick_time: ResMut<LastTickTime>) {
-    last_tick_time.0 = time.delta();
+    App::new()
+        .insert_resource(ServerSettings {
+            tick_rate,
+            ..Default::default()
+        })
+        .insert_resource(network_settings)
+        .insert_resource(bedwars_settings)
+        .add_plugins(TimePlugin)
+        .add_plugins(DefaultPlugins)
+        .add_plugins(BedwarsPlugins)
+        .run();
+
+    Ok(())
 }
@@ -0,0 +1,210 @@
+use std::path::{Path, PathBuf};
+
+use color_eyre::eyre::eyre;
+use rand::Rng;
+use valence::prelude::Component;
+
+use crate::{
+    bedwars_config::{
+        self, MapRotationMode, ShopConfig, WorldConfig, SHOP_CONFIG_NAME, WORLD_CONFIG_NAME,
+        WORLD_DIR_NAME,
+    },
+    config_validation::ConfigError,
+};
+
+/// A map that can be played in an arena
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MapInfo {
+    pub name: String,
+    pub world_dir: PathBuf,
+    pub config_path: PathBuf,
+    /// Shop that replaces the global shop while this map is played, if the file exists
+    pub shop_path: Option<PathBuf>,
+}
+
+impl MapInfo {
+    /// A map with its world folder, world config and shop in one directory
+    pub fn from_dir(dir: &Path) -> Self {
+        Self {
+            name: dir
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| "default".to_string()),
+            world_dir: dir.join(WORLD_DIR_NAME),
+            config_path: dir.join(WORLD_CONFIG_NAME),
+            shop_path: Some(dir.join(SHOP_CONFIG_NAME)),
+        }
+    }
+}
+
+/// The world config and shop of a map
+#[derive(Debug, Clone, Default)]
+pub struct LoadedMap {
+    /// `None` if the map has no world config yet
+    pub config: Option<WorldConfig>,
+    pub shop: Option<ShopConfig>,
+    /// Problems of the world config, they can be fixed in edit mode
+    pub errors: Vec<ConfigError>,
+}
+
+impl LoadedMap {
+    /// Whether a match can be played on this map
+    pub fn is_playable(&self) -> bool {
+        self.config.is_some() && self.errors.is_empty()
+    }
+}
+
+/// Load the world config and shop of a map.
+/// Fails if one of the files can not be parsed, or if the shop is invalid.
+pub fn load_map(map: &MapInfo) -> color_eyre::Result<LoadedMap> {
+    let config = if map.config_path.exists() {
+        let config = bedwars_config::load_config(&map.config_path)
+            .map_err(|e| eyre!("failed to parse {}: {}", map.config_path.display(), e))?;
+        Some(config)
+    } else {
+        None
+    };
+
+    let errors = config
+        .as_ref()
+        .map(|config| config.validate())
+        .unwrap_or_default();
+
+    let shop = match &map.shop_path {
+        Some(shop_path) if shop_path.exists() => {
+            let shop = bedwars_config::load_shop_config(shop_path)
+                .map_err(|e| eyre!("failed to parse {}: {}", shop_path.display(), e))?;
+            if let Some(error) = shop.validate().first() {
+                return Err(eyre!("invalid shop: {}", error));
+            }
+
+            Some(shop)
+        }
+        _ => None,
+    };
+
+    Ok(LoadedMap {
+        config,
+        shop,
+        errors,
+    })
+}
+
+/// The maps an arena switches between after every match, stored on the arena entity
+#[derive(Debug, Clone, Component)]
+pub struct MapRotation {
+    pub maps: Vec<MapInfo>,
+    /// Index of the map that is currently played
+    pub current: usize,
+}
+
+impl MapRotation {
+    pub fn new(maps: Vec<MapInfo>, current: usize) -> Self {
+        assert!(!maps.is_empty(), "a map rotation needs at least one map");
+        Self {
+            current: current % maps.len(),
+            maps,
+        }
+    }
+
+    /// A rotation that always plays the same map
+    pub fn single(map: MapInfo) -> Self {
+        Self::new(vec![map], 0)
+    }
+
+    pub fn current(&self) -> &MapInfo {
+        &self.maps[self.current]
+    }
+
+    /// Switch to the next map
+    pub fn advance(&mut self, mode: MapRotationMode) {
+        if self.maps.len() < 2 {
+            return;
+        }
+
+        self.current = match mode {
+            MapRotationMode::Sequential => (self.current + 1) % self.maps.len(),
+            MapRotationMode::Random => {
+                // Skip the current map
+                let next = rand::thread_rng().gen_range(0..self.maps.len() - 1);
+                if next >= self.current {
+                    next + 1
+                } else {
+                    next
+                }
+            }
+        };
+    }
+
+    /// Switch to the next map that can be played and load it.
+    /// Returns `None` and keeps the current map if no other map can be played.
+    pub fn load_next(&mut self, mode: MapRotationMode) -> Option<LoadedMap> {
+        let previous = self.current;
+
+        for _ in 1..self.maps.len() {
+            self.advance(mode);
+            let map = self.current();
+
+            match load_map(map) {
+                Ok(loaded) if loaded.is_playable() => return Some(loaded),
+                Ok(loaded) => {
+                    for error in &loaded.errors {
+                        tracing::error!("Invalid bedwars config of map {}: {}", map.name, error);
+                    }
+                }
+                Err(e) => tracing::error!("Failed to load map {}: {}", map.name, e),
+            }
+        }
+
+        self.current = previous;
+        None
+    }
+}
+
+/// The shop of the map an arena is playing, replaces the global `ShopConfig` in that arena
+#[derive(Debug, Clone, Component)]
+pub struct MapShop(pub ShopConfig);
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    fn rotation(count: usize) -> MapRotation {
+        let maps = (0..count)
+            .map(|idx| MapInfo::from_dir(Path::new(&format!("maps/map{idx}"))))
+            .collect();
+        MapRotation::new(maps, 0)
+    }
+
+    #[test]
+    fn test_sequential_rotation() {
+        let mut rotation = rotation(3);
+
+        rotation.advance(MapRotationMode::Sequential);
+        assert_eq!(rotation.current().name, "map1");
+        rotation.advance(MapRotationMode::Sequential);
+        rotation.advance(MapRotationMode::Sequential);
+        assert_eq!(rotation.current().name, "map0");
+    }
+
+    #[test]
+    fn test_random_rotation_changes_map() {
+        let mut rotation = rotation(2);
+
+        for _ in 0..10 {
+            let last = rotation.current;
+            rotation.advance(MapRotationMode::Random);
+            assert_ne!(rotation.current, last);
+        }
+    }
+
+    #[test]
+    fn test_load_next_keeps_current_map() {
+        // None of the maps exist, so none of them can be played
+        let mut rotation = rotation(3);
+        rotation.current = 1;
+
+        assert!(rotation.load_next(MapRotationMode::Sequential).is_none());
+        assert_eq!(rotation.current, 1);
+    }
+}
@@ -1,32 +1,33 @@
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, VecDeque},
+    time::Instant,
+};
 
 use bevy_ecs::{
+    bundle::Bundle,
     entity::Entity,
     event::{Event, EventReader, EventWriter},
-    query::With,
-    system::{Commands, Query, Res, ResMut},
+    query::{Changed, With},
+    system::{Commands, Query, Res},
     world::OnRemove,
 };
-use bevy_state::{
-    prelude::in_state,
-    state::{NextState, OnEnter},
-};
 use bevy_time::{Time, Timer, TimerMode};
 use valence::{
     app::{Plugin, Update},
     client::{Client, Username},
-    entity::{item::Stack, Position},
+    entity::{item::Stack, EntityLayerId, Position},
     equipment::EquipmentInventorySync,
     math::DVec3,
     message::SendMessage,
     player_list::DisplayName,
-    prelude::{Block, DetectChanges, Equipment, IntoSystemConfigs, Inventory, Resource, Trigger},
+    prelude::{Block, Component, Equipment, IntoSystemConfigs, Inventory, Trigger},
     protocol::{sound::SoundCategory, Sound},
     title::SetTitle,
     BlockPos, BlockState, ChunkLayer, Despawned, GameMode, ItemKind,
 };
 
 use crate::{
+    arena::{any_arena_in, move_to_arena, spawn_arena, Arena, LayerResources},
     base::{
         break_blocks::BedDestroyedEvent,
         build::PlayerPlacedBlocks,
@@ -37,20 +38,28 @@ use crate::{
         physics::CollidableForEntities,
         scoreboard::BedwarsScoreboard,
     },
-    bedwars_config::WorldConfig,
+    bedwars_config::{GameRules, MatchEvent, TieBreak, WorldConfig},
+    items::effects::ActiveEffects,
+    maps::MapRotation,
+    reconnect::DisconnectedPlayers,
     resource_spawners::ResourceSpawner,
+    shop::Shop,
+    team_upgrades::TeamUpgrade,
+    traps::Trap,
     utils::inventory::InventoryExt,
     GameState, LobbyPlayer, Spectator, Team,
 };
 
-/// Time to wait before sending all the players back to the lobby after the match ends.
-pub const POST_MATCH_TIME_SECS: f32 = 10.0;
-
-#[derive(Debug, Clone, Resource)]
+/// State of the match running in an arena, stored on the arena entity
+#[derive(Debug, Clone, Component)]
 pub struct MatchState {
     pub started: std::time::Instant,
     pub player_stats: HashMap<String, PlayerStats>,
     pub teams: HashMap<String, TeamState>,
+    /// Events of the match schedule that did not happen yet, sorted by time
+    pub schedule: Vec<MatchEvent>,
+    /// Set by the sudden death event, players no longer regenerate health
+    pub sudden_death: bool,
 }
 
 impl Default for MatchState {
@@ -65,6 +74,47 @@ impl MatchState {
             started: std::time::Instant::now(),
             player_stats: HashMap::new(),
             teams: HashMap::new(),
+            schedule: vec![],
+            sudden_death: false,
+        }
+    }
+
+    /// The next event of the match schedule
+    pub fn next_event(&self) -> Option<&MatchEvent> {
+        self.schedule.first()
+    }
+
+    /// The team that wins if the match ends while multiple teams are left,
+    /// `None` if the match is a draw
+    pub fn tie_break_winner(&self, tie_break: TieBreak) -> Option<&str> {
+        let score = |team: &TeamState| match tie_break {
+            TieBreak::PlayersAlive => team.players_alive.len() as u32,
+            TieBreak::Kills => team
+                .players
+                .iter()
+                .filter_map(|player| self.player_stats.get(player))
+                .map(|stats| stats.kills as u32)
+                .sum(),
+            TieBreak::Draw => 0,
+        };
+
+        if tie_break == TieBreak::Draw {
+            return None;
+        }
+
+        let scores = self
+            .teams
+            .iter()
+            .filter(|(_, team)| !team.players_alive.is_empty())
+            .map(|(name, team)| (name, score(team)))
+            .collect::<Vec<_>>();
+
+        let best_score = scores.iter().map(|(_, score)| *score).max()?;
+        let mut best_teams = scores.iter().filter(|(_, score)| *score == best_score);
+
+        match (best_teams.next(), best_teams.next()) {
+            (Some((name, _)), None) => Some(name.as_str()),
+            _ => None,
         }
     }
 }
@@ -83,14 +133,27 @@ pub struct TeamState {
     pub players: Vec<String>,
     pub players_alive: Vec<String>,
     pub bed_destroyed: bool,
+    /// Bought tier of every team upgrade
+    pub upgrades: HashMap<TeamUpgrade, u32>,
+    /// Traps that were bought, the first one is triggered next
+    pub traps: VecDeque<Trap>,
+    /// When the last trap of the team was triggered
+    pub last_trap_triggered: Option<Instant>,
 }
 
-#[derive(Debug, Clone, Resource)]
+impl TeamState {
+    /// The tier of an upgrade, 0 if it was not bought yet
+    pub fn upgrade_tier(&self, upgrade: TeamUpgrade) -> u32 {
+        self.upgrades.get(&upgrade).copied().unwrap_or(0)
+    }
+}
+
+#[derive(Debug, Clone, Component)]
 struct PostMatchTimer(pub Timer);
 
-impl Default for PostMatchTimer {
-    fn default() -> Self {
-        Self(Timer::from_seconds(POST_MATCH_TIME_SECS, TimerMode::Once))
+impl PostMatchTimer {
+    fn new(post_match_time_secs: f32) -> Self {
+        Self(Timer::from_seconds(post_match_time_secs, TimerMode::Once))
     }
 }
 
@@ -98,90 +161,126 @@ pub struct MatchPlugin;
 
 impl Plugin for MatchPlugin {
     fn build(&self, app: &mut valence::app::App) {
-        app.add_systems(OnEnter(GameState::Match), (start_match,))
+        app.add_systems(Update, (start_match, on_enter_post_match))
             .add_systems(
                 Update,
                 (on_bed_destroy, on_player_death, on_player_elimination)
-                    .run_if(in_state(GameState::Match)),
+                    .run_if(any_arena_in(GameState::Match)),
             )
             .add_event::<EndMatch>()
-            .add_systems(Update, (on_end_match,).run_if(in_state(GameState::Match)))
-            .add_systems(OnEnter(GameState::PostMatch), (on_enter_post_match,))
             .add_systems(
                 Update,
-                (tick_postmatch_timer,).run_if(in_state(GameState::PostMatch)),
+                (on_end_match, on_match_ended)
+                    .chain()
+                    .run_if(any_arena_in(GameState::Match)),
+            )
+            .add_systems(
+                Update,
+                (tick_postmatch_timer,).run_if(any_arena_in(GameState::PostMatch)),
             )
             .observe(on_remove_team);
     }
 }
 
+#[allow(clippy::type_complexity)]
 fn start_match(
     mut commands: Commands,
-    mut players: Query<(
-        Entity,
-        &mut Position,
-        &mut Inventory,
-        &mut Client,
-        &mut GameMode,
-        &Username,
-        &Team,
-    )>,
-    bedwars_config: Res<WorldConfig>,
+    arenas: Query<(Entity, &GameState, &WorldConfig), Changed<GameState>>,
+    mut players: Query<
+        (
+            Entity,
+            &EntityLayerId,
+            &mut Position,
+            &mut Inventory,
+            &mut GameMode,
+            &Username,
+            &Team,
+        ),
+        With<Client>,
+    >,
+    rules: Res<GameRules>,
 ) {
-    tracing::info!("Starting match");
+    for (arena, state, bedwars_config) in &arenas {
+        if *state != GameState::Match {
+            continue;
+        }
 
-    let mut match_state = MatchState::new();
-    for team in &bedwars_config.teams {
-        match_state
-            .teams
-            .insert(team.0.to_string(), TeamState::default());
-    }
+        tracing::info!("Starting match");
 
-    for (entity, mut pos, mut inventory, _client, mut game_mode, username, team) in
-        players.iter_mut()
-    {
-        *game_mode = GameMode::Survival;
-        inventory.clear();
-        inventory.readonly = false;
+        let mut match_state = MatchState::new();
+        match_state.schedule = rules.match_schedule.clone();
+        match_state.schedule.sort_by_key(|event| event.time_secs);
 
-        // inventory.set_slot(36, valence::ItemStack::new(ItemKind::Brick, 64, None));
-        // inventory.set_slot(37, valence::ItemStack::new(ItemKind::IronIngot, 64, None));
-        // inventory.set_slot(38, valence::ItemStack::new(ItemKind::GoldIngot, 64, None));
+        for team in &bedwars_config.teams {
+            match_state
+                .teams
+                .insert(team.0.to_string(), TeamState::default());
+        }
 
-        let team_spawn = bedwars_config.spawns.get(&team.name).unwrap();
-        pos.set(team_spawn.clone());
+        for (entity, layer_id, mut pos, mut inventory, mut game_mode, username, team) in
+            players.iter_mut()
+        {
+            if layer_id.0 != arena {
+                continue;
+            }
 
-        commands
-            .entity(entity)
-            .insert(CombatState::default())
-            .insert(FallingState::default())
-            .insert(Equipment::default())
-            .insert(CollidableForEntities)
-            .insert(EquipmentInventorySync);
+            *game_mode = GameMode::Survival;
+            inventory.clear();
+            inventory.readonly = false;
 
-        match_state
-            .player_stats
-            .insert(username.0.clone(), PlayerStats::default());
+            // inventory.set_slot(36, valence::ItemStack::new(ItemKind::Brick, 64, None));
+            // inventory.set_slot(37, valence::ItemStack::new(ItemKind::IronIngot, 64, None));
+            // inventory.set_slot(38, valence::ItemStack::new(ItemKind::GoldIngot, 64, None));
+
+            let (Some(team_spawn), Some(team_state)) = (
+                bedwars_config.spawns.get(&team.name),
+                match_state.teams.get_mut(&team.name),
+            ) else {
+                tracing::error!("Team {} does not exist in this arena", team.name);
+                continue;
+            };
+
+            pos.set(team_spawn.clone());
+            team_state.players_alive.push(username.to_string());
+            team_state.players.push(username.to_string());
 
-        let team = match_state.teams.get_mut(&team.name).unwrap();
+            commands.entity(entity).insert(match_player_bundle());
+
+            match_state
+                .player_stats
+                .insert(username.0.clone(), PlayerStats::default());
+        }
 
-        team.players_alive.push(username.to_string());
-        team.players.push(username.to_string());
+        commands
+            .entity(arena)
+            .insert((match_state, DisconnectedPlayers::default()));
     }
+}
 
-    commands.insert_resource(match_state);
+/// Components of every player that takes part in a match
+pub fn match_player_bundle() -> impl Bundle {
+    (
+        CombatState::default(),
+        FallingState::default(),
+        Equipment::default(),
+        CollidableForEntities,
+        EquipmentInventorySync,
+        ActiveEffects::default(),
+    )
 }
 
 fn on_bed_destroy(
-    mut clients: Query<(&mut Client, &Team)>,
+    mut clients: Query<(&mut Client, &EntityLayerId, &Team)>,
     mut events: EventReader<BedDestroyedEvent>,
-    mut layer: Query<&mut ChunkLayer>,
-    mut match_state: ResMut<MatchState>,
-    bedwars_config: Res<WorldConfig>,
+    mut arenas: Query<(&mut ChunkLayer, &mut MatchState, &WorldConfig)>,
 ) {
     for event in events.read() {
-        for (mut client, team) in &mut clients {
-            if team == &event.team {
+        let Ok((mut layer, mut match_state, bedwars_config)) = arenas.get_mut(event.arena) else {
+            continue;
+        };
+
+        for (mut client, layer_id, team) in &mut clients {
+            if layer_id.0 == event.arena && team == &event.team {
                 client.set_title("§cyour bed was destroyed!");
                 client.send_chat_message("§cYour bed was destroyed!");
             }
@@ -195,11 +294,10 @@ fn on_bed_destroy(
             .unwrap()
             .bed_destroyed = true;
 
-        let mut layer = layer.single_mut();
         layer.play_sound(
             Sound::EntityHorseDeath,
             SoundCategory::Master,
-            Into::<DVec3>::into(bed_pos[0].0.clone()),
+            Into::<DVec3>::into(bed_pos[0].pos.clone()),
             1.0,
             1.0,
         );
@@ -208,11 +306,15 @@ fn on_bed_destroy(
 
 fn on_player_death(
     mut events: EventReader<PlayerDeathEvent>,
-    players: Query<(&Username, &Team)>,
-    mut match_state: ResMut<MatchState>,
+    players: Query<(&Username, &EntityLayerId, &Team)>,
+    mut arenas: Query<&mut MatchState>,
 ) {
     for event in events.read() {
-        let Ok((victim_name, _victim_team)) = players.get(event.victim) else {
+        let Ok((victim_name, layer_id, _victim_team)) = players.get(event.victim) else {
+            continue;
+        };
+
+        let Ok(mut match_state) = arenas.get_mut(layer_id.0) else {
             continue;
         };
 
@@ -220,7 +322,7 @@ fn on_player_death(
         victim_stats.deaths += 1;
 
         if let Some(attacker) = event.attacker {
-            let Ok((attacker_name, _)) = players.get(attacker) else {
+            let Ok((attacker_name, _, _)) = players.get(attacker) else {
                 continue;
             };
 
@@ -232,11 +334,15 @@ fn on_player_death(
 
 fn on_player_elimination(
     mut events: EventReader<PlayerEliminatedEvent>,
-    players: Query<(&Username, &Team)>,
-    mut match_state: ResMut<MatchState>,
+    players: Query<(&Username, &EntityLayerId, &Team)>,
+    mut arenas: Query<&mut MatchState>,
 ) {
     for event in events.read() {
-        let Ok((victim_name, victim_team)) = players.get(event.victim) else {
+        let Ok((victim_name, layer_id, victim_team)) = players.get(event.victim) else {
+            continue;
+        };
+
+        let Ok(mut match_state) = arenas.get_mut(layer_id.0) else {
             continue;
         };
 
@@ -249,7 +355,7 @@ fn on_player_elimination(
         victim_stats.deaths += 1;
 
         if let Some(attacker) = event.attacker {
-            let Ok((attacker_name, _)) = players.get(attacker) else {
+            let Ok((attacker_name, _, _)) = players.get(attacker) else {
                 continue;
             };
 
@@ -261,131 +367,216 @@ fn on_player_elimination(
 
 #[derive(Debug, Clone, Event)]
 pub struct EndMatch {
-    pub winner: Team,
+    pub arena: Entity,
+    /// `None` if the match ended in a draw
+    pub winner: Option<Team>,
 }
 
 fn on_end_match(
-    // commands: Commands,
-    match_state: ResMut<MatchState>,
-    bedwars_config: Res<WorldConfig>,
-    mut clients: Query<(Entity, &mut Client, &Position, &Team), With<Client>>,
+    arenas: Query<(Entity, &GameState, &MatchState, &WorldConfig), Changed<MatchState>>,
     mut event_writer: EventWriter<EndMatch>,
-    mut state: ResMut<NextState<GameState>>,
 ) {
-    if !match_state.is_changed() {
-        return;
-    }
+    for (arena, state, match_state, bedwars_config) in &arenas {
+        if *state != GameState::Match {
+            continue;
+        }
+
+        let teams_left = match_state
+            .teams
+            .iter()
+            .filter(|(_, team)| !team.players_alive.is_empty())
+            .count();
 
-    let teams_left = match_state
-        .teams
-        .iter()
-        .filter(|(_, team)| !team.players_alive.is_empty())
-        .count();
+        // DEBUG
+        if teams_left > 1 {
+            continue;
+        }
 
-    // DEBUG
-    if teams_left > 1 {
-        return;
+        let winner = match_state
+            .teams
+            .iter()
+            .find(|(_, team)| !team.players_alive.is_empty())
+            .and_then(|(name, _)| bedwars_config.teams.get_key_value(name))
+            .map(|(name, color)| Team {
+                name: name.to_string(),
+                color: *color,
+            });
+
+        event_writer.send(EndMatch { arena, winner });
     }
+}
 
-    let winner = match_state
-        .teams
-        .iter()
-        .find(|(_, team)| !team.players_alive.is_empty())
-        .map(|(name, _)| bedwars_config.teams.get_key_value(name).unwrap())
-        .unwrap();
-
-    event_writer.send(EndMatch {
-        winner: Team {
-            name: winner.0.to_string(),
-            color: *winner.1,
-        },
-    });
-
-    for (_ent, mut client, position, team) in &mut clients {
-        if team.name == *winner.0 {
-            client.play_sound(
-                Sound::EntityPlayerLevelup,
-                SoundCategory::Player,
-                position.0,
-                0.75,
-                1.0,
-            );
+fn on_match_ended(
+    mut commands: Commands,
+    mut events: EventReader<EndMatch>,
+    mut clients: Query<(&mut Client, &EntityLayerId, &Position, &Team)>,
+    arenas: Query<&GameState>,
+) {
+    let mut ended = vec![];
+
+    for event in events.read() {
+        // The match may end for multiple reasons in the same tick
+        if ended.contains(&event.arena)
+            || arenas
+                .get(event.arena)
+                .map_or(true, |state| *state != GameState::Match)
+        {
+            continue;
+        }
+        ended.push(event.arena);
+
+        if let Some(winner) = &event.winner {
+            for (mut client, layer_id, position, team) in &mut clients {
+                if layer_id.0 == event.arena && team == winner {
+                    client.play_sound(
+                        Sound::EntityPlayerLevelup,
+                        SoundCategory::Player,
+                        position.0,
+                        0.75,
+                        1.0,
+                    );
+                }
+            }
         }
-    }
 
-    state.set(GameState::PostMatch);
+        commands.entity(event.arena).insert(GameState::PostMatch);
+    }
 }
 
-fn on_enter_post_match(mut commands: Commands) {
-    commands.insert_resource(PostMatchTimer::default());
+fn on_enter_post_match(
+    mut commands: Commands,
+    arenas: Query<(Entity, &GameState), Changed<GameState>>,
+    rules: Res<GameRules>,
+) {
+    for (arena, state) in &arenas {
+        if *state == GameState::PostMatch {
+            commands
+                .entity(arena)
+                .insert(PostMatchTimer::new(rules.post_match_time_secs));
+        }
+    }
 }
 
+#[allow(clippy::type_complexity)]
 #[allow(clippy::too_many_arguments)]
 fn tick_postmatch_timer(
     mut commands: Commands,
-    mut players: Query<(Entity, &Client)>,
-    mut timer: ResMut<PostMatchTimer>,
-    mut state: ResMut<NextState<GameState>>,
-    scoreboard: Query<Entity, With<BedwarsScoreboard>>,
-    items: Query<Entity, With<Stack>>,
-    player_placed_blocks: ResMut<PlayerPlacedBlocks>,
-    mut layer: Query<&mut ChunkLayer>,
+    players: Query<(Entity, &EntityLayerId), With<Client>>,
+    mut arenas: Query<(
+        Entity,
+        &Arena,
+        &mut PostMatchTimer,
+        &mut ChunkLayer,
+        &mut PlayerPlacedBlocks,
+        &mut ChestState,
+        &mut MapRotation,
+        &WorldConfig,
+    )>,
     time: Res<Time>,
-    bedwars_config: Res<WorldConfig>,
-    mut chest_state: ResMut<ChestState>,
-    resource_spawners: Query<(Entity, &ResourceSpawner)>,
+    rules: Res<GameRules>,
+    layer_resources: LayerResources,
+    scoreboard: Query<(Entity, &EntityLayerId), With<BedwarsScoreboard>>,
+    items: Query<(Entity, &EntityLayerId), With<Stack>>,
+    shops: Query<(Entity, &EntityLayerId), With<Shop>>,
+    resource_spawners: Query<(Entity, &EntityLayerId), With<ResourceSpawner>>,
 ) {
-    timer.0.tick(time.delta());
+    for (
+        arena,
+        arena_info,
+        mut timer,
+        mut layer,
+        mut player_placed_blocks,
+        mut chest_state,
+        mut rotation,
+        bedwars_config,
+    ) in &mut arenas
+    {
+        timer.0.tick(time.delta());
 
-    if !timer.0.finished() {
-        return;
-    }
-    for (ent, _client) in &mut players {
-        commands
-            .entity(ent)
-            .remove::<CombatState>()
-            .remove::<FallingState>()
-            // .remove::<Equipment>()
-            .remove::<CollidableForEntities>()
-            .remove::<EquipmentInventorySync>()
-            .remove::<Team>()
-            .remove::<IsDead>()
-            .remove::<Spectator>()
-            .remove::<Burning>()
-            .insert(LobbyPlayer);
-    }
+        if !timer.0.finished() {
+            continue;
+        }
 
-    *chest_state = ChestState::default();
+        // Switch to the next map, the new map gets a new arena entity with a fresh world
+        let next_arena = rotation.load_next(rules.map_rotation).map(|map| {
+            tracing::info!(
+                "Arena {} switches to map {}",
+                arena_info.name,
+                rotation.current().name
+            );
 
-    for scoreboard in &mut scoreboard.iter() {
-        commands.entity(scoreboard).insert(Despawned);
-    }
+            spawn_arena(
+                &mut commands,
+                &layer_resources,
+                arena_info.name.clone(),
+                rotation.clone(),
+                GameState::Lobby,
+                map,
+            )
+        });
 
-    let mut layer = layer.single_mut();
+        for (ent, layer_id) in &players {
+            if layer_id.0 != arena {
+                continue;
+            }
 
-    for block_pos in player_placed_blocks.0.iter() {
-        layer.set_block(*block_pos.0, BlockState::AIR);
-    }
+            commands
+                .entity(ent)
+                .remove::<CombatState>()
+                .remove::<FallingState>()
+                // .remove::<Equipment>()
+                .remove::<CollidableForEntities>()
+                .remove::<EquipmentInventorySync>()
+                .remove::<Team>()
+                .remove::<IsDead>()
+                .remove::<Spectator>()
+                .remove::<Burning>()
+                .remove::<ActiveEffects>()
+                .insert(LobbyPlayer);
+
+            if let Some(next_arena) = next_arena {
+                move_to_arena(&mut commands, ent, next_arena);
+            }
+        }
 
-    // Despawn items
-    for item in &mut items.iter() {
-        commands.entity(item).insert(Despawned);
-    }
+        // Despawn the scoreboard, items, shops and resource spawners of the arena
+        for (ent, layer_id) in scoreboard
+            .iter()
+            .chain(items.iter())
+            .chain(shops.iter())
+            .chain(resource_spawners.iter())
+        {
+            if layer_id.0 == arena {
+                commands.entity(ent).insert(Despawned);
+            }
+        }
 
-    // Replace beds
-    for (_, bed_blocks_map) in bedwars_config.beds.iter() {
-        for (pos, block) in bed_blocks_map.iter() {
-            let block_pos = BlockPos::new(pos.x, pos.y, pos.z);
-            layer.set_block(block_pos, Block::from(block.clone()));
+        if next_arena.is_some() {
+            commands.entity(arena).insert(Despawned);
+            continue;
         }
-    }
 
-    // Remove resource spawners
-    for (ent, _) in &mut resource_spawners.iter() {
-        commands.entity(ent).insert(Despawned);
-    }
+        *chest_state = ChestState::default();
+
+        for block_pos in player_placed_blocks.0.iter() {
+            layer.set_block(*block_pos.0, BlockState::AIR);
+        }
+
+        player_placed_blocks.0.clear();
+
+        // Replace beds
+        for (_, bed_blocks_map) in bedwars_config.beds.iter() {
+            for bed_block in bed_blocks_map.iter() {
+                let block_pos = BlockPos::new(bed_block.pos.x, bed_block.pos.y, bed_block.pos.z);
+                layer.set_block(block_pos, Block::from(bed_block.block.clone()));
+            }
+        }
 
-    state.set(GameState::Lobby);
+        commands
+            .entity(arena)
+            .remove::<PostMatchTimer>()
+            .insert(GameState::Lobby);
+    }
 }
 
 fn on_remove_team(trigger: Trigger<OnRemove, Team>, mut clients: Query<&mut DisplayName>) {
@@ -395,3 +586,56 @@ fn on_remove_team(trigger: Trigger<OnRemove, Team>, mut clients: Query<&mut Disp
 
     display_name.0 = None;
 }
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    fn team(players: &[&str], players_alive: &[&str]) -> TeamState {
+        TeamState {
+            players: players.iter().map(|p| p.to_string()).collect(),
+            players_alive: players_alive.iter().map(|p| p.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_tie_break_winner() {
+        let mut match_state = MatchState::new();
+        match_state
+            .teams
+            .insert("red".to_string(), team(&["a", "b"], &["a", "b"]));
+        match_state
+            .teams
+            .insert("blue".to_string(), team(&["c", "d"], &["c"]));
+        match_state
+            .teams
+            .insert("green".to_string(), team(&["e", "f"], &[]));
+
+        for (player, kills) in [("a", 1), ("c", 3), ("e", 5)] {
+            match_state.player_stats.insert(
+                player.to_string(),
+                PlayerStats {
+                    kills,
+                    ..Default::default()
+                },
+            );
+        }
+
+        assert_eq!(
+            match_state.tie_break_winner(TieBreak::PlayersAlive),
+            Some("red")
+        );
+        // Eliminated teams can not win
+        assert_eq!(match_state.tie_break_winner(TieBreak::Kills), Some("blue"));
+        assert_eq!(match_state.tie_break_winner(TieBreak::Draw), None);
+
+        match_state
+            .teams
+            .get_mut("red")
+            .unwrap()
+            .players_alive
+            .pop();
+        assert_eq!(match_state.tie_break_winner(TieBreak::PlayersAlive), None);
+    }
+}
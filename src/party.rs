@@ -0,0 +1,133 @@
+use bevy_ecs::{
+    entity::Entity,
+    query::Added,
+    system::{Commands, Query},
+    world::OnRemove,
+};
+use valence::{
+    app::{App, Plugin, Update},
+    client::{Client, Username},
+    command::{scopes::CommandScopes, AddCommand},
+    message::SendMessage,
+    prelude::{Component, Trigger},
+};
+
+use crate::commands::party::{handle_party_command, PartyCommand};
+
+/// A group of players that will be put into the same team.
+/// Every party is its own entity, the members link to it with [`PartyMember`].
+#[derive(Debug, Clone, Component)]
+pub struct Party {
+    /// Name of the player that created the party
+    pub leader: String,
+    /// Names of all players in the party, including the leader
+    pub members: Vec<String>,
+}
+
+impl Party {
+    /// Parties are created when the first invitation is accepted, so they start with two members
+    pub fn new(leader: String, member: String) -> Self {
+        Self {
+            members: vec![leader.clone(), member],
+            leader,
+        }
+    }
+
+    /// Remove a member, if the leader left the next member becomes the leader
+    pub fn remove_member(&mut self, name: &str) {
+        self.members.retain(|member| member != name);
+
+        if self.leader == name {
+            if let Some(new_leader) = self.members.first() {
+                self.leader = new_leader.clone();
+            }
+        }
+    }
+
+    /// A party with a single player left is disbanded
+    pub fn is_disbanded(&self) -> bool {
+        self.members.len() <= 1
+    }
+}
+
+/// Attached to players that are in a party
+#[derive(Debug, Clone, Copy, Component)]
+pub struct PartyMember(pub Entity);
+
+/// Names of the players that invited a player into their party.
+/// The invitation is for the party the inviting player is in when it is accepted.
+#[derive(Debug, Clone, Default, Component)]
+pub struct PartyInvites(pub Vec<String>);
+
+pub struct PartyPlugin;
+
+impl Plugin for PartyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_command::<PartyCommand>()
+            .add_systems(
+                Update,
+                (
+                    init_party_scope,
+                    handle_party_command,
+                    remove_stale_party_members,
+                ),
+            )
+            .observe(leave_party_on_disconnect);
+    }
+}
+
+/// Every player can use the party commands
+fn init_party_scope(mut clients: Query<&mut CommandScopes, Added<Client>>) {
+    for mut scopes in &mut clients {
+        scopes.add("bedwars.command.party");
+    }
+}
+
+fn leave_party_on_disconnect(
+    trigger: Trigger<OnRemove, Client>,
+    mut commands: Commands,
+    mut clients: Query<(&Username, &mut Client, Option<&PartyMember>)>,
+    mut parties: Query<&mut Party>,
+) {
+    let Ok((username, _, Some(party_member))) = clients.get(trigger.entity()) else {
+        return;
+    };
+    let (username, party_ent) = (username.0.clone(), party_member.0);
+
+    let Ok(mut party) = parties.get_mut(party_ent) else {
+        return;
+    };
+
+    party.remove_member(&username);
+
+    for (_, mut client, member) in &mut clients {
+        if member.is_some_and(|member| member.0 == party_ent) {
+            client.send_chat_message(format!("§e{} left the party", username));
+        }
+    }
+
+    if party.is_disbanded() {
+        commands.entity(party_ent).despawn();
+    }
+}
+
+/// Members of parties that were disbanded, or that they left, lose their [`PartyMember`] component
+fn remove_stale_party_members(
+    mut commands: Commands,
+    mut members: Query<(Entity, &Username, &mut Client, &PartyMember)>,
+    parties: Query<&Party>,
+) {
+    for (player, username, mut client, party_member) in &mut members {
+        let is_member = parties
+            .get(party_member.0)
+            .is_ok_and(|party| party.members.contains(&username.0));
+
+        if !is_member {
+            if parties.get(party_member.0).is_err() {
+                client.send_chat_message("§eYour party was disbanded");
+            }
+
+            commands.entity(player).remove::<PartyMember>();
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use std::{collections::HashMap, time::Duration};
+
+use bevy_ecs::{
+    entity::Entity,
+    system::{Query, Res},
+};
+use bevy_time::{Time, Timer, TimerMode};
+use valence::{
+    app::{App, Plugin, Update},
+    client::Client,
+    entity::EntityLayerId,
+    message::SendMessage,
+    prelude::{Component, IntoSystemConfigs},
+};
+
+use crate::{arena::any_arena_in, r#match::MatchState, GameState, Team};
+
+/// Players that disconnected during the match of an arena, stored on the arena entity.
+/// Their team, stats and enderchest are kept by username until they rejoin,
+/// or until the grace period ends and they are eliminated.
+#[derive(Debug, Clone, Default, Component)]
+pub struct DisconnectedPlayers(pub HashMap<String, DisconnectedPlayer>);
+
+#[derive(Debug, Clone)]
+pub struct DisconnectedPlayer {
+    pub team: Team,
+    /// Time left until the player is eliminated
+    pub grace_timer: Timer,
+}
+
+impl DisconnectedPlayer {
+    pub fn new(team: Team, grace_secs: u32) -> Self {
+        Self {
+            team,
+            grace_timer: Timer::from_seconds(grace_secs as f32, TimerMode::Once),
+        }
+    }
+}
+
+impl DisconnectedPlayers {
+    /// Tick the grace periods, and remove the players that can not come back anymore.
+    /// A player can not respawn once their bed is destroyed, so they are eliminated right away.
+    pub fn remove_expired(
+        &mut self,
+        delta: Duration,
+        match_state: &MatchState,
+    ) -> Vec<(String, DisconnectedPlayer)> {
+        let expired = self
+            .0
+            .iter_mut()
+            .filter_map(|(name, player)| {
+                let bed_destroyed = match_state
+                    .teams
+                    .get(&player.team.name)
+                    .map_or(true, |team| team.bed_destroyed);
+
+                let expired = player.grace_timer.tick(delta).finished() || bed_destroyed;
+                expired.then(|| name.clone())
+            })
+            .collect::<Vec<_>>();
+
+        expired
+            .into_iter()
+            .filter_map(|name| self.0.remove_entry(&name))
+            .collect()
+    }
+}
+
+pub struct ReconnectPlugin;
+
+impl Plugin for ReconnectPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (eliminate_disconnected_players,).run_if(any_arena_in(GameState::Match)),
+        );
+    }
+}
+
+fn eliminate_disconnected_players(
+    mut arenas: Query<(
+        Entity,
+        &GameState,
+        &mut DisconnectedPlayers,
+        &mut MatchState,
+    )>,
+    mut clients: Query<(&EntityLayerId, &mut Client)>,
+    time: Res<Time>,
+) {
+    for (arena, state, mut disconnected, mut match_state) in &mut arenas {
+        if *state != GameState::Match || disconnected.0.is_empty() {
+            continue;
+        }
+
+        for (name, player) in disconnected.remove_expired(time.delta(), &match_state) {
+            tracing::info!("Player {} did not reconnect in time", name);
+
+            if let Some(team_state) = match_state.teams.get_mut(&player.team.name) {
+                team_state.players_alive.retain(|p| p != &name);
+            }
+
+            if let Some(stats) = match_state.player_stats.get_mut(&name) {
+                stats.deaths += 1;
+            }
+
+            let msg = format!(
+                "{}§n{}§r §adid not reconnect and was eliminated",
+                player.team.color.text_color(),
+                name
+            );
+
+            for (layer_id, mut client) in &mut clients {
+                if layer_id.0 == arena {
+                    client.send_chat_message(&msg);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::{colors::TeamColor, r#match::TeamState};
+
+    #[test]
+    fn test_remove_expired() {
+        let red = Team {
+            name: "red".to_string(),
+            color: TeamColor::Red,
+        };
+        let blue = Team {
+            name: "blue".to_string(),
+            color: TeamColor::Blue,
+        };
+
+        let mut match_state = MatchState::new();
+        match_state
+            .teams
+            .insert("red".to_string(), TeamState::default());
+        match_state.teams.insert(
+            "blue".to_string(),
+            TeamState {
+                bed_destroyed: true,
+                ..Default::default()
+            },
+        );
+
+        let mut disconnected = DisconnectedPlayers::default();
+        disconnected
+            .0
+            .insert("a".to_string(), DisconnectedPlayer::new(red, 10));
+        disconnected
+            .0
+            .insert("b".to_string(), DisconnectedPlayer::new(blue, 10));
+
+        // The bed of "b" is destroyed
+        let expired = disconnected.remove_expired(Duration::from_secs(1), &match_state);
+        assert_eq!(
+            expired
+                .into_iter()
+                .map(|(name, _)| name)
+                .collect::<Vec<_>>(),
+            vec!["b".to_string()]
+        );
+
+        let expired = disconnected.remove_expired(Duration::from_secs(10), &match_state);
+        assert_eq!(expired.len(), 1);
+        assert!(disconnected.0.is_empty());
+    }
+}
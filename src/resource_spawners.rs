@@ -1,5 +1,4 @@
 use bevy_ecs::bundle::Bundle;
-use bevy_state::{prelude::in_state, state::OnEnter};
 use bevy_time::{Time, Timer, TimerMode};
 use rand::Rng;
 use valence::{
@@ -13,13 +12,14 @@ use valence::{
 };
 
 use crate::{
+    arena::any_arena_in,
     base::{
         item_pickup::PickupMarker,
         physics::{
             CollidesWithBlocks, GetsStuckOnCollision, Gravity, PhysicsMarker, SimPhysicsForTime,
         },
     },
-    bedwars_config::WorldConfig,
+    bedwars_config::{SpawnerPlacement, WorldConfig},
     r#match::MatchState,
     utils::block::get_block_center,
     GameState, Team,
@@ -29,8 +29,10 @@ pub struct ResourceSpawnerPlugin;
 
 impl Plugin for ResourceSpawnerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::Match), (init_resource_spawners,))
-            .add_systems(Update, spawn_resources.run_if(in_state(GameState::Match)));
+        app.add_systems(Update, init_resource_spawners).add_systems(
+            Update,
+            spawn_resources.run_if(any_arena_in(GameState::Match)),
+        );
     }
 }
 
@@ -38,6 +40,7 @@ impl Plugin for ResourceSpawnerPlugin {
 pub struct ResourceSpawnerBundle {
     marker: ResourceSpawner,
     position: Position,
+    layer: EntityLayerId,
 }
 
 #[derive(Debug, Component)]
@@ -46,36 +49,92 @@ pub struct ResourceSpawner {
     timer: Timer,
 }
 
-fn init_resource_spawners(mut commands: Commands, bedwars_config: Res<WorldConfig>) {
-    for (pos, ser_item_stack, interval_sec, team_name) in &bedwars_config.resource_spawners {
-        let spawner_ent = commands
-            .spawn(ResourceSpawnerBundle {
-                marker: ResourceSpawner {
-                    item: Into::<ItemStack>::into(ser_item_stack.clone()),
-                    timer: Timer::from_seconds(*interval_sec, TimerMode::Repeating),
-                },
-                position: Position([pos.x as f64, pos.y as f64, pos.z as f64].into()),
-            })
-            .id();
-
-        if let Some(team_name) = team_name {
-            let team_color = bedwars_config.teams.get(team_name).unwrap();
-            commands.entity(spawner_ent).insert(Team {
-                name: team_name.clone(),
-                color: *team_color,
-            });
+impl ResourceSpawner {
+    pub fn item(&self) -> &ItemStack {
+        &self.item
+    }
+
+    /// Multiply the time between two spawns
+    pub fn upgrade(&mut self, interval_multiplier: f32) {
+        let interval = self.timer.duration().mul_f32(interval_multiplier);
+        self.timer.set_duration(interval);
+    }
+}
+
+fn init_resource_spawners(
+    mut commands: Commands,
+    arenas: Query<(Entity, &GameState, &WorldConfig), Changed<GameState>>,
+) {
+    for (arena, state, bedwars_config) in &arenas {
+        if *state != GameState::Match {
+            continue;
+        }
+
+        for SpawnerPlacement {
+            pos,
+            item,
+            interval_secs,
+            team: team_name,
+        } in &bedwars_config.resource_spawners
+        {
+            let team = match team_name {
+                Some(team_name) => {
+                    let Some(team_color) = bedwars_config.teams.get(team_name) else {
+                        tracing::error!(
+                            "Resource spawner at {} belongs to unknown team {}",
+                            pos,
+                            team_name
+                        );
+                        continue;
+                    };
+
+                    Some(Team {
+                        name: team_name.clone(),
+                        color: *team_color,
+                    })
+                }
+                None => None,
+            };
+
+            let spawner_ent = commands
+                .spawn(ResourceSpawnerBundle {
+                    marker: ResourceSpawner {
+                        item: Into::<ItemStack>::into(item.clone()),
+                        timer: Timer::from_seconds(*interval_secs, TimerMode::Repeating),
+                    },
+                    position: Position([pos.x as f64, pos.y as f64, pos.z as f64].into()),
+                    layer: EntityLayerId(arena),
+                })
+                .id();
+
+            if let Some(team) = team {
+                commands.entity(spawner_ent).insert(team);
+            }
         }
     }
 }
 
 fn spawn_resources(
     mut commands: Commands,
-    mut spawners: Query<(Entity, &mut ResourceSpawner, &Position, Option<&Team>)>,
-    match_state: Res<MatchState>,
+    mut spawners: Query<(
+        Entity,
+        &mut ResourceSpawner,
+        &Position,
+        &EntityLayerId,
+        Option<&Team>,
+    )>,
+    arenas: Query<(&GameState, &MatchState)>,
     time: Res<Time>,
-    layers: Query<Entity, (With<ChunkLayer>, With<EntityLayer>)>,
 ) {
-    for (spawner_ent, mut spawner, pos, team) in &mut spawners {
+    for (spawner_ent, mut spawner, pos, layer_id, team) in &mut spawners {
+        let Ok((state, match_state)) = arenas.get(layer_id.0) else {
+            continue;
+        };
+
+        if *state != GameState::Match {
+            continue;
+        }
+
         if spawner.timer.tick(time.delta()).just_finished() {
             if let Some(team) = team {
                 if match_state.teams.get(&team.name).unwrap().bed_destroyed {
@@ -84,8 +143,6 @@ fn spawn_resources(
                 }
             }
 
-            let layer = layers.single();
-
             let mut pos = get_block_center(BlockPos::new(
                 pos.0.x as i32,
                 pos.0.y as i32,
@@ -106,7 +163,7 @@ fn spawn_resources(
             commands
                 .spawn(ItemEntityBundle {
                     item_stack: Stack(spawner.item.clone()),
-                    layer: EntityLayerId(layer),
+                    layer: *layer_id,
                     position: Position(pos),
                     entity_no_gravity: NoGravity(true),
                     ..Default::default()
@@ -0,0 +1,177 @@
+use std::time::Duration;
+
+use bevy_ecs::{
+    entity::Entity,
+    event::EventWriter,
+    system::{Query, Res},
+};
+use bevy_time::Time;
+use valence::{
+    app::{App, Plugin, Update},
+    client::Client,
+    entity::{EntityLayerId, Position},
+    message::SendMessage,
+    prelude::IntoSystemConfigs,
+    protocol::{sound::SoundCategory, Sound},
+    title::SetTitle,
+    BlockPos, BlockState, ChunkLayer,
+};
+
+use crate::{
+    arena::any_arena_in,
+    base::break_blocks::BedDestroyedEvent,
+    bedwars_config::{GameRules, MatchEventAction, WorldConfig},
+    r#match::{EndMatch, MatchState},
+    resource_spawners::ResourceSpawner,
+    GameState, Team,
+};
+
+/// Runs the events of the match schedule (see [`GameRules::match_schedule`])
+pub struct MatchSchedulePlugin;
+
+impl Plugin for MatchSchedulePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (run_match_schedule, show_next_event).run_if(any_arena_in(GameState::Match)),
+        );
+    }
+}
+
+fn run_match_schedule(
+    mut arenas: Query<(
+        Entity,
+        &GameState,
+        &mut MatchState,
+        &mut ChunkLayer,
+        &WorldConfig,
+    )>,
+    mut clients: Query<(&EntityLayerId, &mut Client, &Position)>,
+    mut spawners: Query<(&EntityLayerId, &mut ResourceSpawner)>,
+    mut bed_destroyed_writer: EventWriter<BedDestroyedEvent>,
+    mut end_match_writer: EventWriter<EndMatch>,
+    rules: Res<GameRules>,
+) {
+    for (arena, state, mut match_state, mut layer, bedwars_config) in &mut arenas {
+        if *state != GameState::Match {
+            continue;
+        }
+
+        let elapsed_secs = match_state.started.elapsed().as_secs();
+
+        while match_state
+            .next_event()
+            .is_some_and(|event| event.time_secs as u64 <= elapsed_secs)
+        {
+            let event = match_state.schedule.remove(0);
+            tracing::info!("Match event {} started", event.name);
+
+            for (layer_id, mut client, position) in &mut clients {
+                if layer_id.0 == arena {
+                    client.send_chat_message(format!("§6§l{}", event.name));
+                    client.play_sound(
+                        Sound::BlockNoteBlockPling,
+                        SoundCategory::Master,
+                        position.0,
+                        1.0,
+                        1.0,
+                    );
+                }
+            }
+
+            match event.action {
+                MatchEventAction::UpgradeSpawners {
+                    item,
+                    interval_multiplier,
+                } => {
+                    for (layer_id, mut spawner) in &mut spawners {
+                        if layer_id.0 == arena && spawner.item().item == item.0 {
+                            spawner.upgrade(interval_multiplier);
+                        }
+                    }
+                }
+                MatchEventAction::DestroyBeds => {
+                    for (team_name, bed_blocks) in &bedwars_config.beds {
+                        let bed_destroyed = match_state
+                            .teams
+                            .get(team_name)
+                            .map_or(true, |team| team.bed_destroyed);
+                        let Some(color) = bedwars_config.teams.get(team_name) else {
+                            continue;
+                        };
+
+                        if bed_destroyed {
+                            continue;
+                        }
+
+                        for bed_block in bed_blocks {
+                            let pos = &bed_block.pos;
+                            layer.set_block(BlockPos::new(pos.x, pos.y, pos.z), BlockState::AIR);
+                        }
+
+                        bed_destroyed_writer.send(BedDestroyedEvent {
+                            arena,
+                            attacker: None,
+                            team: Team {
+                                name: team_name.clone(),
+                                color: *color,
+                            },
+                        });
+                    }
+                }
+                MatchEventAction::SuddenDeath => {
+                    match_state.sudden_death = true;
+                }
+                MatchEventAction::EndMatch => {
+                    let winner = match_state
+                        .tie_break_winner(rules.tie_break)
+                        .and_then(|name| bedwars_config.teams.get_key_value(name))
+                        .map(|(name, color)| Team {
+                            name: name.clone(),
+                            color: *color,
+                        });
+
+                    end_match_writer.send(EndMatch { arena, winner });
+                }
+            }
+        }
+    }
+}
+
+/// Show the time until the next event in the action bar, updated every second
+fn show_next_event(
+    arenas: Query<(Entity, &GameState, &MatchState)>,
+    mut clients: Query<(&EntityLayerId, &mut Client)>,
+    time: Res<Time>,
+) {
+    for (arena, state, match_state) in &arenas {
+        if *state != GameState::Match {
+            continue;
+        }
+
+        let Some(event) = match_state.next_event() else {
+            continue;
+        };
+
+        let elapsed = match_state.started.elapsed();
+        if elapsed.as_secs() == elapsed.saturating_sub(time.delta()).as_secs() {
+            continue;
+        }
+
+        let remaining = Duration::from_secs(event.time_secs as u64)
+            .saturating_sub(elapsed)
+            .as_secs();
+        let msg = format!(
+            "§f{} §7in §a{}:{:02}",
+            event.name,
+            remaining / 60,
+            remaining % 60
+        );
+
+        for (layer_id, mut client) in &mut clients {
+            if layer_id.0 == arena {
+                client.set_action_bar(msg.clone());
+            }
+        }
+    }
+}
@@ -1,26 +1,34 @@
+use std::collections::HashSet;
+
 use bevy_ecs::{
     entity::Entity,
     event::EventReader,
-    query::{With, Without},
+    query::{Changed, With, Without},
+    removal_detection::RemovedComponents,
     system::{Commands, Query, Res},
+    world::OnRemove,
 };
-use bevy_state::{prelude::in_state, state::OnEnter};
 use valence::{
     app::{Plugin, Update},
     client::{Client, Username},
     entity::{player::PlayerEntity, EntityLayerId, HeadYaw, Look, Position},
     prelude::{
-        Component, DetectChangesMut, InteractEntityEvent, IntoSystemConfigs, Inventory,
-        InventoryKind,
+        Component, DetectChanges, DetectChangesMut, InteractEntityEvent, IntoSystemConfigs,
+        Inventory, InventoryKind, Trigger,
     },
     protocol::{sound::SoundCategory, Sound},
-    ChunkLayer, EntityLayer, ItemKind, ItemStack,
+    ItemKind, ItemStack,
 };
 
 use crate::{
+    arena::any_arena_in,
     base::death::IsDead,
-    bedwars_config::{ShopConfig, WorldConfig},
+    bedwars_config::{ShopConfig, ShopKind, ShopOffer, ShopPlacement, WorldConfig},
+    colors::TeamColor,
+    items::custom_item::CustomItems,
+    maps::MapShop,
     menu::{ItemMenu, MenuItemSelectEvent},
+    team_upgrades::UpgradeShop,
     utils::inventory::InventoryExt,
     GameState, Team,
 };
@@ -39,43 +47,69 @@ pub struct ShopPlugin;
 
 impl Plugin for ShopPlugin {
     fn build(&self, app: &mut valence::prelude::App) {
-        app.add_systems(OnEnter(GameState::Match), (init_shops,))
+        app.add_systems(Update, (init_shops, refresh_open_shops))
             .add_systems(
                 Update,
-                (on_shop_click, on_shop_open).run_if(in_state(GameState::Match)),
-            );
+                (on_shop_click, on_shop_open).run_if(any_arena_in(GameState::Match)),
+            )
+            .observe(close_shop_menu);
     }
 }
 
-/// Initialize the shops
+/// Initialize the shops of arenas that just started a match
 fn init_shops(
     mut commands: Commands,
-    bedwars_config: Res<WorldConfig>,
-    layers: Query<Entity, (With<ChunkLayer>, With<EntityLayer>)>,
+    arenas: Query<(Entity, &GameState, &WorldConfig), Changed<GameState>>,
 ) {
-    tracing::debug!("initializing shops");
-    let layer = layers.single();
-    for ((pos, yaw), team) in &bedwars_config.shops {
-        // TODO: why use villager as entity ?
-        let mut entity_commands = commands.spawn(valence::entity::villager::VillagerEntityBundle {
-            layer: EntityLayerId(layer),
-            position: valence::entity::Position(
-                [pos.x as f64 + 0.5, pos.y as f64, pos.z as f64 + 0.5].into(),
-            ),
-            head_yaw: HeadYaw(*yaw),
-            look: Look::new(*yaw, 0.0),
-            ..Default::default()
-        });
-
-        entity_commands.insert(Shop);
-
-        tracing::debug!("Initialized shop at {:?}", pos);
-        if let Some(team) = team {
-            let team_color = bedwars_config.teams.get(team.as_str()).unwrap();
-            entity_commands.insert(Team {
-                name: team.clone(),
-                color: *team_color,
-            });
+    for (arena, state, bedwars_config) in &arenas {
+        if *state != GameState::Match {
+            continue;
+        }
+
+        tracing::debug!("initializing shops");
+        for ShopPlacement {
+            pos,
+            yaw,
+            team,
+            kind,
+        } in &bedwars_config.shops
+        {
+            let team = match team {
+                Some(team) => {
+                    let Some(team_color) = bedwars_config.teams.get(team.as_str()) else {
+                        tracing::error!("Shop at {} belongs to unknown team {}", pos, team);
+                        continue;
+                    };
+
+                    Some(Team {
+                        name: team.clone(),
+                        color: *team_color,
+                    })
+                }
+                None => None,
+            };
+
+            // TODO: why use villager as entity ?
+            let mut entity_commands =
+                commands.spawn(valence::entity::villager::VillagerEntityBundle {
+                    layer: EntityLayerId(arena),
+                    position: valence::entity::Position(
+                        [pos.x as f64 + 0.5, pos.y as f64, pos.z as f64 + 0.5].into(),
+                    ),
+                    head_yaw: HeadYaw(*yaw),
+                    look: Look::new(*yaw, 0.0),
+                    ..Default::default()
+                });
+
+            entity_commands.insert(Shop);
+            if *kind == ShopKind::Upgrades {
+                entity_commands.insert(UpgradeShop);
+            }
+
+            tracing::debug!("Initialized shop at {:?}", pos);
+            if let Some(team) = team {
+                entity_commands.insert(team);
+            }
         }
     }
 }
@@ -85,12 +119,16 @@ fn init_shops(
 fn on_shop_open(
     mut commands: Commands,
     mut events: EventReader<InteractEntityEvent>,
-    mut players: Query<(Entity, &mut Client, &Username), (With<PlayerEntity>, Without<IsDead>)>,
-    shops: Query<&Position, With<Shop>>,
+    mut players: Query<
+        (Entity, &mut Client, &Username, &EntityLayerId),
+        (With<PlayerEntity>, Without<IsDead>),
+    >,
+    shops: Query<&Position, (With<Shop>, Without<UpgradeShop>)>,
     shop_config: Res<ShopConfig>,
+    map_shops: Query<&MapShop>,
 ) {
     for event in events.read() {
-        let Ok((player_ent, mut client, username)) = players.get_mut(event.client) else {
+        let Ok((player_ent, mut client, username, layer_id)) = players.get_mut(event.client) else {
             continue;
         };
 
@@ -98,7 +136,8 @@ fn on_shop_open(
             continue;
         };
 
-        let shop_menu = main_menu_from_shop_config(&shop_config);
+        let shop_config = arena_shop(&map_shops, layer_id.0, &shop_config);
+        let shop_menu = main_menu_from_shop_config(shop_config);
 
         tracing::debug!("{username} opened shop");
 
@@ -117,14 +156,110 @@ fn on_shop_open(
     }
 }
 
+fn close_shop_menu(trigger: Trigger<OnRemove, ItemMenu>, mut commands: Commands) {
+    commands.entity(trigger.entity()).remove::<ShopState>();
+}
+
+/// The shop of the map the arena is playing, or the global shop if the map has none
+pub fn arena_shop<'a>(
+    map_shops: &'a Query<&MapShop>,
+    arena: Entity,
+    shop_config: &'a ShopConfig,
+) -> &'a ShopConfig {
+    map_shops
+        .get(arena)
+        .map(|map_shop| &map_shop.0)
+        .unwrap_or(shop_config)
+}
+
 fn main_menu_from_shop_config(shop_config: &ShopConfig) -> ItemMenu {
-    let mut shop_menu = Inventory::new(InventoryKind::Generic9x5);
+    let mut shop_menu = Inventory::new(SHOP_INVENTORY_TYPE);
+    fill_main_menu(&mut shop_menu, shop_config);
+
+    ItemMenu::new(shop_menu)
+}
+
+/// Show the categories of the shop
+fn fill_main_menu(menu_inventory: &mut Inventory, shop_config: &ShopConfig) {
+    menu_inventory.clear();
     for (idx, (_category_name, (category_item, _))) in shop_config.shop_items.iter().enumerate() {
         // TODO: add category_name via nbt
-        shop_menu.set_slot(idx as u16, category_item.clone());
+        menu_inventory.set_slot(idx as u16, category_item.clone());
     }
+}
 
-    ItemMenu::new(shop_menu)
+/// Show the offers of a category, and a button to return to the main menu
+fn fill_category_menu(
+    menu_inventory: &mut Inventory,
+    offers: &[ShopOffer],
+    team_color: TeamColor,
+    custom_items: &CustomItems,
+) {
+    menu_inventory.clear();
+    for item in offers {
+        let Some(next_slot) = menu_inventory.first_empty_slot() else {
+            break;
+        };
+        let item_stack = custom_items.shop_stack(item);
+        // Convert to team color
+        let item_stack = team_color.to_team_item_stack(item_stack);
+
+        menu_inventory.set_slot(next_slot, item_stack);
+    }
+
+    menu_inventory.set_slot(
+        SHOP_INVENTORY_TYPE.slot_count() as u16 - 1,
+        ItemStack::new(ItemKind::Barrier, 1, None),
+    );
+}
+
+/// Rebuild the open shop menus after the global shop or the shop of their arena was reloaded
+fn refresh_open_shops(
+    mut inventories: Query<&mut Inventory, Without<Client>>,
+    mut clients: Query<(&mut ShopState, &ItemMenu, &EntityLayerId, Option<&Team>)>,
+    shop_config: Res<ShopConfig>,
+    map_shops: Query<&MapShop>,
+    changed_map_shops: Query<(), Changed<MapShop>>,
+    mut removed_map_shops: RemovedComponents<MapShop>,
+    custom_items: Res<CustomItems>,
+) {
+    let removed_map_shops: HashSet<Entity> = removed_map_shops.read().collect();
+
+    for (mut shop_state, item_menu, layer_id, team) in &mut clients {
+        let arena = layer_id.0;
+        if !shop_config.is_changed()
+            && !changed_map_shops.contains(arena)
+            && !removed_map_shops.contains(&arena)
+        {
+            continue;
+        }
+
+        let shop_config = arena_shop(&map_shops, layer_id.0, &shop_config);
+
+        let Some(inventory_ent) = item_menu.inventory_ent() else {
+            continue;
+        };
+
+        let Ok(mut menu_inventory) = inventories.get_mut(inventory_ent) else {
+            continue;
+        };
+
+        let offers = shop_state
+            .selected_category
+            .as_ref()
+            .and_then(|category| shop_config.shop_items.get(category));
+
+        match (offers, team) {
+            (Some((_, offers)), Some(team)) => {
+                fill_category_menu(&mut menu_inventory, offers, team.color, &custom_items)
+            }
+            _ => {
+                // The category does not exist anymore
+                shop_state.selected_category = None;
+                fill_main_menu(&mut menu_inventory, shop_config);
+            }
+        }
+    }
 }
 
 fn on_shop_click(
@@ -136,18 +271,22 @@ fn on_shop_click(
         &mut ShopState,
         &Team,
         &ItemMenu,
+        &EntityLayerId,
     )>,
     mut events: EventReader<MenuItemSelectEvent>,
     shop_config: Res<ShopConfig>,
-    bedwars_config: Res<WorldConfig>,
+    map_shops: Query<&MapShop>,
+    custom_items: Res<CustomItems>,
 ) {
     for event in events.read() {
-        let Ok((mut client, position, mut inventory, mut shop_state, team, item_menu)) =
+        let Ok((mut client, position, mut inventory, mut shop_state, team, item_menu, layer_id)) =
             clients.get_mut(event.client)
         else {
             continue;
         };
 
+        let shop_config = arena_shop(&map_shops, layer_id.0, &shop_config);
+
         let Some(inventory_ent) = item_menu.inventory_ent() else {
             continue;
         };
@@ -156,7 +295,7 @@ fn on_shop_click(
             continue;
         };
 
-        let team_color = bedwars_config.teams.get(&team.name).unwrap();
+        let team_color = team.color;
 
         let select_index = event.idx;
 
@@ -166,22 +305,8 @@ fn on_shop_click(
                 if let Some((category_name, (_, shop_items))) =
                     shop_config.shop_items.get_index(select_index as usize)
                 {
-                    menu_inventory.clear();
-
                     shop_state.selected_category = Some(category_name.clone());
-                    for item in shop_items {
-                        let next_slot = menu_inventory.first_empty_slot().unwrap();
-                        let item_stack: ItemStack = item.offer.clone().into();
-                        // Convert to team color
-                        let item_stack = team_color.to_team_item_stack(item_stack);
-
-                        menu_inventory.set_slot(next_slot, item_stack);
-                    }
-
-                    menu_inventory.set_slot(
-                        SHOP_INVENTORY_TYPE.slot_count() as u16 - 1,
-                        ItemStack::new(ItemKind::Barrier, 1, None),
-                    );
+                    fill_category_menu(&mut menu_inventory, shop_items, team_color, &custom_items);
                 }
             }
             Some(category) => {
@@ -190,19 +315,13 @@ fn on_shop_click(
                 if select_index == SHOP_INVENTORY_TYPE.slot_count() as u16 - 1 {
                     // return to the main menu
                     shop_state.selected_category = None;
-                    menu_inventory.clear();
-                    for (idx, (_category_name, (category_item, _))) in
-                        shop_config.shop_items.iter().enumerate()
-                    {
-                        // TODO: add category_name via nbt
-                        menu_inventory.set_slot(idx as u16, category_item.clone());
-                    }
+                    fill_main_menu(&mut menu_inventory, shop_config);
                 }
 
                 if let Some((_, shop_items)) = shop_config.shop_items.get(&category) {
                     if let Some(item_to_buy) = shop_items.get(select_index as usize) {
                         let price = item_to_buy.price.clone().into();
-                        let offer: ItemStack = item_to_buy.offer.clone().into();
+                        let offer = custom_items.shop_stack(item_to_buy);
                         // Convert to team color
                         let mut offer = team_color.to_team_item_stack(offer);
 
@@ -1,25 +1,26 @@
-use bevy_state::prelude::in_state;
 use valence::prelude::*;
 
-use crate::{bedwars_config::WorldConfig, GameState, Spectator};
+use crate::{bedwars_config::WorldConfig, Spectator};
 
 pub struct SpectatorPlugin;
 
 impl Plugin for SpectatorPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            Update,
-            (init_spectator,).run_if(not(in_state(GameState::Edit))),
-        );
+        app.add_systems(Update, (init_spectator,));
     }
 }
 
 fn init_spectator(
     // commands: Commands,
-    mut clients: Query<(Entity, &mut Position, &mut GameMode), Added<Spectator>>,
-    bedwars_config: Res<WorldConfig>,
+    mut clients: Query<(Entity, &EntityLayerId, &mut Position, &mut GameMode), Added<Spectator>>,
+    arenas: Query<&WorldConfig>,
 ) {
-    for (_player_ent, mut position, mut game_mode) in clients.iter_mut() {
+    for (_player_ent, layer_id, mut position, mut game_mode) in clients.iter_mut() {
+        // Arenas in edit mode have no world config yet
+        let Ok(bedwars_config) = arenas.get(layer_id.0) else {
+            continue;
+        };
+
         *game_mode = GameMode::Spectator;
         position.set(Into::<DVec3>::into(bedwars_config.spectator_spawn.clone()));
     }
@@ -0,0 +1,521 @@
+use bevy_ecs::{
+    change_detection::{DetectChanges, Ref},
+    entity::Entity,
+    event::EventReader,
+    query::{With, Without},
+    system::{Commands, Local, Query, Res},
+    world::OnRemove,
+};
+use bevy_time::{Time, Timer, TimerMode};
+use valence::{
+    app::{App, Plugin, Update},
+    client::{Client, Username},
+    entity::{living::Health, player::PlayerEntity, EntityLayerId, Position},
+    math::DVec3,
+    message::SendMessage,
+    nbt::{compound, List},
+    prelude::{
+        Component, InteractEntityEvent, IntoSystemConfigs, Inventory, InventoryKind, Trigger,
+    },
+    protocol::{sound::SoundCategory, Sound},
+    ItemKind, ItemStack,
+};
+
+use crate::{
+    arena::any_arena_in,
+    base::{
+        armor::ItemKindExtArmor,
+        death::IsDead,
+        enchantments::{Enchantment, ItemStackExtEnchantments},
+    },
+    bedwars_config::{ShopConfig, TeamUpgradeConfig, WorldConfig},
+    maps::MapShop,
+    menu::{ItemMenu, MenuItemSelectEvent},
+    r#match::{MatchState, TeamState},
+    resource_spawners::ResourceSpawner,
+    shop::arena_shop,
+    traps::Trap,
+    utils::{inventory::InventoryExt, item_kind::ItemKindExtWeapons},
+    GameState, Team,
+};
+
+const UPGRADE_SHOP_INVENTORY_TYPE: InventoryKind = InventoryKind::Generic9x4;
+/// Slots of the upgrades in the shop, in the order of [`TeamUpgrade::ALL`]
+const UPGRADE_SLOTS: [u16; 4] = [10, 12, 14, 16];
+/// Slots of the traps in the shop, in the order of [`Trap::ALL`]
+const TRAP_SLOTS: [u16; 4] = [19, 21, 23, 25];
+/// First slot of the trap queue display
+const TRAP_QUEUE_SLOT: u16 = 30;
+/// Every forge tier multiplies the interval of the team resource spawners with this
+const FORGE_INTERVAL_MULTIPLIER: f32 = 0.75;
+/// Distance to the team spawn in which the heal pool heals players
+const HEAL_POOL_RADIUS: f64 = 10.0;
+/// Time it takes the heal pool to heal one health point
+const HEAL_POOL_SECS_PER_HP: f32 = 2.0;
+
+/// Upgrades that are bought for the whole team, they are kept until the match ends
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TeamUpgrade {
+    /// Sharpness for all swords of the team
+    Sharpness,
+    /// Protection for all armor of the team
+    Protection,
+    /// The team resource spawners spawn faster
+    Forge,
+    /// Players regenerate health near their team spawn
+    HealPool,
+}
+
+impl TeamUpgrade {
+    pub const ALL: [TeamUpgrade; 4] = [
+        TeamUpgrade::Sharpness,
+        TeamUpgrade::Protection,
+        TeamUpgrade::Forge,
+        TeamUpgrade::HealPool,
+    ];
+
+    /// Name of the upgrade in the shop config
+    pub fn config_name(&self) -> &'static str {
+        match self {
+            TeamUpgrade::Sharpness => "sharpness",
+            TeamUpgrade::Protection => "protection",
+            TeamUpgrade::Forge => "forge",
+            TeamUpgrade::HealPool => "heal_pool",
+        }
+    }
+
+    fn display_name(&self) -> &'static str {
+        match self {
+            TeamUpgrade::Sharpness => "Sharpened Swords",
+            TeamUpgrade::Protection => "Reinforced Armor",
+            TeamUpgrade::Forge => "Forge",
+            TeamUpgrade::HealPool => "Heal Pool",
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            TeamUpgrade::Sharpness => "Your team's swords get sharpness",
+            TeamUpgrade::Protection => "Your team's armor gets protection",
+            TeamUpgrade::Forge => "Resources spawn faster at your base",
+            TeamUpgrade::HealPool => "Regenerate health near your base",
+        }
+    }
+
+    fn icon(&self) -> ItemKind {
+        match self {
+            TeamUpgrade::Sharpness => ItemKind::IronSword,
+            TeamUpgrade::Protection => ItemKind::IronChestplate,
+            TeamUpgrade::Forge => ItemKind::Furnace,
+            TeamUpgrade::HealPool => ItemKind::Beacon,
+        }
+    }
+}
+
+/// Something that can be bought in the upgrade shop
+#[derive(Debug, Clone, Copy)]
+enum UpgradeShopItem {
+    Upgrade(TeamUpgrade),
+    Trap(Trap),
+}
+
+impl UpgradeShopItem {
+    fn from_slot(slot: u16) -> Option<Self> {
+        if let Some(idx) = UPGRADE_SLOTS.iter().position(|s| *s == slot) {
+            return Some(Self::Upgrade(TeamUpgrade::ALL[idx]));
+        }
+
+        TRAP_SLOTS
+            .iter()
+            .position(|s| *s == slot)
+            .map(|idx| Self::Trap(Trap::ALL[idx]))
+    }
+}
+
+/// A shop villager that sells team upgrades instead of items
+#[derive(Debug, Clone, Component)]
+pub struct UpgradeShop;
+
+/// Attached to players that have the upgrade shop open
+#[derive(Debug, Clone, Component)]
+pub struct UpgradeShopMenu;
+
+pub struct TeamUpgradesPlugin;
+
+impl Plugin for TeamUpgradesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                open_upgrade_shop,
+                buy_upgrade,
+                apply_team_enchantments,
+                heal_pool,
+            )
+                .run_if(any_arena_in(GameState::Match)),
+        )
+        .observe(close_upgrade_shop_menu);
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn open_upgrade_shop(
+    mut commands: Commands,
+    mut events: EventReader<InteractEntityEvent>,
+    mut players: Query<
+        (Entity, &mut Client, &EntityLayerId, &Team),
+        (With<PlayerEntity>, Without<IsDead>),
+    >,
+    shops: Query<&Position, With<UpgradeShop>>,
+    arenas: Query<&MatchState>,
+    shop_config: Res<ShopConfig>,
+    map_shops: Query<&MapShop>,
+) {
+    for event in events.read() {
+        let Ok((player, mut client, layer_id, team)) = players.get_mut(event.client) else {
+            continue;
+        };
+
+        let Ok(shop_position) = shops.get(event.entity) else {
+            continue;
+        };
+
+        let Some(team_state) = arenas
+            .get(layer_id.0)
+            .ok()
+            .and_then(|match_state| match_state.teams.get(&team.name))
+        else {
+            continue;
+        };
+
+        let config = &arena_shop(&map_shops, layer_id.0, &shop_config).team_upgrades;
+        let mut menu_inventory = Inventory::new(UPGRADE_SHOP_INVENTORY_TYPE);
+        fill_upgrade_menu(&mut menu_inventory, config, team_state);
+
+        client.play_sound(
+            Sound::EntityVillagerAmbient,
+            SoundCategory::Neutral,
+            shop_position.0,
+            0.5,
+            1.0,
+        );
+
+        commands
+            .entity(player)
+            .insert(ItemMenu::new(menu_inventory))
+            .insert(UpgradeShopMenu);
+    }
+}
+
+/// Show every upgrade with its tier and the price of the next tier
+fn fill_upgrade_menu(
+    menu_inventory: &mut Inventory,
+    config: &TeamUpgradeConfig,
+    team_state: &TeamState,
+) {
+    menu_inventory.clear();
+
+    for (upgrade, slot) in TeamUpgrade::ALL.iter().zip(UPGRADE_SLOTS) {
+        let prices = config.prices(*upgrade);
+        if prices.is_empty() {
+            // The upgrade is disabled
+            continue;
+        }
+
+        let tier = team_state.upgrade_tier(*upgrade);
+        let price = match prices.get(tier as usize) {
+            Some(price) => format!("§7Cost: §6{} {}", price, config.currency.0.to_str()),
+            None => "§aMaxed".to_string(),
+        };
+
+        let lore = [
+            format!("§7{}", upgrade.description()),
+            format!("§7Tier: §f{}/{}", tier, prices.len()),
+            price,
+        ];
+
+        menu_inventory.set_slot(
+            slot,
+            menu_item(upgrade.icon(), upgrade.display_name(), &lore),
+        );
+    }
+
+    if config.traps.is_empty() {
+        // Traps are disabled
+        return;
+    }
+
+    let trap_price = match config.traps.get(team_state.traps.len()) {
+        Some(price) => format!("§7Cost: §6{} {}", price, config.currency.0.to_str()),
+        None => "§cThe trap queue is full".to_string(),
+    };
+
+    for (trap, slot) in Trap::ALL.iter().zip(TRAP_SLOTS) {
+        let lore = [format!("§7{}", trap.description()), trap_price.clone()];
+        menu_inventory.set_slot(slot, menu_item(trap.icon(), trap.display_name(), &lore));
+    }
+
+    for (idx, trap) in team_state.traps.iter().enumerate() {
+        let slot = TRAP_QUEUE_SLOT + idx as u16;
+        if slot >= menu_inventory.slot_count() {
+            break;
+        }
+
+        let lore = [format!(
+            "§7Trap #{}, triggered when an enemy comes close to your bed",
+            idx + 1
+        )];
+        menu_inventory.set_slot(slot, menu_item(trap.icon(), trap.display_name(), &lore));
+    }
+}
+
+fn menu_item(kind: ItemKind, name: &str, lore: &[String]) -> ItemStack {
+    ItemStack::new(
+        kind,
+        1,
+        Some(compound! {
+            "display" => compound! {
+                "Name" => format!("{{\"text\":\"§e{}\",\"italic\":false}}", name),
+                "Lore" => List::String(
+                    lore.iter()
+                        .map(|line| format!("{{\"text\":\"{}\",\"italic\":false}}", line))
+                        .collect()
+                ),
+            }
+        }),
+    )
+}
+
+fn close_upgrade_shop_menu(trigger: Trigger<OnRemove, ItemMenu>, mut commands: Commands) {
+    commands
+        .entity(trigger.entity())
+        .remove::<UpgradeShopMenu>();
+}
+
+#[allow(clippy::type_complexity)]
+#[allow(clippy::too_many_arguments)]
+fn buy_upgrade(
+    mut events: EventReader<MenuItemSelectEvent>,
+    mut clients: Query<
+        (
+            &mut Client,
+            &Username,
+            &Position,
+            &mut Inventory,
+            &Team,
+            &EntityLayerId,
+        ),
+        With<Client>,
+    >,
+    menus: Query<(&EntityLayerId, &Team, &ItemMenu), With<UpgradeShopMenu>>,
+    mut inventories: Query<&mut Inventory, Without<Client>>,
+    mut arenas: Query<&mut MatchState>,
+    mut spawners: Query<(&EntityLayerId, &Team, &mut ResourceSpawner)>,
+    shop_config: Res<ShopConfig>,
+    map_shops: Query<&MapShop>,
+) {
+    for event in events.read() {
+        if !menus.contains(event.client) {
+            continue;
+        }
+
+        let Some(item) = UpgradeShopItem::from_slot(event.idx) else {
+            continue;
+        };
+
+        let Ok((mut client, username, position, mut inventory, team, layer_id)) =
+            clients.get_mut(event.client)
+        else {
+            continue;
+        };
+        let (username, team, arena) = (username.0.clone(), team.clone(), layer_id.0);
+
+        let Ok(mut match_state) = arenas.get_mut(arena) else {
+            continue;
+        };
+
+        let Some(team_state) = match_state.teams.get_mut(&team.name) else {
+            continue;
+        };
+
+        let config = &arena_shop(&map_shops, arena, &shop_config).team_upgrades;
+        let (prices, bought, maxed_msg) = match item {
+            UpgradeShopItem::Upgrade(upgrade) => (
+                config.prices(upgrade),
+                team_state.upgrade_tier(upgrade) as usize,
+                "§cThis upgrade is already maxed",
+            ),
+            UpgradeShopItem::Trap(_) => (
+                config.traps.as_slice(),
+                team_state.traps.len(),
+                "§cThe trap queue is full",
+            ),
+        };
+
+        let Some(price) = prices.get(bought) else {
+            if !prices.is_empty() {
+                client.send_chat_message(maxed_msg);
+            }
+            continue;
+        };
+
+        let price = ItemStack::new(config.currency.0, *price, None);
+        if !inventory.try_remove_all(&price) {
+            client.play_sound(
+                Sound::BlockNoteBlockBass,
+                SoundCategory::Master,
+                position.0,
+                1.0,
+                0.8,
+            );
+            continue;
+        }
+
+        client.play_sound(
+            Sound::BlockNoteBlockBell,
+            SoundCategory::Master,
+            position.0,
+            1.0,
+            1.8,
+        );
+
+        let msg = match item {
+            UpgradeShopItem::Upgrade(upgrade) => {
+                let tier = team_state.upgrade_tier(upgrade) + 1;
+                team_state.upgrades.insert(upgrade, tier);
+
+                if upgrade == TeamUpgrade::Forge {
+                    for (spawner_layer_id, spawner_team, mut spawner) in &mut spawners {
+                        if spawner_layer_id.0 == arena && *spawner_team == team {
+                            spawner.upgrade(FORGE_INTERVAL_MULTIPLIER);
+                        }
+                    }
+                }
+
+                format!(
+                    "§a{} bought §6{} {}",
+                    username,
+                    upgrade.display_name(),
+                    tier
+                )
+            }
+            UpgradeShopItem::Trap(trap) => {
+                team_state.traps.push_back(trap);
+                format!("§a{} bought §6{}", username, trap.display_name())
+            }
+        };
+
+        // Update the open upgrade shops of the team
+        for (menu_layer_id, menu_team, item_menu) in &menus {
+            if menu_layer_id.0 != arena || *menu_team != team {
+                continue;
+            }
+
+            let Some(mut menu_inventory) = item_menu
+                .inventory_ent()
+                .and_then(|inventory_ent| inventories.get_mut(inventory_ent).ok())
+            else {
+                continue;
+            };
+
+            fill_upgrade_menu(&mut menu_inventory, config, team_state);
+        }
+
+        for (mut client, _, _, _, client_team, client_layer_id) in &mut clients {
+            if client_layer_id.0 == arena && *client_team == team {
+                client.send_chat_message(&msg);
+            }
+        }
+    }
+}
+
+/// Add the sharpness and protection upgrades to the swords and armor of the team,
+/// this also enchants items that are bought (or picked up) later on
+fn apply_team_enchantments(
+    mut players: Query<(&EntityLayerId, &Team, &mut Inventory), With<Client>>,
+    arenas: Query<Ref<MatchState>>,
+) {
+    for (layer_id, team, mut inventory) in &mut players {
+        let Ok(match_state) = arenas.get(layer_id.0) else {
+            continue;
+        };
+
+        if !inventory.is_changed() && !match_state.is_changed() {
+            continue;
+        }
+
+        let Some(team_state) = match_state.teams.get(&team.name) else {
+            continue;
+        };
+
+        let sharpness = team_state.upgrade_tier(TeamUpgrade::Sharpness);
+        let protection = team_state.upgrade_tier(TeamUpgrade::Protection);
+
+        for idx in 0..inventory.slot_count() {
+            let stack = inventory.slot(idx);
+
+            let (enchantment, level) = if stack.item.is_sword() {
+                (Enchantment::Sharpness, sharpness)
+            } else if stack.item.is_armor() {
+                (Enchantment::Protection, protection)
+            } else {
+                continue;
+            };
+
+            // Items that already have a higher level are kept
+            if stack.enchantments().get(&enchantment).copied().unwrap_or(0) >= level {
+                continue;
+            }
+
+            let mut stack = stack.clone();
+            stack.set_enchantment(enchantment, level);
+            inventory.set_slot(idx, stack);
+        }
+    }
+}
+
+struct HealPoolTimer(Timer);
+
+impl Default for HealPoolTimer {
+    fn default() -> Self {
+        Self(Timer::from_seconds(
+            HEAL_POOL_SECS_PER_HP,
+            TimerMode::Repeating,
+        ))
+    }
+}
+
+fn heal_pool(
+    mut players: Query<(&EntityLayerId, &Team, &Position, &mut Health), Without<IsDead>>,
+    arenas: Query<(&MatchState, &WorldConfig)>,
+    mut timer: Local<HealPoolTimer>,
+    time: Res<Time>,
+) {
+    if !timer.0.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    for (layer_id, team, position, mut health) in &mut players {
+        let Ok((match_state, bedwars_config)) = arenas.get(layer_id.0) else {
+            continue;
+        };
+
+        let has_heal_pool = match_state
+            .teams
+            .get(&team.name)
+            .is_some_and(|team_state| team_state.upgrade_tier(TeamUpgrade::HealPool) > 0);
+
+        let Some(team_spawn) = bedwars_config.spawns.get(&team.name) else {
+            continue;
+        };
+
+        if !has_heal_pool
+            || health.0 >= 20.0
+            || position.0.distance(Into::<DVec3>::into(team_spawn.clone())) > HEAL_POOL_RADIUS
+        {
+            continue;
+        }
+
+        health.0 = (health.0 + 1.0).min(20.0);
+    }
+}
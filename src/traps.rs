@@ -0,0 +1,260 @@
+use std::time::{Duration, Instant};
+
+use bevy_ecs::{
+    entity::Entity,
+    query::{With, Without},
+    system::{Query, Res},
+};
+use valence::{
+    app::{App, Plugin, Update},
+    client::{Client, Username},
+    entity::{EntityLayerId, Position},
+    math::DVec3,
+    message::SendMessage,
+    prelude::IntoSystemConfigs,
+    protocol::{sound::SoundCategory, Sound},
+    title::SetTitle,
+    ItemKind,
+};
+
+use crate::{
+    arena::any_arena_in,
+    base::death::IsDead,
+    bedwars_config::{BedBlock, GameRules, WorldConfig},
+    items::effects::{ActiveEffects, StatusEffect},
+    r#match::{MatchState, TeamState},
+    GameState, Spectator, Team,
+};
+
+/// Time after a trap was triggered until the next trap of the team can be triggered
+const TRAP_COOLDOWN: Duration = Duration::from_secs(20);
+/// Duration of the effects given by traps
+const TRAP_EFFECT_DURATION: Duration = Duration::from_secs(8);
+/// Duration of the counter-offensive effects for the defenders
+const COUNTER_OFFENSIVE_DURATION: Duration = Duration::from_secs(15);
+
+/// Traps are bought for a team in the upgrade shop and queued,
+/// the first trap is triggered when an enemy comes close to the bed of the team
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Trap {
+    /// Blindness and slowness for the enemy
+    Blindness,
+    /// Warns the team with the name of the enemy
+    Alarm,
+    /// Speed and jump boost for the team near their bed
+    CounterOffensive,
+    /// Mining fatigue for the enemy
+    MinerFatigue,
+}
+
+impl Trap {
+    pub const ALL: [Trap; 4] = [
+        Trap::Blindness,
+        Trap::Alarm,
+        Trap::CounterOffensive,
+        Trap::MinerFatigue,
+    ];
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            Trap::Blindness => "It's a Trap!",
+            Trap::Alarm => "Alarm Trap",
+            Trap::CounterOffensive => "Counter-Offensive Trap",
+            Trap::MinerFatigue => "Miner Fatigue Trap",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Trap::Blindness => "Blinds and slows down the enemy",
+            Trap::Alarm => "Reveals the name of the enemy",
+            Trap::CounterOffensive => "Speed and jump boost for your team near your bed",
+            Trap::MinerFatigue => "Gives the enemy mining fatigue",
+        }
+    }
+
+    pub fn icon(&self) -> ItemKind {
+        match self {
+            Trap::Blindness => ItemKind::Tripwire,
+            Trap::Alarm => ItemKind::RedstoneTorch,
+            Trap::CounterOffensive => ItemKind::Feather,
+            Trap::MinerFatigue => ItemKind::IronPickaxe,
+        }
+    }
+}
+
+impl TeamState {
+    /// Remove the next trap of the queue, if the last trap is not on cooldown anymore
+    pub fn trigger_trap(&mut self, now: Instant) -> Option<Trap> {
+        if self
+            .last_trap_triggered
+            .is_some_and(|last| now.duration_since(last) < TRAP_COOLDOWN)
+        {
+            return None;
+        }
+
+        let trap = self.traps.pop_front()?;
+        self.last_trap_triggered = Some(now);
+        Some(trap)
+    }
+}
+
+pub struct TrapPlugin;
+
+impl Plugin for TrapPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (trigger_traps,).run_if(any_arena_in(GameState::Match)),
+        );
+    }
+}
+
+/// Whether a position is within `radius` of any block of the bed
+fn is_near_bed(position: DVec3, bed_blocks: &[BedBlock], radius: f64) -> bool {
+    bed_blocks.iter().any(|bed_block| {
+        let center = DVec3::from(bed_block.pos.clone()) + DVec3::splat(0.5);
+        position.distance(center) <= radius
+    })
+}
+
+#[allow(clippy::type_complexity)]
+fn trigger_traps(
+    intruders: Query<
+        (Entity, &EntityLayerId, &Position, &Team, &Username),
+        (With<Client>, Without<IsDead>, Without<Spectator>),
+    >,
+    mut clients: Query<(
+        &EntityLayerId,
+        &Position,
+        &mut Client,
+        Option<&Team>,
+        Option<&mut ActiveEffects>,
+    )>,
+    mut arenas: Query<(&GameState, &mut MatchState, &WorldConfig)>,
+    rules: Res<GameRules>,
+) {
+    for (intruder, layer_id, position, intruder_team, username) in &intruders {
+        let Ok((state, mut match_state, bedwars_config)) = arenas.get_mut(layer_id.0) else {
+            continue;
+        };
+
+        if *state != GameState::Match {
+            continue;
+        }
+
+        for (team_name, bed_blocks) in &bedwars_config.beds {
+            if *team_name == intruder_team.name
+                || !is_near_bed(position.0, bed_blocks, rules.trap_radius)
+            {
+                continue;
+            }
+
+            // Check first, so the match state is not marked as changed every tick
+            if match_state.teams.get(team_name).map_or(true, |team_state| {
+                team_state.traps.is_empty() || team_state.players_alive.is_empty()
+            }) {
+                continue;
+            }
+
+            let Some(trap) = match_state
+                .teams
+                .get_mut(team_name)
+                .and_then(|team_state| team_state.trigger_trap(Instant::now()))
+            else {
+                continue;
+            };
+
+            tracing::debug!(
+                "{} triggered the {:?} trap of team {}",
+                username.0,
+                trap,
+                team_name
+            );
+
+            if let Ok((_, _, mut client, _, effects)) = clients.get_mut(intruder) {
+                client.send_chat_message(format!("§cYou triggered a trap of team {}", team_name));
+
+                if let Some(mut effects) = effects {
+                    match trap {
+                        Trap::Blindness => {
+                            effects.add(StatusEffect::Blindness, 0, TRAP_EFFECT_DURATION);
+                            effects.add(StatusEffect::Slowness, 0, TRAP_EFFECT_DURATION);
+                        }
+                        Trap::MinerFatigue => {
+                            effects.add(StatusEffect::MiningFatigue, 0, TRAP_EFFECT_DURATION);
+                        }
+                        Trap::Alarm | Trap::CounterOffensive => {}
+                    }
+                }
+            }
+
+            let subtitle = match trap {
+                Trap::Alarm => format!(
+                    "§7{}{} §7entered your base",
+                    intruder_team.color.text_color(),
+                    username.0
+                ),
+                _ => format!("§7{}", trap.display_name()),
+            };
+
+            for (client_layer_id, client_position, mut client, client_team, effects) in &mut clients
+            {
+                if client_layer_id.0 != layer_id.0
+                    || client_team.map_or(true, |team| team.name != *team_name)
+                {
+                    continue;
+                }
+
+                client.set_title("§cTrap triggered!");
+                client.set_subtitle(subtitle.clone());
+                client.play_sound(
+                    if trap == Trap::Alarm {
+                        Sound::BlockBellUse
+                    } else {
+                        Sound::BlockNoteBlockPling
+                    },
+                    SoundCategory::Master,
+                    client_position.0,
+                    1.0,
+                    0.5,
+                );
+
+                if trap != Trap::CounterOffensive
+                    || !is_near_bed(client_position.0, bed_blocks, rules.trap_radius)
+                {
+                    continue;
+                }
+
+                if let Some(mut effects) = effects {
+                    effects.add(StatusEffect::Speed, 0, COUNTER_OFFENSIVE_DURATION);
+                    effects.add(StatusEffect::JumpBoost, 1, COUNTER_OFFENSIVE_DURATION);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trigger_trap() {
+        let mut team_state = TeamState::default();
+        let now = Instant::now();
+        assert_eq!(team_state.trigger_trap(now), None);
+
+        team_state.traps.push_back(Trap::Alarm);
+        team_state.traps.push_back(Trap::Blindness);
+        assert_eq!(team_state.trigger_trap(now), Some(Trap::Alarm));
+
+        // The next trap is on cooldown
+        assert_eq!(team_state.trigger_trap(now + Duration::from_secs(1)), None);
+        assert_eq!(
+            team_state.trigger_trap(now + TRAP_COOLDOWN),
+            Some(Trap::Blindness)
+        );
+        assert!(team_state.traps.is_empty());
+    }
+}
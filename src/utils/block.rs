@@ -16,7 +16,11 @@
 //     }
 // }
 
-use valence::{math::DVec3, BlockPos};
+use valence::{
+    block::{PropName, PropValue},
+    math::DVec3,
+    BlockKind, BlockPos, BlockState,
+};
 
 pub fn get_block_center(pos: BlockPos) -> DVec3 {
     let x = if pos.x < 0 {
@@ -33,3 +37,116 @@ pub fn get_block_center(pos: BlockPos) -> DVec3 {
 
     DVec3::new(x, y, z)
 }
+
+/// Explosions can not destroy or pass through blocks with this blast resistance
+pub const BLAST_PROOF: f32 = f32::INFINITY;
+
+/// Blast resistance of a block, like in vanilla except that glass is blast proof
+pub fn blast_resistance(state: BlockState) -> f32 {
+    let kind = state.to_kind();
+    let name = kind.to_str();
+
+    match kind {
+        _ if name.contains("glass") => BLAST_PROOF,
+        BlockKind::Obsidian | BlockKind::Bedrock | BlockKind::Barrier => BLAST_PROOF,
+        BlockKind::EndStone => 9.0,
+        BlockKind::Ladder => 0.4,
+        BlockKind::Tnt => 0.0,
+        _ if name.ends_with("_wool") => 0.8,
+        _ if name.ends_with("_bed") => 0.2,
+        _ if name.ends_with("_planks") => 3.0,
+        _ if name.ends_with("terracotta") => 4.2,
+        _ => 6.0,
+    }
+}
+
+/// Format a block state like `minecraft:red_bed[facing=north,part=head]`.
+/// Unlike the raw state id, this does not change between minecraft versions.
+pub fn block_state_to_string(state: BlockState) -> String {
+    let kind = state.to_kind();
+
+    let props = kind
+        .props()
+        .iter()
+        .filter_map(|name| {
+            state
+                .get(*name)
+                .map(|value| format!("{}={}", name.to_str(), value.to_str()))
+        })
+        .collect::<Vec<_>>();
+
+    if props.is_empty() {
+        format!("minecraft:{}", kind.to_str())
+    } else {
+        format!("minecraft:{}[{}]", kind.to_str(), props.join(","))
+    }
+}
+
+/// Parse a block state in the format of [`block_state_to_string`].
+/// Properties that are not specified will use their default value.
+pub fn parse_block_state(s: &str) -> Result<BlockState, String> {
+    let (name, props) = match s.split_once('[') {
+        Some((name, props)) => (
+            name,
+            props
+                .strip_suffix(']')
+                .ok_or_else(|| format!("missing `]` in `{s}`"))?,
+        ),
+        None => (s, ""),
+    };
+
+    let name = name.strip_prefix("minecraft:").unwrap_or(name);
+    let kind = BlockKind::from_str(name).ok_or_else(|| format!("unknown block `{name}`"))?;
+    let mut state = kind.to_state();
+
+    for prop in props.split(',').filter(|prop| !prop.is_empty()) {
+        let Some((prop_name, prop_value)) = prop.split_once('=') else {
+            return Err(format!("invalid property `{prop}`, expected `name=value`"));
+        };
+
+        let prop_name = PropName::from_str(prop_name.trim())
+            .filter(|prop_name| state.get(*prop_name).is_some())
+            .ok_or_else(|| format!("block `{name}` has no property `{prop_name}`"))?;
+
+        let value = PropValue::from_str(prop_value.trim())
+            .filter(|value| state.set(prop_name, *value).get(prop_name) == Some(*value))
+            .ok_or_else(|| {
+                format!(
+                    "invalid value `{prop_value}` for property `{}` of `{name}`",
+                    prop_name.to_str()
+                )
+            })?;
+
+        state = state.set(prop_name, value);
+    }
+
+    Ok(state)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_state_string_roundtrip() {
+        let state = BlockState::RED_BED
+            .set(PropName::Facing, PropValue::North)
+            .set(PropName::Part, PropValue::Head);
+
+        let s = block_state_to_string(state);
+
+        assert!(s.starts_with("minecraft:red_bed["));
+        assert!(s.contains("facing=north"));
+        assert!(s.contains("part=head"));
+        assert_eq!(parse_block_state(&s), Ok(state));
+    }
+
+    #[test]
+    fn test_parse_block_state() {
+        assert_eq!(parse_block_state("minecraft:stone"), Ok(BlockState::STONE));
+        assert_eq!(parse_block_state("stone"), Ok(BlockState::STONE));
+        assert!(parse_block_state("minecraft:red_bed[facing=up]").is_err());
+        assert!(parse_block_state("minecraft:stone[facing=north]").is_err());
+        assert!(parse_block_state("minecraft:not_a_block").is_err());
+    }
+}
@@ -96,6 +96,7 @@ impl ItemKindExtColor for ItemKind {
 }
 
 pub trait ItemKindExtWeapons {
+    fn is_sword(&self) -> bool;
     fn damage(&self) -> f32;
     fn knockback(&self) -> f32;
     fn burn_duration(&self) -> Option<u32>;
@@ -103,6 +104,18 @@ pub trait ItemKindExtWeapons {
 }
 
 impl ItemKindExtWeapons for ItemKind {
+    fn is_sword(&self) -> bool {
+        matches!(
+            self,
+            ItemKind::WoodenSword
+                | ItemKind::StoneSword
+                | ItemKind::IronSword
+                | ItemKind::GoldenSword
+                | ItemKind::DiamondSword
+                | ItemKind::NetheriteSword
+        )
+    }
+
     fn damage(&self) -> f32 {
         match self {
             ItemKind::WoodenSword => 4.0,
@@ -22,6 +22,92 @@ pub fn aabb_full_block_intersections(aabb: &Aabb) -> Vec<BlockPos> {
     blocks
 }
 
+/// Returns the blocks a ray passes through, in the order they are passed (DDA voxel traversal).
+/// Returns no blocks if the direction is zero or not finite.
+pub fn ray_blocks(origin: DVec3, direction: DVec3, max_distance: f64) -> Vec<BlockPos> {
+    if direction.length_squared() == 0.0 || !direction.is_finite() || !max_distance.is_finite() {
+        return vec![];
+    }
+
+    let direction = direction.normalize();
+    let mut pos = origin.floor();
+    let step = DVec3::new(
+        direction.x.signum(),
+        direction.y.signum(),
+        direction.z.signum(),
+    );
+
+    // Distance along the ray to cross one block on each axis
+    let t_delta = DVec3::new(
+        (1.0 / direction.x).abs(),
+        (1.0 / direction.y).abs(),
+        (1.0 / direction.z).abs(),
+    );
+
+    // Distance along the ray to the next block border on each axis
+    let border = |origin: f64, pos: f64, direction: f64| {
+        if direction > 0.0 {
+            (pos + 1.0 - origin) / direction
+        } else if direction < 0.0 {
+            (origin - pos) / -direction
+        } else {
+            f64::INFINITY
+        }
+    };
+
+    let mut t_max = DVec3::new(
+        border(origin.x, pos.x, direction.x),
+        border(origin.y, pos.y, direction.y),
+        border(origin.z, pos.z, direction.z),
+    );
+
+    let mut blocks = vec![BlockPos::new(pos.x as i32, pos.y as i32, pos.z as i32)];
+
+    loop {
+        let t = t_max.min_element();
+        if t > max_distance {
+            break;
+        }
+
+        if t == t_max.x {
+            pos.x += step.x;
+            t_max.x += t_delta.x;
+        } else if t == t_max.y {
+            pos.y += step.y;
+            t_max.y += t_delta.y;
+        } else {
+            pos.z += step.z;
+            t_max.z += t_delta.z;
+        }
+
+        blocks.push(BlockPos::new(pos.x as i32, pos.y as i32, pos.z as i32));
+    }
+
+    blocks
+}
+
+/// Distance along a ray to the point where it enters the AABB, if it hits the AABB
+/// within `max_distance`. Rays starting inside of the AABB do not hit it.
+pub fn ray_aabb_intersection(
+    origin: DVec3,
+    direction: DVec3,
+    max_distance: f64,
+    aabb: &Aabb,
+) -> Option<f64> {
+    let ray = direction.normalize() * max_distance;
+    let (entry, (nx, ny, nz)) = collide(
+        &Aabb::new(origin, origin),
+        Vec3::new(ray.x as f32, ray.y as f32, ray.z as f32),
+        aabb,
+    );
+
+    if nx.is_none() && ny.is_none() && nz.is_none() {
+        return None;
+    }
+
+    Some(entry.max(0.0) * max_distance)
+}
+
 #[derive(Debug)]
 pub enum CollisionObject {
     Block { pos: DVec3, face_normal: Direction },
@@ -182,6 +268,65 @@ pub fn collide(hb1: &Aabb, velocity: Vec3, hb2: &Aabb) -> CollisionResult {
 pub mod tests {
     use super::*;
 
+    #[test]
+    fn test_ray_blocks() {
+        let blocks = ray_blocks(DVec3::new(0.5, 0.5, 0.5), DVec3::new(1.0, 0.0, 0.5), 2.5);
+        assert_eq!(
+            blocks,
+            vec![
+                BlockPos::new(0, 0, 0),
+                BlockPos::new(1, 0, 0),
+                BlockPos::new(1, 0, 1),
+                BlockPos::new(2, 0, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ray_blocks_diagonal() {
+        let blocks = ray_blocks(DVec3::new(0.5, 0.5, 0.5), DVec3::new(1.0, 1.0, 0.0), 1.5);
+        assert_eq!(
+            blocks,
+            vec![
+                BlockPos::new(0, 0, 0),
+                BlockPos::new(1, 0, 0),
+                BlockPos::new(1, 1, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ray_blocks_negative_direction() {
+        let blocks = ray_blocks(DVec3::new(0.5, 0.5, 0.5), DVec3::NEG_X, 2.0);
+        assert_eq!(
+            blocks,
+            vec![
+                BlockPos::new(0, 0, 0),
+                BlockPos::new(-1, 0, 0),
+                BlockPos::new(-2, 0, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ray_blocks_invalid_direction() {
+        let origin = DVec3::new(0.5, 0.5, 0.5);
+        assert!(ray_blocks(origin, DVec3::ZERO, 10.0).is_empty());
+        assert!(ray_blocks(origin, DVec3::new(f64::NAN, 0.0, 1.0), 10.0).is_empty());
+    }
+
+    #[test]
+    fn test_ray_aabb_intersection() {
+        let hitbox = Aabb::new(DVec3::new(3.0, 0.0, 0.0), DVec3::new(4.0, 1.0, 1.0));
+        let distance =
+            ray_aabb_intersection(DVec3::new(0.5, 0.5, 0.5), DVec3::X, 10.0, &hitbox).unwrap();
+        assert!((distance - 2.5).abs() < 1e-6);
+        assert!(ray_aabb_intersection(DVec3::new(0.5, 0.5, 0.5), DVec3::X, 2.0, &hitbox).is_none());
+        assert!(
+            ray_aabb_intersection(DVec3::new(0.5, 0.5, 0.5), DVec3::Z, 10.0, &hitbox).is_none()
+        );
+    }
+
     #[test]
     fn test_block_aabb_intersections1() {
         let aabb = Aabb::new(DVec3::new(0.0, 0.0, 0.0), DVec3::new(1.0, 1.0, 1.0));